@@ -0,0 +1,698 @@
+//! Runtime settings read from `BREAK_*` environment variables.
+//!
+//! Apart from the flag defaults saved by `break set-default` (see `defaults`),
+//! breakrs has no config file. The few knobs that need tuning are read from the
+//! environment instead; the daemon inherits the environment of the command that
+//! started it, so set them in your shell profile.
+
+use crate::parser;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Once;
+
+/// Default number of times a failed notification is retried
+const DEFAULT_NOTIFICATION_RETRIES: u32 = 1;
+
+/// Default delay before the first notification retry, in milliseconds
+const DEFAULT_NOTIFICATION_RETRY_DELAY_MS: u64 = 500;
+
+/// Default shortest daemon sleep between timer checks, in seconds
+const DEFAULT_DAEMON_MIN_SLEEP_SECS: u64 = 1;
+
+/// Default longest daemon sleep between timer checks, in seconds
+const DEFAULT_DAEMON_MAX_SLEEP_SECS: u64 = 3600;
+
+/// Default shortest timer duration, in seconds
+const DEFAULT_MIN_DURATION_SECS: u64 = 3;
+
+/// Default notification title for the `fixed_title` layout
+const DEFAULT_NOTIFICATION_TITLE: &str = "Break!";
+
+/// Default maximum length of a message shown in a notification, in characters
+const DEFAULT_MAX_MESSAGE_DISPLAY_LEN: usize = 200;
+
+/// Parses a setting value, falling back to `default` when it is unset or invalid.
+///
+/// Invalid values print a warning instead of failing, so a typo in the
+/// environment never stops timers from firing.
+///
+/// # Arguments
+///
+/// * `key` - The environment variable name (used in the warning)
+/// * `value` - The raw value, if set
+/// * `default` - The value used when unset or invalid
+fn parse_setting<T>(key: &str, value: Option<String>, default: T) -> T
+where
+    T: FromStr + Display,
+{
+    let Some(value) = value else {
+        return default;
+    };
+
+    match value.trim().parse() {
+        Ok(parsed) => parsed,
+        Err(_) => {
+            eprintln!(
+                "Warning: Ignoring invalid {}={:?} (using {})",
+                key, value, default
+            );
+            default
+        }
+    }
+}
+
+/// Reads a setting from the environment.
+fn env_setting<T>(key: &str, default: T) -> T
+where
+    T: FromStr + Display,
+{
+    parse_setting(key, std::env::var(key).ok(), default)
+}
+
+/// Number of times the daemon retries a notification that failed to show.
+///
+/// Read from `BREAK_NOTIFICATION_RETRIES` (default 1).
+pub fn notification_retries() -> u32 {
+    env_setting("BREAK_NOTIFICATION_RETRIES", DEFAULT_NOTIFICATION_RETRIES)
+}
+
+/// Delay before the first notification retry in milliseconds; it doubles on each
+/// further attempt.
+///
+/// Read from `BREAK_NOTIFICATION_RETRY_DELAY_MS` (default 500).
+pub fn notification_retry_delay_ms() -> u64 {
+    env_setting(
+        "BREAK_NOTIFICATION_RETRY_DELAY_MS",
+        DEFAULT_NOTIFICATION_RETRY_DELAY_MS,
+    )
+}
+
+/// Checks daemon sleep bounds, falling back to the defaults when they don't make
+/// sense (a floor below 1 second, or a floor above the ceiling).
+fn sleep_bounds(min_secs: u64, max_secs: u64) -> (u64, u64) {
+    if min_secs >= 1 && min_secs <= max_secs {
+        return (min_secs, max_secs);
+    }
+
+    eprintln!(
+        "Warning: Ignoring invalid daemon sleep bounds {}s..{}s (need 1 <= min <= max; using {}s..{}s)",
+        min_secs, max_secs, DEFAULT_DAEMON_MIN_SLEEP_SECS, DEFAULT_DAEMON_MAX_SLEEP_SECS
+    );
+    (DEFAULT_DAEMON_MIN_SLEEP_SECS, DEFAULT_DAEMON_MAX_SLEEP_SECS)
+}
+
+/// Shortest and longest time the daemon sleeps between timer checks, in seconds.
+///
+/// Read from `BREAK_DAEMON_MIN_SLEEP_SECS` (default 1) and
+/// `BREAK_DAEMON_MAX_SLEEP_SECS` (default 3600).
+pub fn daemon_sleep_bounds() -> (u64, u64) {
+    sleep_bounds(
+        env_setting("BREAK_DAEMON_MIN_SLEEP_SECS", DEFAULT_DAEMON_MIN_SLEEP_SECS),
+        env_setting("BREAK_DAEMON_MAX_SLEEP_SECS", DEFAULT_DAEMON_MAX_SLEEP_SECS),
+    )
+}
+
+/// What happens to a timer shorter than the minimum duration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShortTimerPolicy {
+    /// Lengthen the timer to the minimum
+    #[default]
+    Bump,
+    /// Run the timer in the foreground, like `--no-daemon`
+    Inline,
+}
+
+impl FromStr for ShortTimerPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bump" => Ok(Self::Bump),
+            "inline" => Ok(Self::Inline),
+            _ => Err(format!("Unknown short timer policy '{}'", s)),
+        }
+    }
+}
+
+impl Display for ShortTimerPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Bump => "bump",
+            Self::Inline => "inline",
+        })
+    }
+}
+
+/// Shortest duration a new timer may have, in seconds; shorter timers could fire
+/// before the daemon has even started.
+///
+/// Read from `BREAK_MIN_DURATION_SECS` (default 3); `0` disables the check.
+pub fn min_duration_secs() -> u64 {
+    env_setting("BREAK_MIN_DURATION_SECS", DEFAULT_MIN_DURATION_SECS)
+}
+
+/// What happens to timers shorter than [`min_duration_secs`].
+///
+/// Read from `BREAK_SHORT_TIMER_POLICY`: `bump` (default) or `inline`.
+pub fn short_timer_policy() -> ShortTimerPolicy {
+    env_setting("BREAK_SHORT_TIMER_POLICY", ShortTimerPolicy::default())
+}
+
+/// On-disk format of the timer database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DatabaseFormat {
+    /// Pretty-printed JSON, easy to read and edit by hand
+    #[default]
+    Json,
+    /// CBOR: smaller and faster to parse for large histories
+    Binary,
+}
+
+impl FromStr for DatabaseFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "binary" => Ok(Self::Binary),
+            _ => Err(format!("Unknown database format '{}'", s)),
+        }
+    }
+}
+
+impl Display for DatabaseFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Json => "json",
+            Self::Binary => "binary",
+        })
+    }
+}
+
+/// Format the database is written in; either format is read back regardless.
+///
+/// Read from `BREAK_DB_FORMAT`: `json` (default) or `binary`.
+pub fn database_format() -> DatabaseFormat {
+    env_setting("BREAK_DB_FORMAT", DatabaseFormat::default())
+}
+
+/// How the history is stored inside the database file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HistoryCompression {
+    /// A plain `history` array next to `timers`
+    #[default]
+    None,
+    /// Gzip-compressed (and base64-encoded) in `history_gz`, leaving `timers` plain
+    Gzip,
+}
+
+impl FromStr for HistoryCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "gzip" => Ok(Self::Gzip),
+            _ => Err(format!("Unknown history compression '{}'", s)),
+        }
+    }
+}
+
+impl Display for HistoryCompression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Gzip => "gzip",
+        })
+    }
+}
+
+/// How the history is written on save; compressed history is read back regardless.
+///
+/// Read from `BREAK_HISTORY_COMPRESSION`: `none` (default) or `gzip`.
+pub fn history_compression() -> HistoryCompression {
+    env_setting("BREAK_HISTORY_COMPRESSION", HistoryCompression::default())
+}
+
+/// How new timers get their numeric IDs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// Always one more than the last ID handed out, so IDs are never reused
+    #[default]
+    Sequential,
+    /// The smallest ID no active timer is using, so IDs stay short
+    LowestAvailable,
+}
+
+impl FromStr for IdStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sequential" => Ok(Self::Sequential),
+            "lowest-available" => Ok(Self::LowestAvailable),
+            _ => Err(format!("Unknown id strategy '{}'", s)),
+        }
+    }
+}
+
+impl Display for IdStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Sequential => "sequential",
+            Self::LowestAvailable => "lowest-available",
+        })
+    }
+}
+
+/// How new timers get their IDs.
+///
+/// Read from `BREAK_ID_STRATEGY`: `sequential` (default) or `lowest-available`.
+pub fn id_strategy() -> IdStrategy {
+    env_setting("BREAK_ID_STRATEGY", IdStrategy::default())
+}
+
+/// Where a timer's message goes in its notification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotificationLayout {
+    /// The message is the title, with a fixed line of text below it
+    #[default]
+    MessageAsTitle,
+    /// A fixed title (`BREAK_NOTIFICATION_TITLE`), with the message below it
+    FixedTitle,
+}
+
+impl FromStr for NotificationLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "message_as_title" => Ok(Self::MessageAsTitle),
+            "fixed_title" => Ok(Self::FixedTitle),
+            _ => Err(format!("Unknown notification layout '{}'", s)),
+        }
+    }
+}
+
+impl Display for NotificationLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::MessageAsTitle => "message_as_title",
+            Self::FixedTitle => "fixed_title",
+        })
+    }
+}
+
+/// Where timer messages go in notifications.
+///
+/// Read from `BREAK_NOTIFICATION_LAYOUT`: `message_as_title` (default) or
+/// `fixed_title`.
+pub fn notification_layout() -> NotificationLayout {
+    env_setting("BREAK_NOTIFICATION_LAYOUT", NotificationLayout::default())
+}
+
+/// Title of notifications in the `fixed_title` layout.
+///
+/// Read from `BREAK_NOTIFICATION_TITLE` (default "Break!").
+pub fn notification_title() -> String {
+    std::env::var("BREAK_NOTIFICATION_TITLE")
+        .ok()
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_NOTIFICATION_TITLE.to_string())
+}
+
+/// Maximum width of a timer message shown in a notification, in terminal columns
+/// (wide characters like emoji and CJK count as two).
+///
+/// Read from `BREAK_MAX_MESSAGE_LEN` (default 200); `0` disables truncation.
+pub fn max_message_display_len() -> Option<usize> {
+    match env_setting("BREAK_MAX_MESSAGE_LEN", DEFAULT_MAX_MESSAGE_DISPLAY_LEN) {
+        0 => None,
+        len => Some(len),
+    }
+}
+
+/// Returns the `BREAK_*` environment variables that are set, sorted by name.
+pub fn environment_settings() -> BTreeMap<String, String> {
+    std::env::vars()
+        .filter(|(key, _)| key.starts_with("BREAK_"))
+        .collect()
+}
+
+/// Parses a `tag=icon` list such as `work=briefcase,health=/path/heart.png`.
+///
+/// Entries without a tag or icon are skipped with a warning; when a tag repeats,
+/// the last icon wins.
+fn parse_tag_icons(value: &str) -> BTreeMap<String, String> {
+    let mut icons = BTreeMap::new();
+
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        match entry.split_once('=') {
+            Some((tag, icon)) if !tag.trim().is_empty() && !icon.trim().is_empty() => {
+                icons.insert(tag.trim().to_string(), icon.trim().to_string());
+            }
+            _ => eprintln!(
+                "Warning: Ignoring invalid BREAK_TAG_ICONS entry {:?} (expected tag=icon)",
+                entry
+            ),
+        }
+    }
+
+    icons
+}
+
+/// Default notification icons per tag, for timers without their own `--icon`.
+///
+/// Read from `BREAK_TAG_ICONS` as a comma-separated `tag=icon` list (unset means
+/// no defaults).
+pub fn tag_icons() -> BTreeMap<String, String> {
+    std::env::var("BREAK_TAG_ICONS")
+        .map(|value| parse_tag_icons(&value))
+        .unwrap_or_default()
+}
+
+/// Directory override from `BREAK_DATA_DIR`, if set to a non-empty path.
+pub fn data_dir_override() -> Option<PathBuf> {
+    std::env::var_os("BREAK_DATA_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Picks the data directory: the override, then the platform data directory's
+/// `break` folder, then `~/.break`, then a `break` folder in the temp directory.
+///
+/// # Returns
+///
+/// Returns the directory and whether it is a fallback (neither the override nor
+/// the platform data directory was available).
+fn resolve_data_dir(
+    data_dir_override: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    home_dir: Option<PathBuf>,
+) -> (PathBuf, bool) {
+    if let Some(dir) = data_dir_override {
+        return (dir, false);
+    }
+    if let Some(dir) = data_dir {
+        return (dir.join("break"), false);
+    }
+
+    let fallback = home_dir
+        .map(|home| home.join(".break"))
+        .unwrap_or_else(|| std::env::temp_dir().join("break"));
+    (fallback, true)
+}
+
+/// Directory holding the database (`~/.local/share/break` on Linux).
+///
+/// Read from `BREAK_DATA_DIR` when set. Where the platform has no data directory
+/// (e.g. minimal containers), falls back to `~/.break` or the temp directory and
+/// warns once.
+pub fn data_dir() -> PathBuf {
+    static FALLBACK_WARNING: Once = Once::new();
+
+    let (dir, fallback) = resolve_data_dir(data_dir_override(), dirs::data_dir(), dirs::home_dir());
+    if fallback {
+        FALLBACK_WARNING.call_once(|| {
+            eprintln!(
+                "Warning: Could not find data directory; using {} (set BREAK_DATA_DIR to choose)",
+                dir.display()
+            );
+        });
+    }
+    dir
+}
+
+/// Reads a shell command setting; unset or blank means no command.
+fn command_setting(key: &str) -> Option<String> {
+    std::env::var(key)
+        .ok()
+        .filter(|command| !command.trim().is_empty())
+}
+
+/// Shell command the daemon runs when it starts (e.g. to update a status file).
+///
+/// Read from `BREAK_ON_DAEMON_START`; unset means no hook.
+pub fn on_daemon_start() -> Option<String> {
+    command_setting("BREAK_ON_DAEMON_START")
+}
+
+/// Shell command the daemon runs just before it exits.
+///
+/// Read from `BREAK_ON_DAEMON_STOP`; unset means no hook.
+pub fn on_daemon_stop() -> Option<String> {
+    command_setting("BREAK_ON_DAEMON_STOP")
+}
+
+/// Parses an on/off setting: `1`, `true`, `yes` and `on` (any case) mean on,
+/// anything else (or unset) means off.
+fn parse_flag(value: Option<&str>) -> bool {
+    value.is_some_and(|value| {
+        matches!(
+            value.trim().to_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// Whether commands must never start the daemon (for CI and sandboxed hosts);
+/// timers are still added to and read from the database.
+///
+/// Read from `BREAK_NO_DAEMON` (e.g. `1`); unset means the daemon is started
+/// as needed.
+pub fn daemon_spawn_disabled() -> bool {
+    parse_flag(std::env::var("BREAK_NO_DAEMON").ok().as_deref())
+}
+
+/// Whether read-only commands (`list`, `status`, `top`) leave a stopped daemon
+/// alone instead of starting it; adding a timer or `break daemon` still starts it.
+///
+/// Read from `BREAK_NO_AUTOSTART` (e.g. `1`); unset means any command that finds
+/// active timers starts the daemon.
+pub fn autostart_disabled() -> bool {
+    parse_flag(std::env::var("BREAK_NO_AUTOSTART").ok().as_deref())
+}
+
+/// Whether to ask before creating a timer whose input holds several separate
+/// durations (like `5m 3h meeting`), as if `--confirm` were always given.
+///
+/// Read from `BREAK_CONFIRM_AMBIGUOUS` (e.g. `1`); unset means timers are
+/// created without asking.
+pub fn confirm_ambiguous() -> bool {
+    parse_flag(std::env::var("BREAK_CONFIRM_AMBIGUOUS").ok().as_deref())
+}
+
+/// Whether a comma between digits in timer input is a decimal point (`1,5h` as
+/// 90 minutes), as typed in many European locales.
+///
+/// Read from `BREAK_DECIMAL_COMMA` (`true` or `false`, default `false`); when
+/// off, numbers like `1,000` stay in the message as written.
+pub fn decimal_comma() -> bool {
+    env_setting("BREAK_DECIMAL_COMMA", false)
+}
+
+/// Reads a duration setting (e.g. `5m`) from the environment, in seconds.
+///
+/// Returns `None` when unset; invalid values print a warning and count as unset.
+fn duration_setting(key: &str) -> Option<u64> {
+    parsed_setting(key, parser::parse_duration)
+}
+
+/// Reads a setting with `parse`, treating invalid values like `duration_setting`.
+fn parsed_setting(key: &str, parse: fn(&str) -> Result<u64, parser::ParseError>) -> Option<u64> {
+    let value = std::env::var(key).ok()?;
+    match parse(&value) {
+        Ok(seconds) => Some(seconds),
+        Err(e) => {
+            eprintln!("Warning: Ignoring invalid {}={:?}: {}", key, value, e);
+            None
+        }
+    }
+}
+
+/// Idle time after which the daemon pauses recurring timers, in seconds.
+///
+/// Read from `BREAK_IDLE_THRESHOLD` as a duration (e.g. `5m`); unset disables
+/// idle detection. Only takes effect in builds with the `idle` feature.
+pub fn idle_threshold_secs() -> Option<u64> {
+    duration_setting("BREAK_IDLE_THRESHOLD")
+}
+
+/// How long notifications stay on screen by default, in seconds (`0` = until
+/// dismissed).
+///
+/// Read from `BREAK_NOTIFICATION_TIMEOUT` as a duration (e.g. `10s`) or `never`;
+/// unset leaves it to the notification server. Urgent timers never expire unless
+/// they set their own `--timeout`.
+pub fn notification_timeout_secs() -> Option<u64> {
+    parsed_setting("BREAK_NOTIFICATION_TIMEOUT", parser::parse_timeout)
+}
+
+/// Converts a show-seconds threshold into whole minutes; unset means always.
+fn seconds_threshold_mins(threshold_secs: Option<u64>) -> i64 {
+    threshold_secs.map_or(i64::MAX, |secs| (secs / 60) as i64)
+}
+
+/// Remaining time below which timer countdowns include seconds, in whole minutes.
+///
+/// Read from `BREAK_SHOW_SECONDS_UNDER` as a duration (e.g. `10m`); unset shows
+/// seconds for every timer.
+pub fn show_seconds_threshold_mins() -> i64 {
+    seconds_threshold_mins(duration_setting("BREAK_SHOW_SECONDS_UNDER"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_setting() {
+        assert_eq!(parse_setting("KEY", None, 1u32), 1);
+        assert_eq!(parse_setting("KEY", Some(" 3 ".to_string()), 1u32), 3);
+        assert_eq!(parse_setting("KEY", Some("lots".to_string()), 1u32), 1);
+        assert_eq!(parse_setting("KEY", Some("-2".to_string()), 500u64), 500);
+    }
+
+    #[test]
+    fn test_parse_short_timer_policy() {
+        let parse = |value: &str| {
+            parse_setting(
+                "BREAK_SHORT_TIMER_POLICY",
+                Some(value.to_string()),
+                ShortTimerPolicy::default(),
+            )
+        };
+        assert_eq!(parse("inline"), ShortTimerPolicy::Inline);
+        assert_eq!(parse(" Bump "), ShortTimerPolicy::Bump);
+        assert_eq!(parse("later"), ShortTimerPolicy::Bump);
+    }
+
+    #[test]
+    fn test_parse_flag() {
+        for on in ["1", "true", "YES", " on "] {
+            assert!(parse_flag(Some(on)), "{:?}", on);
+        }
+        for off in ["0", "false", "no", ""] {
+            assert!(!parse_flag(Some(off)), "{:?}", off);
+        }
+        assert!(!parse_flag(None));
+    }
+
+    #[test]
+    fn test_parse_database_format() {
+        let parse = |value: &str| {
+            parse_setting(
+                "BREAK_DB_FORMAT",
+                Some(value.to_string()),
+                DatabaseFormat::default(),
+            )
+        };
+        assert_eq!(parse("binary"), DatabaseFormat::Binary);
+        assert_eq!(parse("JSON"), DatabaseFormat::Json);
+        assert_eq!(parse("xml"), DatabaseFormat::Json);
+    }
+
+    #[test]
+    fn test_parse_history_compression() {
+        let parse = |value: &str| {
+            parse_setting(
+                "BREAK_HISTORY_COMPRESSION",
+                Some(value.to_string()),
+                HistoryCompression::default(),
+            )
+        };
+        assert_eq!(parse("gzip"), HistoryCompression::Gzip);
+        assert_eq!(parse("NONE"), HistoryCompression::None);
+        assert_eq!(parse("zstd"), HistoryCompression::None);
+    }
+
+    #[test]
+    fn test_parse_id_strategy() {
+        let parse = |value: &str| {
+            parse_setting(
+                "BREAK_ID_STRATEGY",
+                Some(value.to_string()),
+                IdStrategy::default(),
+            )
+        };
+        assert_eq!(parse("lowest-available"), IdStrategy::LowestAvailable);
+        assert_eq!(parse("Sequential"), IdStrategy::Sequential);
+        assert_eq!(parse("random"), IdStrategy::Sequential);
+    }
+
+    #[test]
+    fn test_parse_notification_layout() {
+        let parse = |value: &str| {
+            parse_setting(
+                "BREAK_NOTIFICATION_LAYOUT",
+                Some(value.to_string()),
+                NotificationLayout::default(),
+            )
+        };
+        assert_eq!(parse("fixed_title"), NotificationLayout::FixedTitle);
+        assert_eq!(
+            parse("Message_As_Title"),
+            NotificationLayout::MessageAsTitle
+        );
+        assert_eq!(parse("sideways"), NotificationLayout::MessageAsTitle);
+    }
+
+    #[test]
+    fn test_resolve_data_dir_without_platform_dir() {
+        let custom = PathBuf::from("/srv/break");
+        let data = PathBuf::from("/home/me/.local/share");
+        let home = PathBuf::from("/home/me");
+
+        // BREAK_DATA_DIR wins even when the platform directory is unavailable
+        assert_eq!(
+            resolve_data_dir(Some(custom.clone()), None, None),
+            (custom.clone(), false)
+        );
+        assert_eq!(
+            resolve_data_dir(Some(custom.clone()), Some(data.clone()), None),
+            (custom, false)
+        );
+
+        assert_eq!(
+            resolve_data_dir(None, Some(data.clone()), Some(home.clone())),
+            (data.join("break"), false)
+        );
+        assert_eq!(
+            resolve_data_dir(None, None, Some(home.clone())),
+            (home.join(".break"), true)
+        );
+        assert_eq!(
+            resolve_data_dir(None, None, None),
+            (std::env::temp_dir().join("break"), true)
+        );
+    }
+
+    #[test]
+    fn test_sleep_bounds() {
+        assert_eq!(sleep_bounds(1, 3600), (1, 3600));
+        assert_eq!(sleep_bounds(5, 5), (5, 5));
+        // Invalid combinations fall back to the defaults
+        assert_eq!(sleep_bounds(0, 60), (1, 3600));
+        assert_eq!(sleep_bounds(120, 60), (1, 3600));
+    }
+
+    #[test]
+    fn test_seconds_threshold_mins() {
+        assert_eq!(seconds_threshold_mins(None), i64::MAX);
+        assert_eq!(seconds_threshold_mins(Some(600)), 10);
+        assert_eq!(seconds_threshold_mins(Some(90)), 1);
+    }
+
+    #[test]
+    fn test_parse_tag_icons() {
+        let icons =
+            parse_tag_icons(" work=briefcase, health = /icons/heart.png,,bad,=x,work=mail ");
+        assert_eq!(icons.len(), 2);
+        assert_eq!(icons["work"], "mail");
+        assert_eq!(icons["health"], "/icons/heart.png");
+
+        assert!(parse_tag_icons("").is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! Reads crontab lines as recurring timers for `break import --from-cron`.
+//!
+//! Only schedules that repeat at a fixed interval map onto a recurring timer:
+//! every day at a time (`30 9 * * *`), every hour at a minute (`15 * * * *`),
+//! every few minutes (`*/20 * * * *`) or every few hours (`0 */2 * * *`), plus the
+//! `@hourly`, `@daily` and `@midnight` shorthands. Anything else (ranges, lists,
+//! particular days or months) is reported and skipped.
+
+use time::{Duration, OffsetDateTime};
+
+/// One of the minute or hour fields of a supported schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// A single value (`30`)
+    At(u8),
+    /// Every `n`th value starting at zero (`*/15`); `*` is every single value
+    Every(u8),
+}
+
+impl Field {
+    /// Parses a minute or hour field whose values run from 0 to `limit - 1`.
+    ///
+    /// Steps must divide `limit` evenly, so the schedule repeats at a fixed
+    /// interval across the hour or day boundary.
+    fn parse(text: &str, limit: u8) -> Option<Self> {
+        if text == "*" {
+            return Some(Self::Every(1));
+        }
+        if let Some(step) = text.strip_prefix("*/") {
+            return step
+                .parse()
+                .ok()
+                .filter(|step| *step > 0 && limit.is_multiple_of(*step))
+                .map(Self::Every);
+        }
+        text.parse()
+            .ok()
+            .filter(|value| *value < limit)
+            .map(Self::At)
+    }
+
+    /// Returns whether the field matches `value`.
+    fn matches(self, value: u8) -> bool {
+        match self {
+            Self::At(at) => value == at,
+            Self::Every(step) => value.is_multiple_of(step),
+        }
+    }
+}
+
+/// A cron schedule that repeats at a fixed interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CronSchedule {
+    pub minute: Field,
+    pub hour: Field,
+}
+
+impl CronSchedule {
+    /// Parses the five time fields of a crontab line.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of why the schedule can't be a recurring timer.
+    fn parse(fields: &[&str]) -> Result<Self, String> {
+        let [minute, hour, day, month, weekday] = fields else {
+            return Err("expected five time fields".to_string());
+        };
+        if [day, month, weekday].iter().any(|field| **field != "*") {
+            return Err("only schedules that run every day are supported".to_string());
+        }
+
+        let (Some(minute), Some(hour)) = (Field::parse(minute, 60), Field::parse(hour, 24)) else {
+            return Err(
+                "only single values, * and evenly dividing */N steps are supported".to_string(),
+            );
+        };
+        match (minute, hour) {
+            (_, Field::Every(1)) | (Field::At(_), _) => Ok(Self { minute, hour }),
+            _ => Err("a minute step limited to some hours doesn't repeat evenly".to_string()),
+        }
+    }
+
+    /// Returns how many seconds pass between two runs.
+    pub fn period_secs(&self) -> u64 {
+        match (self.minute, self.hour) {
+            (Field::Every(step), _) => u64::from(step) * 60,
+            (Field::At(_), Field::Every(step)) => u64::from(step) * 3600,
+            (Field::At(_), Field::At(_)) => 24 * 3600,
+        }
+    }
+
+    /// Returns the first run after `now`, on `now`'s clock.
+    pub fn next_after(&self, now: OffsetDateTime) -> OffsetDateTime {
+        let mut candidate = now
+            .replace_second(0)
+            .and_then(|now| now.replace_nanosecond(0))
+            .unwrap_or(now)
+            + Duration::minutes(1);
+        // Every supported schedule runs at least once a day
+        for _ in 0..24 * 60 {
+            if self.minute.matches(candidate.minute()) && self.hour.matches(candidate.hour()) {
+                break;
+            }
+            candidate += Duration::minutes(1);
+        }
+        candidate
+    }
+}
+
+/// A crontab line that can be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronEntry {
+    /// Line number in the crontab, starting at 1
+    pub line: usize,
+    pub schedule: CronSchedule,
+    /// The line's trailing `# comment`, else the comment line just above it,
+    /// else the command
+    pub message: String,
+}
+
+/// Splits the first `count` whitespace-separated words off `line`.
+///
+/// # Returns
+///
+/// Returns the words (fewer if the line runs out) and the rest of the line.
+fn split_fields(line: &str, count: usize) -> (Vec<&str>, &str) {
+    let mut fields = Vec::new();
+    let mut rest = line.trim_start();
+    while fields.len() < count && !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    (fields, rest)
+}
+
+/// Splits a `# comment` starting a word off the end of a command.
+///
+/// # Returns
+///
+/// Returns the command and the comment, if there is a non-empty one.
+fn split_comment(command: &str) -> (&str, Option<&str>) {
+    let start = command
+        .match_indices('#')
+        .map(|(index, _)| index)
+        .find(|index| *index == 0 || command[..*index].ends_with(char::is_whitespace));
+    match start {
+        Some(index) => (
+            command[..index].trim(),
+            Some(command[index + 1..].trim()).filter(|comment| !comment.is_empty()),
+        ),
+        None => (command.trim(), None),
+    }
+}
+
+/// Parses a crontab into the entries that can become recurring timers.
+///
+/// Blank lines, comments and variable assignments (`MAILTO=...`) are ignored.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (entries, skipped) = parse_crontab("# Stretch\n0 */2 * * * notify-send stretch\n");
+/// assert_eq!(entries[0].schedule.period_secs(), 7200);
+/// assert_eq!(entries[0].message, "Stretch");
+/// ```
+///
+/// # Returns
+///
+/// Returns the importable entries and, for every other schedule line, its line
+/// number and why it was skipped.
+pub fn parse_crontab(text: &str) -> (Vec<CronEntry>, Vec<(usize, String)>) {
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    let mut comment: Option<&str> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(text) = line.strip_prefix('#') {
+            comment = Some(text.trim()).filter(|text| !text.is_empty());
+            continue;
+        }
+        let above = comment.take();
+        let first = line.split_whitespace().next().unwrap_or("");
+        if line.is_empty() || first.contains('=') {
+            continue;
+        }
+
+        let (fields, command) = match first {
+            "@hourly" => (vec!["0", "*", "*", "*", "*"], split_fields(line, 1).1),
+            "@daily" | "@midnight" => (vec!["0", "0", "*", "*", "*"], split_fields(line, 1).1),
+            _ if first.starts_with('@') => {
+                skipped.push((index + 1, format!("{} isn't a fixed interval", first)));
+                continue;
+            }
+            _ => split_fields(line, 5),
+        };
+
+        let schedule = match CronSchedule::parse(&fields) {
+            Ok(schedule) => schedule,
+            Err(reason) => {
+                skipped.push((index + 1, reason));
+                continue;
+            }
+        };
+        let (command, trailing) = split_comment(command);
+        match trailing.or(above).unwrap_or(command) {
+            "" => skipped.push((
+                index + 1,
+                "no command or comment to use as the message".to_string(),
+            )),
+            message => entries.push(CronEntry {
+                line: index + 1,
+                schedule,
+                message: message.to_string(),
+            }),
+        }
+    }
+
+    (entries, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_parse_crontab() {
+        let crontab = "\
+MAILTO=me@example.com
+# Stand up and stretch
+0 */2 * * * notify-send stretch
+30 9 * * * ~/bin/standup.sh # Standup in 5
+*/20 * * * * notify-send eyes
+@hourly water.sh
+0 9 * * 1-5 weekdays.sh
+*/7 * * * * odd-step.sh
+";
+        let (entries, skipped) = parse_crontab(crontab);
+
+        let schedules: Vec<(usize, u64, &str)> = entries
+            .iter()
+            .map(|e| (e.line, e.schedule.period_secs(), e.message.as_str()))
+            .collect();
+        assert_eq!(
+            schedules,
+            vec![
+                (3, 7200, "Stand up and stretch"),
+                (4, 86400, "Standup in 5"),
+                (5, 1200, "notify-send eyes"),
+                (6, 3600, "water.sh"),
+            ]
+        );
+        assert_eq!(
+            skipped.iter().map(|(line, _)| *line).collect::<Vec<_>>(),
+            vec![7, 8]
+        );
+
+        // The first run follows the schedule on the local clock
+        let now = datetime!(2025-01-24 10:42:30 +2);
+        let next: Vec<OffsetDateTime> =
+            entries.iter().map(|e| e.schedule.next_after(now)).collect();
+        assert_eq!(
+            next,
+            vec![
+                datetime!(2025-01-24 12:00 +2),
+                datetime!(2025-01-25 9:30 +2),
+                datetime!(2025-01-24 11:00 +2),
+                datetime!(2025-01-24 11:00 +2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_schedules() {
+        for line in [
+            "*/15 9 * * * morning-only",
+            "0,30 * * * * list",
+            "0 8-17 * * * range",
+            "0 */5 * * * uneven",
+            "@reboot startup",
+            "5 4 * *",
+        ] {
+            let (entries, skipped) = parse_crontab(line);
+            assert!(entries.is_empty(), "{}", line);
+            assert_eq!(skipped.len(), 1, "{}", line);
+        }
+    }
+}
@@ -2,12 +2,22 @@
 //!
 //! This module provides a JSON-based database for storing active timers and
 //! timer history, with file locking to prevent corruption from concurrent access.
-
+//! The database can also be written as CBOR (`BREAK_DB_FORMAT=binary`), and its
+//! history gzip-compressed (`BREAK_HISTORY_COMPRESSION=gzip`); either is read
+//! back transparently.
+
+use crate::config;
+use crate::journal::{self, EventKind, JournalEvent};
+use crate::text;
+use base64::prelude::{BASE64_STANDARD, Engine};
+use config::{DatabaseFormat, HistoryCompression, IdStrategy};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
 use fs2::FileExt;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
@@ -18,6 +28,21 @@ const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR; // 86400
 const SECONDS_PER_YEAR: u64 = 365 * SECONDS_PER_DAY; // 31,536,000
 const DAYS_PER_TWO_YEARS: i64 = 730;
 
+/// Checks a duration against the 1-year limit every timer duration and delay has.
+///
+/// # Errors
+///
+/// Returns an error naming the limit if `seconds` is longer than a year.
+pub fn check_duration_limit(seconds: u64) -> Result<u64, String> {
+    if seconds > SECONDS_PER_YEAR {
+        return Err(format!(
+            "Duration too large (max {} days)",
+            SECONDS_PER_YEAR / SECONDS_PER_DAY
+        ));
+    }
+    Ok(seconds)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Timer {
     pub uuid: Uuid,
@@ -34,17 +59,179 @@ pub struct Timer {
     pub sound: bool,
     #[serde(default)]
     pub recurring: bool,
+    /// Complete the timer without showing a notification
+    #[serde(default)]
+    pub silent: bool,
+    /// When the timer was moved into history (`None` for active timers and old entries)
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub completed_at: Option<OffsetDateTime>,
+    /// When the daemon showed this timer's notification; guards against notifying
+    /// twice if the daemon restarts before completing the timer
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub fired_at: Option<OffsetDateTime>,
+    /// Free-form labels used to group related timers (e.g. "work", "health")
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Notification icon: a file path or a freedesktop icon name (e.g. "appointment-soon")
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// How many intervals of a recurring timer have fired so far
+    #[serde(default)]
+    pub fire_count: u32,
+    /// Recur on a fixed schedule (e.g. every day at 12:30): each interval starts at
+    /// the previous due time instead of when the timer fired, so it never drifts
+    #[serde(default)]
+    pub anchored: bool,
+    /// Whether a notification was shown for the timer's latest firing; completed
+    /// timers still `false` are resent when the daemon next starts. Entries saved by
+    /// older versions count as notified so upgrading never replays them.
+    #[serde(default = "default_notified")]
+    pub notified: bool,
+    /// How long the notification stays on screen, in seconds (`0` = until dismissed,
+    /// `None` = the configured or server default)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Fire again as urgent this many seconds after the first notification, unless
+    /// the timer is removed (acknowledged) first
+    #[serde(default)]
+    pub urgent_after_secs: Option<u64>,
+    /// Listed ahead of other timers regardless of due time (display only)
+    #[serde(default)]
+    pub pinned: bool,
+    /// Notify again this many seconds after each notification until the timer is
+    /// removed (acknowledged), instead of completing it
+    #[serde(default)]
+    pub nag_interval_secs: Option<u64>,
+    /// Messages a recurring timer shows in turn, one per fire (empty = always
+    /// `message`)
+    #[serde(default)]
+    pub messages: Vec<String>,
+    /// When the timer was paused; while set its remaining time is frozen and it
+    /// never fires
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub paused_at: Option<OffsetDateTime>,
+    /// When the daemon resumes a paused timer on its own (`None` = paused until
+    /// resumed by hand)
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub resume_at: Option<OffsetDateTime>,
+    /// Whether the notification skips the notification center (`Some(true)`,
+    /// `--transient`) or stays there (`Some(false)`, `--persist`); `None` leaves it
+    /// to the notification server. Linux only
+    #[serde(default)]
+    pub transient: Option<bool>,
+    /// Short name shown by `list` instead of the message (`--label`); the
+    /// notification still shows the full message
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+fn default_notified() -> bool {
+    true
+}
+
+/// Shortest time between two fires of a recurring timer, in seconds: half its
+/// interval, but at least one second.
+fn recurring_cooldown_secs(interval_secs: u64) -> u64 {
+    (interval_secs / 2).max(1)
+}
+
+impl Timer {
+    /// Returns the whole seconds left until the timer is due.
+    ///
+    /// The value is zero or negative once the timer is due, and truncates toward
+    /// zero like `time::Duration::whole_seconds`. Paused timers report the time
+    /// they had left when they were paused.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The reference time
+    pub fn remaining_seconds(&self, now: OffsetDateTime) -> i64 {
+        (self.due_at - self.paused_at.unwrap_or(now)).whole_seconds()
+    }
+
+    /// Returns whether the timer is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Returns which reminder the timer's next firing is, starting at 1.
+    ///
+    /// For recurring timers this counts the current interval, so the fourth time a
+    /// recurring timer fires it is reminder 4. One-time timers are always reminder 1.
+    pub fn reminder_number(&self) -> u32 {
+        self.fire_count.saturating_add(1)
+    }
+
+    /// Returns the message for the timer's next firing.
+    ///
+    /// Timers with rotating `messages` cycle through them by `fire_count`, wrapping
+    /// around after the last one; all other timers always show `message`.
+    pub fn current_message(&self) -> &str {
+        if self.messages.is_empty() {
+            &self.message
+        } else {
+            &self.messages[self.fire_count as usize % self.messages.len()]
+        }
+    }
+
+    /// Returns the name the timer is listed under: its `--label`, or else its
+    /// message shortened to `DEFAULT_LABEL_WIDTH` columns.
+    pub fn label(&self) -> String {
+        match &self.label {
+            Some(label) => label.clone(),
+            None => text::truncate_to_width(&self.message, DEFAULT_LABEL_WIDTH),
+        }
+    }
+
+    /// Returns whether the timer is due at `now` (its `due_at` is not in the future).
+    /// Paused timers are never due.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The reference time
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        !self.is_paused() && self.due_at <= now
+    }
 }
 
 /// Maximum number of active timers allowed to prevent resource exhaustion
 const MAX_TIMERS: usize = 100;
 
+/// Widest a message is listed, in terminal columns, when the timer has no label
+const DEFAULT_LABEL_WIDTH: usize = 40;
+
+/// Flag changes for `Database::set_flags`; `None` leaves a flag as it is.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FlagChanges {
+    pub urgent: Option<bool>,
+    pub sound: Option<bool>,
+    pub recurring: Option<bool>,
+    pub silent: Option<bool>,
+}
+
+/// The database as written with `BREAK_HISTORY_COMPRESSION=gzip`: active timers
+/// stay plain, so they can still be read at a glance, and the history is only
+/// stored compressed.
+#[derive(Serialize)]
+struct CompressedDatabase<'a> {
+    timers: &'a [Timer],
+    next_id: u32,
+    history_gz: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Database {
     pub timers: Vec<Timer>,
     #[serde(default)]
     pub history: Vec<Timer>,
     next_id: u32,
+    /// `history` as gzip-compressed, base64-encoded JSON, only in files saved with
+    /// `BREAK_HISTORY_COMPRESSION=gzip`; `decode` expands it back into `history`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    history_gz: Option<String>,
+    /// Events waiting to be appended to the journal when the database is saved
+    #[serde(skip)]
+    journal: RefCell<Vec<JournalEvent>>,
 }
 
 impl Database {
@@ -53,6 +240,30 @@ impl Database {
             timers: Vec::new(),
             history: Vec::new(),
             next_id: 1,
+            history_gz: None,
+            journal: RefCell::default(),
+        }
+    }
+
+    /// Queues a journal event for `timer`, written out on the next save.
+    fn record(&self, event: EventKind, timer: &Timer, at: OffsetDateTime) {
+        self.journal
+            .borrow_mut()
+            .push(JournalEvent::new(event, timer, at));
+    }
+
+    /// Takes the journal events queued since the last save.
+    pub fn take_journal(&self) -> Vec<JournalEvent> {
+        self.journal.take()
+    }
+
+    /// Appends the queued journal events next to the database at `path`.
+    ///
+    /// The journal is a record, not part of the database, so failing to write
+    /// it only prints a warning.
+    fn flush_journal(&self, path: &Path) {
+        if let Err(e) = journal::append(&journal::path_for(path), &self.take_journal()) {
+            eprintln!("Warning: Failed to write the event journal: {}", e);
         }
     }
 
@@ -99,9 +310,12 @@ impl Database {
     /// Validates and cleans the database after loading.
     ///
     /// Removes any invalid timers and ensures the database is in a consistent state.
+    /// Timers whose `pause --for` has run out are resumed, so every command sees
+    /// them running again even before the daemon wakes up to save that.
     fn validate_and_clean(&mut self) {
         let original_count = self.timers.len();
         self.timers.retain(Self::is_valid_timer);
+        self.resume_due_timers(OffsetDateTime::now_utc());
 
         let removed = original_count - self.timers.len();
         if removed > 0 {
@@ -129,36 +343,211 @@ impl Database {
     /// - File permissions prevent reading
     /// - The data directory cannot be accessed
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let mut db = Self::load_unvalidated()?;
+
+        // Validate and clean the loaded database
+        db.validate_and_clean();
+
+        Ok(db)
+    }
+
+    /// Loads the database like `load`, but without removing invalid timers.
+    ///
+    /// Used by `break validate` to report problems exactly as they are on disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as `load`.
+    pub fn load_unvalidated() -> Result<Self, Box<dyn std::error::Error>> {
         let path = Self::db_path()?;
 
         if !path.exists() {
             return Ok(Self::new());
         }
 
-        // Open file with shared lock (multiple readers allowed)
-        let file = File::open(&path)?;
-        FileExt::lock_shared(&file)?;
+        // Shared lock (multiple readers allowed)
+        let lock = Self::open_lock(&path)?;
+        FileExt::lock_shared(&lock)?;
 
-        let mut contents = String::new();
-        let mut reader = std::io::BufReader::new(&file);
-        reader.read_to_string(&mut contents)?;
+        let mut contents = Vec::new();
+        let mut reader = std::io::BufReader::new(File::open(&path)?);
+        reader.read_to_end(&mut contents)?;
+        let db = Self::decode(&contents, &path)?;
 
-        // Parse JSON with better error messages
-        let mut db: Database = serde_json::from_str(&contents).map_err(|e| {
-            format!(
-                "Database file is corrupted or invalid. Error: {}\nLocation: {}\nTo fix: Delete the file and restart.",
-                e,
-                path.display()
-            )
-        })?;
+        FileExt::unlock(&lock)?;
+        Ok(db)
+    }
 
-        // Validate and clean the loaded database
-        db.validate_and_clean();
+    /// Parses a database file in either format: binary (CBOR) is tried first,
+    /// then JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The raw file contents
+    /// * `path` - The file's path, for the error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error (with the JSON parse error, since that's the format people
+    /// edit by hand) if the contents are neither.
+    fn decode(contents: &[u8], path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut db: Self = match ciborium::from_reader(contents) {
+            Ok(db) => db,
+            // Parse JSON with better error messages
+            Err(_) => serde_json::from_slice(contents).map_err(|e| {
+                format!(
+                    "Database file is corrupted or invalid. Error: {}\nLocation: {}\nTo fix: Delete the file and restart.",
+                    e,
+                    path.display()
+                )
+            })?,
+        };
 
-        FileExt::unlock(&file)?;
+        db.expand_history()
+            .map_err(|e| format!("{} (in {})", e, path.display()))?;
         Ok(db)
     }
 
+    /// Replaces the history with the contents of `history_gz`, if the file had one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the compressed history can't be decoded.
+    fn expand_history(&mut self) -> Result<(), String> {
+        let Some(encoded) = self.history_gz.take() else {
+            return Ok(());
+        };
+        let compressed = BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("Compressed history is corrupted: {}", e))?;
+        self.history = serde_json::from_reader(GzDecoder::new(compressed.as_slice()))
+            .map_err(|e| format!("Compressed history is corrupted: {}", e))?;
+        Ok(())
+    }
+
+    /// Serializes the database in `format` for writing to disk, with the history
+    /// compressed as `compression` says.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    fn encode(
+        &self,
+        format: DatabaseFormat,
+        compression: HistoryCompression,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match compression {
+            HistoryCompression::None => Self::encode_value(self, format),
+            HistoryCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                serde_json::to_writer(&mut encoder, &self.history)?;
+                let stored = CompressedDatabase {
+                    timers: &self.timers,
+                    next_id: self.next_id,
+                    history_gz: BASE64_STANDARD.encode(encoder.finish()?),
+                };
+                Self::encode_value(&stored, format)
+            }
+        }
+    }
+
+    /// Serializes `value` as pretty-printed JSON or CBOR.
+    fn encode_value<T: Serialize>(
+        value: &T,
+        format: DatabaseFormat,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match format {
+            DatabaseFormat::Json => Ok(serde_json::to_vec_pretty(value)?),
+            DatabaseFormat::Binary => {
+                let mut contents = Vec::new();
+                ciborium::into_writer(value, &mut contents)?;
+                Ok(contents)
+            }
+        }
+    }
+
+    /// Opens (creating if needed) the lock file guarding the database at `path`.
+    ///
+    /// Writes replace `timers.json` by renaming a new file over it, so locks are
+    /// taken on a separate `timers.json.lock` that is never replaced.
+    fn open_lock(path: &Path) -> std::io::Result<File> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.with_extension("json.lock"))
+    }
+
+    /// Writes `contents` to `path` atomically.
+    ///
+    /// The data goes to `timers.json.tmp` first, is flushed and synced to disk, and
+    /// then renamed over `path`. A crash at any point leaves either the previous
+    /// file or the new one, never a partial document. The caller must hold the
+    /// exclusive lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary file cannot be written or renamed.
+    fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        let tmp_path = Self::write_temp(path, contents)?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Writes and syncs the temporary file for `write_atomic`, returning its path.
+    fn write_temp(path: &Path, contents: &[u8]) -> std::io::Result<PathBuf> {
+        let tmp_path = path.with_extension("json.tmp");
+        let file = File::create(&tmp_path)?;
+        let mut writer = std::io::BufWriter::new(&file);
+        writer.write_all(contents)?;
+        writer.flush()?;
+        file.sync_all()?;
+        Ok(tmp_path)
+    }
+
+    /// Checks the database for inconsistencies without changing it.
+    ///
+    /// Reports duplicate IDs or UUIDs among active timers, a `next_id` that doesn't
+    /// exceed every active ID, timers due before they were created, nil UUIDs, and
+    /// timers that `load` would discard as invalid.
+    ///
+    /// # Returns
+    ///
+    /// One human-readable line per problem; empty if the database is consistent.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut ids = std::collections::HashSet::new();
+        let mut uuids = std::collections::HashSet::new();
+
+        for timer in &self.timers {
+            if !ids.insert(timer.id) {
+                problems.push(format!("Duplicate timer ID #{}", timer.id));
+            }
+            if timer.uuid.is_nil() {
+                problems.push(format!("Timer #{} has a nil UUID", timer.id));
+            } else if !uuids.insert(timer.uuid) {
+                problems.push(format!("Timer #{} reuses UUID {}", timer.id, timer.uuid));
+            }
+            if timer.id >= self.next_id {
+                problems.push(format!(
+                    "Timer #{} is not below next_id {}",
+                    timer.id, self.next_id
+                ));
+            }
+            if timer.due_at < timer.created_at {
+                problems.push(format!("Timer #{} is due before it was created", timer.id));
+            }
+            if !Self::is_valid_timer(timer) {
+                problems.push(format!(
+                    "Timer #{} is invalid and would be removed on load",
+                    timer.id
+                ));
+            }
+        }
+
+        problems
+    }
+
     /// Executes a load-modify-save transaction with an exclusive lock held throughout.
     ///
     /// This ensures atomic database updates by holding an exclusive file lock for the
@@ -204,32 +593,19 @@ impl Database {
             fs::create_dir_all(parent)?;
         }
 
-        // Open/create file with exclusive lock for entire transaction
-        let file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(false) // Don't truncate - we need to read existing data first
-            .open(&path)?;
-
-        FileExt::lock_exclusive(&file)?;
+        // Exclusive lock for the entire transaction
+        let lock = Self::open_lock(&path)?;
+        FileExt::lock_exclusive(&lock)?;
 
         // Load database
-        let mut db = if file.metadata()?.len() == 0 {
-            // Empty file, create new database
+        let mut db = if fs::metadata(&path).map_or(true, |m| m.len() == 0) {
+            // Missing or empty file, create new database
             Self::new()
         } else {
-            let mut contents = String::new();
-            let mut reader = std::io::BufReader::new(&file);
-            reader.read_to_string(&mut contents)?;
-
-            let mut db: Database = serde_json::from_str(&contents).map_err(|e| {
-                format!(
-                    "Database file is corrupted or invalid. Error: {}\nLocation: {}\nTo fix: Delete the file and restart.",
-                    e,
-                    path.display()
-                )
-            })?;
+            let mut contents = Vec::new();
+            let mut reader = std::io::BufReader::new(File::open(&path)?);
+            reader.read_to_end(&mut contents)?;
+            let mut db = Self::decode(&contents, &path)?;
 
             // Validate and clean the loaded database
             db.validate_and_clean();
@@ -240,13 +616,11 @@ impl Database {
         let result = f(&mut db)?;
 
         // Save database
-        let contents = serde_json::to_string_pretty(&db)?;
-        let file = OpenOptions::new().write(true).truncate(true).open(&path)?;
-        let mut writer = std::io::BufWriter::new(&file);
-        writer.write_all(contents.as_bytes())?;
-        writer.flush()?;
+        let contents = db.encode(config::database_format(), config::history_compression())?;
+        Self::write_atomic(&path, &contents)?;
+        db.flush_journal(&path);
 
-        FileExt::unlock(&file)?;
+        FileExt::unlock(&lock)?;
 
         Ok(result)
     }
@@ -259,21 +633,15 @@ impl Database {
             fs::create_dir_all(parent)?;
         }
 
-        // Open/create file with exclusive lock (only one writer)
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&path)?;
-
-        FileExt::lock_exclusive(&file)?;
+        // Exclusive lock (only one writer)
+        let lock = Self::open_lock(&path)?;
+        FileExt::lock_exclusive(&lock)?;
 
-        let contents = serde_json::to_string_pretty(self)?;
-        let mut writer = std::io::BufWriter::new(&file);
-        writer.write_all(contents.as_bytes())?;
-        writer.flush()?;
+        let contents = self.encode(config::database_format(), config::history_compression())?;
+        Self::write_atomic(&path, &contents)?;
+        self.flush_journal(&path);
 
-        FileExt::unlock(&file)?;
+        FileExt::unlock(&lock)?;
         Ok(())
     }
 
@@ -325,7 +693,7 @@ impl Database {
 
         let timer = Timer {
             uuid: Uuid::new_v4(),
-            id: self.next_id,
+            id: self.next_available_id(config::id_strategy()),
             message,
             duration_seconds,
             created_at: now,
@@ -333,18 +701,58 @@ impl Database {
             urgent,
             sound,
             recurring,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         };
 
-        self.next_id += 1;
+        self.next_id = self.next_id.max(timer.id.saturating_add(1));
         self.timers.push(timer.clone());
         Ok(timer)
     }
 
+    /// Returns the ID the next new timer gets.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - `Sequential` hands out `next_id`, so IDs are never reused;
+    ///   `LowestAvailable` picks the smallest positive ID no active timer uses,
+    ///   reusing the IDs of removed and completed timers
+    pub fn next_available_id(&self, strategy: IdStrategy) -> u32 {
+        match strategy {
+            IdStrategy::Sequential => self.next_id,
+            IdStrategy::LowestAvailable => (1..)
+                .find(|id| !self.timers.iter().any(|t| t.id == *id))
+                .unwrap_or(self.next_id),
+        }
+    }
+
     /// Resets a timer to start over from the current time.
     ///
     /// This is primarily used for recurring timers that need to repeat after completion.
     /// The timer's `created_at` is set to now and `due_at` is recalculated based on
-    /// the original duration.
+    /// the original duration. The finished interval is counted in `fire_count`.
+    ///
+    /// Anchored timers instead advance from their previous due time in whole
+    /// intervals, skipping any that were missed, so they keep their clock time.
+    ///
+    /// Either way the next fire is at least a cooldown (half the interval, and at
+    /// least a second) away, so a timer caught up after a long sleep fires once
+    /// instead of twice in quick succession.
     ///
     /// # Arguments
     ///
@@ -355,185 +763,1947 @@ impl Database {
     /// Returns `Some(Timer)` with the updated timer if found, `None` if no timer
     /// with the given ID exists.
     pub fn reset_timer(&mut self, id: u32) -> Option<Timer> {
+        self.reset_timer_at(id, OffsetDateTime::now_utc())
+    }
+
+    /// Same as `reset_timer`, relative to an explicit `now`.
+    fn reset_timer_at(&mut self, id: u32, now: OffsetDateTime) -> Option<Timer> {
         if let Some(timer) = self.timers.iter_mut().find(|t| t.id == id) {
-            let now = OffsetDateTime::now_utc();
-            timer.due_at = now + time::Duration::seconds(timer.duration_seconds as i64);
+            let earliest = now
+                + time::Duration::seconds(recurring_cooldown_secs(timer.duration_seconds) as i64);
+            if timer.anchored {
+                let interval = time::Duration::seconds(timer.duration_seconds.max(1) as i64);
+                while timer.due_at < earliest {
+                    timer.due_at += interval;
+                }
+            } else {
+                timer.due_at =
+                    (now + time::Duration::seconds(timer.duration_seconds as i64)).max(earliest);
+            }
             timer.created_at = now;
+            // The next interval has not been notified yet
+            timer.fired_at = None;
+            timer.notified = false;
+            timer.fire_count = timer.fire_count.saturating_add(1);
             Some(timer.clone())
         } else {
             None
         }
     }
 
-    /// Removes a timer from the active timers list without adding it to history.
+    /// Escalates a timer that fired without being acknowledged.
     ///
-    /// This is used when a user explicitly cancels/removes a timer. For timers that
-    /// complete naturally, use `complete_timer()` instead to add them to history.
+    /// Instead of completing, the timer stays active as urgent and is due again
+    /// `urgent_after_secs` after `now`. Removing it before then acknowledges it.
     ///
     /// # Arguments
     ///
-    /// * `id` - The ID of the timer to remove
+    /// * `id` - The ID of the timer to escalate
+    /// * `now` - When the timer fired
     ///
     /// # Returns
     ///
-    /// Returns `Some(Timer)` containing the removed timer if found, `None` if no
-    /// timer with the given ID exists.
-    pub fn remove_timer(&mut self, id: u32) -> Option<Timer> {
-        if let Some(pos) = self.timers.iter().position(|t| t.id == id) {
-            Some(self.timers.remove(pos))
-        } else {
-            None
-        }
+    /// Returns `Some(Timer)` with the escalated timer, or `None` if no timer with the
+    /// given ID exists or it has no pending escalation. A delay too far out to
+    /// schedule (from a database written before delays were limited) completes
+    /// the timer instead, and `None` is returned.
+    pub fn escalate_timer(&mut self, id: u32, now: OffsetDateTime) -> Option<Timer> {
+        let timer = self.timers.iter_mut().find(|t| t.id == id)?;
+        let delay = timer.urgent_after_secs.take()?;
+        let Some(due_at) = i64::try_from(delay)
+            .ok()
+            .and_then(|delay| now.checked_add(time::Duration::seconds(delay)))
+        else {
+            self.complete_timer(id);
+            return None;
+        };
+        timer.due_at = due_at;
+        timer.urgent = true;
+        // The escalated notification has not been shown yet
+        timer.fired_at = None;
+        timer.notified = false;
+        Some(timer.clone())
     }
 
-    /// Completes a timer by removing it from active timers and adding it to history.
+    /// Schedules the next notification of a nagging timer that wasn't
+    /// acknowledged.
     ///
-    /// This is the proper way to handle timer expiration. The timer is removed from
-    /// the active list and added to the front of the history list for tracking purposes.
+    /// The timer stays active and is due again `nag_interval_secs` after `now`,
+    /// counting as the next reminder. A pending `urgent_after_secs` counts down with
+    /// each nag, and the nag that reaches it comes early and is urgent; every nag
+    /// after that stays urgent.
     ///
     /// # Arguments
     ///
-    /// * `id` - The ID of the timer to complete
+    /// * `id` - The ID of the timer
+    /// * `now` - When the timer fired
     ///
     /// # Returns
     ///
-    /// Returns `Some(Timer)` containing the completed timer if found, `None` if no
-    /// timer with the given ID exists.
-    pub fn complete_timer(&mut self, id: u32) -> Option<Timer> {
-        if let Some(pos) = self.timers.iter().position(|t| t.id == id) {
-            let timer = self.timers.remove(pos);
-            self.add_to_history(timer.clone());
-            Some(timer)
-        } else {
-            None
+    /// Returns `Some(Timer)` with the rescheduled timer, or `None` if no timer with
+    /// the given ID exists or it doesn't nag. A nag too far out to schedule (from a
+    /// database written before intervals were limited) completes the timer
+    /// instead, and `None` is returned.
+    pub fn nag_timer(&mut self, id: u32, now: OffsetDateTime) -> Option<Timer> {
+        let timer = self.timers.iter_mut().find(|t| t.id == id)?;
+        let mut delay = timer.nag_interval_secs?;
+        match timer.urgent_after_secs {
+            Some(urgent_after) if urgent_after <= delay => {
+                delay = urgent_after;
+                timer.urgent_after_secs = None;
+                timer.urgent = true;
+            }
+            Some(urgent_after) => timer.urgent_after_secs = Some(urgent_after - delay),
+            None => {}
         }
+        let Some(due_at) = i64::try_from(delay)
+            .ok()
+            .and_then(|delay| now.checked_add(time::Duration::seconds(delay)))
+        else {
+            self.complete_timer(id);
+            return None;
+        };
+        timer.due_at = due_at;
+        timer.fire_count = timer.fire_count.saturating_add(1);
+        // The next nag has not been shown yet
+        timer.fired_at = None;
+        timer.notified = false;
+        Some(timer.clone())
     }
 
-    /// Adds a completed timer to the history list.
-    ///
-    /// History is maintained as a most-recent-first list with a maximum of 20 entries.
-    /// When the limit is exceeded, the oldest entries are removed.
+    /// Returns the active timers due within the given number of seconds.
     ///
-    /// This allows users to see recently completed timers even if they missed the
-    /// notification.
+    /// Already expired timers are included, as is a timer due exactly at the threshold.
+    /// Paused timers are left out.
     ///
     /// # Arguments
     ///
-    /// * `timer` - The timer to add to history
-    pub fn add_to_history(&mut self, timer: Timer) {
-        const MAX_HISTORY: usize = 20;
-
-        // Add to front of history (most recent first)
-        self.history.insert(0, timer);
-
-        // Keep only last MAX_HISTORY entries
-        if self.history.len() > MAX_HISTORY {
-            self.history.truncate(MAX_HISTORY);
-        }
-    }
-
-    /// Clears all active timers.
+    /// * `seconds` - The look-ahead window in seconds
     ///
-    /// This removes all timers from the active list without adding them to history.
-    /// Used when the user wants to cancel all pending timers at once.
-    pub fn clear_all(&mut self) {
-        self.timers.clear();
-    }
-
-    /// Clears the history of completed timers.
+    /// # Returns
     ///
-    /// This removes all entries from the history list, providing a fresh start
-    /// for tracking recently completed timers.
-    pub fn clear_history(&mut self) {
-        self.history.clear();
+    /// Returns the matching timers in their stored order.
+    pub fn timers_due_within(&self, seconds: u64) -> Vec<&Timer> {
+        self.timers_due_within_at(seconds, OffsetDateTime::now_utc())
     }
 
-    /// Returns all timers that have expired (due_at is in the past).
-    ///
-    /// This is used by the daemon to identify which timers need to fire notifications.
-    /// Timers are considered expired when their `due_at` time is less than or equal
-    /// to the current UTC time.
-    ///
-    /// # Returns
-    ///
-    /// A vector containing clones of all expired timers. Returns an empty vector
-    /// if no timers have expired.
-    pub fn get_expired_timers(&self) -> Vec<Timer> {
-        let now = OffsetDateTime::now_utc();
+    /// Same as `timers_due_within`, relative to an explicit `now`.
+    fn timers_due_within_at(&self, seconds: u64, now: OffsetDateTime) -> Vec<&Timer> {
+        // A window past the last representable date covers every timer
+        let cutoff = now.saturating_add(time::Duration::seconds(
+            i64::try_from(seconds).unwrap_or(i64::MAX),
+        ));
         self.timers
             .iter()
-            .filter(|t| t.due_at <= now)
-            .cloned()
+            .filter(|t| !t.is_paused() && t.due_at <= cutoff)
             .collect()
     }
 
-    fn db_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let data_dir = dirs::data_dir().ok_or("Could not find data directory")?;
-        Ok(data_dir.join("break").join("timers.json"))
+    /// Sets the remaining time of an active timer to exactly `duration_seconds`.
+    ///
+    /// The timer becomes due `duration_seconds` from now and its duration is updated
+    /// (so recurring timers use it for later intervals too). Message and flags are
+    /// left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to edit
+    /// * `duration_seconds` - The new remaining time in seconds (max 1 year)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(Timer))` with the updated timer, or `Ok(None)` if no timer
+    /// with the given ID exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the duration exceeds 1 year.
+    pub fn set_duration(
+        &mut self,
+        id: u32,
+        duration_seconds: u64,
+    ) -> Result<Option<Timer>, String> {
+        self.set_duration_at(id, duration_seconds, OffsetDateTime::now_utc())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Same as `set_duration`, relative to an explicit `now`.
+    fn set_duration_at(
+        &mut self,
+        id: u32,
+        duration_seconds: u64,
+        now: OffsetDateTime,
+    ) -> Result<Option<Timer>, String> {
+        if duration_seconds > SECONDS_PER_YEAR {
+            return Err(format!(
+                "Duration too large (max {} days)",
+                SECONDS_PER_YEAR / SECONDS_PER_DAY
+            ));
+        }
 
-    #[test]
-    fn test_new_database() {
-        let db = Database::new();
-        assert_eq!(db.timers.len(), 0);
-        assert_eq!(db.history.len(), 0);
-        assert_eq!(db.next_id, 1);
+        let Some(timer) = self.timers.iter_mut().find(|t| t.id == id) else {
+            return Ok(None);
+        };
+        timer.duration_seconds = duration_seconds;
+        // A paused timer gets the new remaining time once it resumes
+        timer.due_at =
+            timer.paused_at.unwrap_or(now) + time::Duration::seconds(duration_seconds as i64);
+        // The new due time has not been notified yet
+        timer.fired_at = None;
+        timer.notified = false;
+        Ok(Some(timer.clone()))
     }
 
-    #[test]
-    fn test_add_timer() {
+    /// Pins an active timer to an exact instant, such as a clock time.
+    ///
+    /// The timer becomes due at `due_at` and its duration becomes the time from
+    /// its creation to then, as if it had been set for that time all along.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to pin
+    /// * `due_at` - When the timer should be due
+    /// * `now` - The reference time
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(Timer))` with the updated timer, or `Ok(None)` if no timer
+    /// with the given ID exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `due_at` isn't in the future or is more than a year
+    /// after the timer was created, or if the timer is paused or recurring (its
+    /// remaining time or interval would stop meaning anything).
+    pub fn pin_due_at(
+        &mut self,
+        id: u32,
+        due_at: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<Option<Timer>, String> {
+        let Some(timer) = self.timers.iter_mut().find(|t| t.id == id) else {
+            return Ok(None);
+        };
+        if timer.is_paused() {
+            return Err(format!("Timer #{} is paused; resume it first", id));
+        }
+        if timer.recurring {
+            return Err(format!(
+                "Timer #{} is recurring; use recur --every to change its interval",
+                id
+            ));
+        }
+        if due_at <= now {
+            return Err("The time to pin to has already passed".to_string());
+        }
+        let duration_seconds = (due_at - timer.created_at).whole_seconds().max(0) as u64;
+        if duration_seconds > SECONDS_PER_YEAR {
+            return Err(format!(
+                "Duration too large (max {} days)",
+                SECONDS_PER_YEAR / SECONDS_PER_DAY
+            ));
+        }
+
+        timer.due_at = due_at.to_offset(time::UtcOffset::UTC);
+        timer.duration_seconds = duration_seconds;
+        // The new due time has not been notified yet
+        timer.fired_at = None;
+        timer.notified = false;
+        Ok(Some(timer.clone()))
+    }
+
+    /// Marks an active timer as notified.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer that was notified
+    /// * `now` - When the notification was shown
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the timer was found, `false` otherwise.
+    pub fn mark_fired(&mut self, id: u32, now: OffsetDateTime) -> bool {
+        match self.timers.iter_mut().find(|t| t.id == id) {
+            Some(timer) => {
+                timer.fired_at = Some(now);
+                timer.notified = true;
+                self.journal
+                    .get_mut()
+                    .push(JournalEvent::new(EventKind::Fired, timer, now));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Marks every history entry of a timer as notified.
+    ///
+    /// # Arguments
+    ///
+    /// * `uuid` - The UUID of the timer whose entries were notified
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of history entries updated.
+    pub fn mark_history_notified(&mut self, uuid: Uuid) -> usize {
+        let mut marked = 0;
+        for timer in self
+            .history
+            .iter_mut()
+            .filter(|t| t.uuid == uuid && !t.notified)
+        {
+            timer.notified = true;
+            marked += 1;
+        }
+        marked
+    }
+
+    /// Replaces the message of an active timer, leaving its timing and flags untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to edit
+    /// * `message` - The new message
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(String)` with the previous message if the timer was found, `None`
+    /// if no timer with the given ID exists.
+    pub fn edit_message(&mut self, id: u32, message: String) -> Option<String> {
+        self.timers
+            .iter_mut()
+            .find(|t| t.id == id)
+            .map(|timer| std::mem::replace(&mut timer.message, message))
+    }
+
+    /// Gives an active timer a new ID, keeping its UUID and everything else.
+    ///
+    /// `next_id` is raised past `new_id` if needed, so later timers never collide
+    /// with the renumbered one.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The current ID of the timer
+    /// * `new_id` - The ID to assign
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(Timer))` with the renumbered timer, or `Ok(None)` if no timer
+    /// with the given ID exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `new_id` is 0 or already used by another active timer.
+    pub fn move_timer(&mut self, id: u32, new_id: u32) -> Result<Option<Timer>, String> {
+        if new_id == 0 {
+            return Err("Timer IDs start at 1".to_string());
+        }
+        if id != new_id && self.timers.iter().any(|t| t.id == new_id) {
+            return Err(format!("Timer #{} already exists", new_id));
+        }
+
+        let Some(timer) = self.timers.iter_mut().find(|t| t.id == id) else {
+            return Ok(None);
+        };
+        timer.id = new_id;
+        let timer = timer.clone();
+
+        self.next_id = self.next_id.max(new_id.saturating_add(1));
+        Ok(Some(timer))
+    }
+
+    /// Applies a change to an active timer.
+    ///
+    /// Used for optional settings that `add_timer` doesn't take directly.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to update
+    /// * `update` - The change to apply
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Timer)` with the updated timer if found, `None` if no timer
+    /// with the given ID exists.
+    pub fn update_timer<F>(&mut self, id: u32, update: F) -> Option<Timer>
+    where
+        F: FnOnce(&mut Timer),
+    {
+        let timer = self.timers.iter_mut().find(|t| t.id == id)?;
+        update(timer);
+        Some(timer.clone())
+    }
+
+    /// Replaces the tags of an active timer.
+    ///
+    /// Tags are trimmed and empty or duplicate tags are dropped, keeping the order
+    /// they were given in (the first tag is the timer's primary group).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to tag
+    /// * `tags` - The new tags
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Timer)` with the updated timer if found, `None` if no timer
+    /// with the given ID exists.
+    pub fn set_tags(&mut self, id: u32, tags: Vec<String>) -> Option<Timer> {
+        let mut cleaned: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !cleaned.iter().any(|t| t == tag) {
+                cleaned.push(tag.to_string());
+            }
+        }
+
+        let timer = self.timers.iter_mut().find(|t| t.id == id)?;
+        timer.tags = cleaned;
+        Some(timer.clone())
+    }
+
+    /// Sets or clears individual flags on an active timer.
+    ///
+    /// Flags left as `None` in `changes` keep their current value; the message and
+    /// due time are never touched.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to change
+    /// * `changes` - The flags to set (`Some(true)`) or clear (`Some(false)`)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Timer)` with the updated timer if found, `None` if no timer
+    /// with the given ID exists.
+    pub fn set_flags(&mut self, id: u32, changes: FlagChanges) -> Option<Timer> {
+        self.update_timer(id, |timer| {
+            let FlagChanges {
+                urgent,
+                sound,
+                recurring,
+                silent,
+            } = changes;
+            timer.urgent = urgent.unwrap_or(timer.urgent);
+            timer.sound = sound.unwrap_or(timer.sound);
+            timer.recurring = recurring.unwrap_or(timer.recurring);
+            timer.silent = silent.unwrap_or(timer.silent);
+        })
+    }
+
+    /// Turns an active timer into a recurring one, or back into a one-off timer.
+    ///
+    /// The timer keeps its current due time. With `interval_secs`, every interval
+    /// after that lasts this long instead of the timer's original duration.
+    /// Turning recurrence off also drops a fixed schedule (`anchored`), which only
+    /// applies to recurring timers.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to change
+    /// * `recurring` - Whether the timer should repeat
+    /// * `interval_secs` - The new interval in seconds (`None` keeps the duration)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(Timer))` with the updated timer, or `Ok(None)` if no timer
+    /// with the given ID exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interval is zero or longer than a year, or if the
+    /// timer nags or escalates (`--nag`, `--urgent-after`), which only one-off
+    /// timers do.
+    pub fn set_recurring(
+        &mut self,
+        id: u32,
+        recurring: bool,
+        interval_secs: Option<u64>,
+    ) -> Result<Option<Timer>, String> {
+        match interval_secs {
+            Some(0) => return Err("Interval must be at least one second".to_string()),
+            Some(seconds) if seconds > SECONDS_PER_YEAR => {
+                return Err(format!(
+                    "Interval too large (max {} days)",
+                    SECONDS_PER_YEAR / SECONDS_PER_DAY
+                ));
+            }
+            _ => {}
+        }
+
+        if recurring && let Some(timer) = self.timers.iter().find(|t| t.id == id) {
+            if timer.urgent_after_secs.is_some() {
+                return Err("--urgent-after can't be combined with --recurring".to_string());
+            }
+            if timer.nag_interval_secs.is_some() {
+                return Err("--nag can't be combined with --recurring".to_string());
+            }
+        }
+
+        Ok(self.update_timer(id, |timer| {
+            timer.recurring = recurring;
+            if let Some(seconds) = interval_secs {
+                timer.duration_seconds = seconds;
+            }
+            if !recurring {
+                timer.anchored = false;
+            }
+        }))
+    }
+
+    /// Pauses an active timer, freezing its remaining time.
+    ///
+    /// Pausing a timer that is already paused keeps its original pause time and
+    /// only replaces when it resumes.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to pause
+    /// * `now` - The time the timer is paused at
+    /// * `resume_at` - Resume the timer automatically at this time (`None` = stay
+    ///   paused until resumed by hand)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Timer)` with the paused timer if found, `None` if no timer with
+    /// the given ID exists.
+    pub fn pause_timer(
+        &mut self,
+        id: u32,
+        now: OffsetDateTime,
+        resume_at: Option<OffsetDateTime>,
+    ) -> Option<Timer> {
+        self.update_timer(id, |timer| {
+            timer.paused_at = timer.paused_at.or(Some(now));
+            timer.resume_at = resume_at;
+        })
+    }
+
+    /// Resumes a paused timer, pushing its due time back by however long it was
+    /// paused so it keeps the remaining time it had.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to resume
+    /// * `now` - The time the timer resumes at
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Timer)` with the timer if found (unchanged if it wasn't
+    /// paused), `None` if no timer with the given ID exists.
+    pub fn resume_timer(&mut self, id: u32, now: OffsetDateTime) -> Option<Timer> {
+        self.update_timer(id, |timer| {
+            if let Some(paused_at) = timer.paused_at.take() {
+                timer.due_at += now - paused_at;
+            }
+            timer.resume_at = None;
+        })
+    }
+
+    /// Resumes every paused timer whose automatic resume time has come.
+    ///
+    /// Each timer resumes as of its scheduled `resume_at`, not `now`, so a late
+    /// check (or a daemon that wasn't running) doesn't stretch the pause.
+    ///
+    /// # Returns
+    ///
+    /// Returns the resumed timers.
+    pub fn resume_due_timers(&mut self, now: OffsetDateTime) -> Vec<Timer> {
+        let due: Vec<(u32, OffsetDateTime)> = self
+            .timers
+            .iter()
+            .filter(|t| t.is_paused())
+            .filter_map(|t| t.resume_at.filter(|&at| at <= now).map(|at| (t.id, at)))
+            .collect();
+
+        due.into_iter()
+            .filter_map(|(id, at)| self.resume_timer(id, at))
+            .collect()
+    }
+
+    /// Starts a fresh active timer from a timer in history.
+    ///
+    /// The new timer gets the historical timer's message, duration, flags, tags and
+    /// notification settings, but a new ID and UUID, and is due `duration_seconds`
+    /// from now. Daily timers restart as plain recurring timers from now.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer in history; the most recent entry wins if
+    ///   several share it
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(Timer))` with the new timer, or `Ok(None)` if no history
+    /// entry has the given ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as `add_timer`.
+    pub fn replay_timer(&mut self, id: u32) -> Result<Option<Timer>, String> {
+        let Some(source) = self.history.iter().find(|t| t.id == id).cloned() else {
+            return Ok(None);
+        };
+
+        self.restart_from_history(&source).map(Some)
+    }
+
+    /// Adds a new active timer with the message, duration and settings of a
+    /// history entry (see `replay_timer`).
+    fn restart_from_history(&mut self, source: &Timer) -> Result<Timer, String> {
+        let timer = self.add_timer(
+            source.message.clone(),
+            source.duration_seconds,
+            source.urgent,
+            source.sound,
+            source.recurring,
+        )?;
+        let restarted = self.update_timer(timer.id, |t| {
+            t.silent = source.silent;
+            t.label = source.label.clone();
+            t.tags = source.tags.clone();
+            t.icon = source.icon.clone();
+            t.timeout_secs = source.timeout_secs;
+            t.urgent_after_secs = source.urgent_after_secs;
+            t.nag_interval_secs = source.nag_interval_secs;
+            t.messages = source.messages.clone();
+            t.pinned = source.pinned;
+            t.transient = source.transient;
+        });
+        Ok(restarted.unwrap_or(timer))
+    }
+
+    /// Adds time to every active timer, pushing its due time back and lengthening
+    /// its duration (so recurring timers keep the longer interval).
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - The time to add to each timer
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of timers extended.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving every timer unchanged, if a timer's duration would
+    /// grow past 1 year.
+    pub fn extend_all(&mut self, seconds: u64) -> Result<usize, String> {
+        if let Some(timer) = self
+            .timers
+            .iter()
+            .find(|t| t.duration_seconds.saturating_add(seconds) > SECONDS_PER_YEAR)
+        {
+            return Err(format!(
+                "Timer #{} would be longer than the maximum of {} days",
+                timer.id,
+                SECONDS_PER_YEAR / SECONDS_PER_DAY
+            ));
+        }
+
+        for timer in &mut self.timers {
+            timer.due_at += time::Duration::seconds(seconds as i64);
+            timer.duration_seconds += seconds;
+        }
+        Ok(self.timers.len())
+    }
+
+    /// Snoozes every timer that went off: active timers past their due time, and
+    /// timers completed at or after `completed_since` (except silent ones), are all
+    /// due again `seconds` after `now`.
+    ///
+    /// Completed timers come back as active timers with their old UUID, settings
+    /// and ID if it is free, so snoozing again never brings the same timer back
+    /// twice. Durations are left alone, so recurring timers keep their interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - The snooze length
+    /// * `completed_since` - Only history entries completed at or after this are
+    ///   snoozed
+    /// * `now` - The time the snooze starts
+    ///
+    /// # Returns
+    ///
+    /// Returns the snoozed timers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snooze is zero or longer than a year, or if too
+    /// many timers would be active; the database may then be partly changed, so
+    /// callers should discard it (as `with_transaction` does on error).
+    pub fn snooze_all(
+        &mut self,
+        seconds: u64,
+        completed_since: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<Vec<Timer>, String> {
+        if seconds == 0 || seconds > SECONDS_PER_YEAR {
+            return Err(format!(
+                "Snooze must be between 1 second and {} days",
+                SECONDS_PER_YEAR / SECONDS_PER_DAY
+            ));
+        }
+        let due_at = now + time::Duration::seconds(seconds as i64);
+        let mut snoozed = Vec::new();
+
+        for timer in self.timers.iter_mut().filter(|t| t.is_expired(now)) {
+            timer.due_at = due_at;
+            timer.fired_at = None;
+            timer.notified = false;
+            snoozed.push(timer.clone());
+        }
+
+        // History is most recent first, so the latest entry of each timer wins
+        let mut completed: Vec<Timer> = Vec::new();
+        for entry in &self.history {
+            let recent = entry.completed_at.is_some_and(|at| at >= completed_since);
+            let active = self.timers.iter().any(|t| t.uuid == entry.uuid);
+            let seen = completed.iter().any(|t| t.uuid == entry.uuid);
+            if recent && !entry.silent && !active && !seen {
+                completed.push(entry.clone());
+            }
+        }
+
+        for source in completed {
+            let timer = self.restart_from_history(&source)?;
+            let id = if self.timers.iter().any(|t| t.id == source.id) {
+                timer.id
+            } else {
+                source.id
+            };
+            let restarted = self.update_timer(timer.id, |t| {
+                t.id = id;
+                t.uuid = source.uuid;
+                t.due_at = due_at;
+                t.fire_count = source.fire_count;
+            });
+            snoozed.extend(restarted);
+        }
+
+        Ok(snoozed)
+    }
+
+    /// Removes a timer from the active timers list without adding it to history.
+    ///
+    /// This is used when a user explicitly cancels/removes a timer. For timers that
+    /// complete naturally, use `complete_timer()` instead to add them to history.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to remove
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Timer)` containing the removed timer if found, `None` if no
+    /// timer with the given ID exists.
+    pub fn remove_timer(&mut self, id: u32) -> Option<Timer> {
+        if let Some(pos) = self.timers.iter().position(|t| t.id == id) {
+            let timer = self.timers.remove(pos);
+            self.record(EventKind::Removed, &timer, OffsetDateTime::now_utc());
+            Some(timer)
+        } else {
+            None
+        }
+    }
+
+    /// Adds a timer in place of the active timer `id` (`--replace`).
+    ///
+    /// The old timer is removed without adding it to history and the new one
+    /// takes over its ID, so anything referring to `#id` now means the new timer.
+    /// Without a timer `id`, this is the same as `add_timer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to replace
+    /// * `message`, `duration_seconds`, `urgent`, `sound`, `recurring` - The new
+    ///   timer, as for `add_timer`
+    ///
+    /// # Returns
+    ///
+    /// Returns the new timer and the one it replaced, if there was one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new timer can't be added (see `add_timer`); the old
+    /// timer is then kept.
+    pub fn replace_timer(
+        &mut self,
+        id: u32,
+        message: String,
+        duration_seconds: u64,
+        urgent: bool,
+        sound: bool,
+        recurring: bool,
+    ) -> Result<(Timer, Option<Timer>), String> {
+        let Some(pos) = self.timers.iter().position(|t| t.id == id) else {
+            let timer = self.add_timer(message, duration_seconds, urgent, sound, recurring)?;
+            return Ok((timer, None));
+        };
+
+        // Taken out first so it doesn't count against the timer limit
+        let old = self.timers.remove(pos);
+        let next_id = self.next_id;
+        let timer = match self.add_timer(message, duration_seconds, urgent, sound, recurring) {
+            Ok(timer) => timer,
+            Err(e) => {
+                self.timers.insert(pos, old);
+                return Err(e);
+            }
+        };
+        self.record(EventKind::Removed, &old, OffsetDateTime::now_utc());
+        let timer = self.move_timer(timer.id, old.id)?.unwrap_or(timer);
+        // The ID the new timer briefly had is still free for the next one
+        self.next_id = next_id;
+        Ok((timer, Some(old)))
+    }
+
+    /// Removes every expired timer without adding them to history.
+    ///
+    /// Used to drop timers that expired while the daemon wasn't running, without
+    /// touching timers that are still counting down.
+    ///
+    /// # Returns
+    ///
+    /// The removed timers.
+    pub fn remove_expired_timers(&mut self) -> Vec<Timer> {
+        self.get_expired_timers()
+            .iter()
+            .filter_map(|timer| self.remove_timer(timer.id))
+            .collect()
+    }
+
+    /// Completes a timer by removing it from active timers and adding it to history.
+    ///
+    /// This is the proper way to handle timer expiration. The timer is removed from
+    /// the active list and added to the front of the history list for tracking purposes.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the timer to complete
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Timer)` containing the completed timer if found, `None` if no
+    /// timer with the given ID exists.
+    pub fn complete_timer(&mut self, id: u32) -> Option<Timer> {
+        if let Some(pos) = self.timers.iter().position(|t| t.id == id) {
+            let timer = self.timers.remove(pos);
+            self.add_to_history(timer.clone());
+            Some(timer)
+        } else {
+            None
+        }
+    }
+
+    /// Adds a completed timer to the history list.
+    ///
+    /// History is maintained as a most-recent-first list with a maximum of 20 entries.
+    /// When the limit is exceeded, the oldest entries are removed.
+    ///
+    /// This allows users to see recently completed timers even if they missed the
+    /// notification.
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - The timer to add to history (its `completed_at` is set to now)
+    pub fn add_to_history(&mut self, mut timer: Timer) {
+        const MAX_HISTORY: usize = 20;
+
+        let now = OffsetDateTime::now_utc();
+        timer.completed_at = Some(now);
+        self.record(EventKind::Completed, &timer, now);
+
+        // Add to front of history (most recent first)
+        self.history.insert(0, timer);
+
+        // Keep only last MAX_HISTORY entries
+        if self.history.len() > MAX_HISTORY {
+            self.history.truncate(MAX_HISTORY);
+        }
+    }
+
+    /// Removes history entries completed before the given cutoff.
+    ///
+    /// Entries without a `completed_at` timestamp (recorded by older versions) fall
+    /// back to their `due_at` time.
+    ///
+    /// # Arguments
+    ///
+    /// * `cutoff` - Entries completed strictly before this instant are removed
+    ///
+    /// # Returns
+    ///
+    /// The number of history entries removed.
+    pub fn purge_history(&mut self, cutoff: OffsetDateTime) -> usize {
+        let original_count = self.history.len();
+        self.history
+            .retain(|t| t.completed_at.unwrap_or(t.due_at) >= cutoff);
+        original_count - self.history.len()
+    }
+
+    /// Clears all active timers.
+    ///
+    /// This removes all timers from the active list without adding them to history.
+    /// Used when the user wants to cancel all pending timers at once.
+    pub fn clear_all(&mut self) {
+        let now = OffsetDateTime::now_utc();
+        for timer in std::mem::take(&mut self.timers) {
+            self.record(EventKind::Removed, &timer, now);
+        }
+    }
+
+    /// Clears the active timers carrying a tag, leaving all others untouched.
+    ///
+    /// Like `clear_all`, the removed timers are not added to history.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag to match (exactly, as stored by `set_tags`)
+    ///
+    /// # Returns
+    ///
+    /// The number of timers removed.
+    pub fn clear_tagged(&mut self, tag: &str) -> usize {
+        let (removed, kept) = std::mem::take(&mut self.timers)
+            .into_iter()
+            .partition::<Vec<Timer>, _>(|timer| timer.tags.iter().any(|t| t == tag));
+        self.timers = kept;
+
+        let now = OffsetDateTime::now_utc();
+        for timer in &removed {
+            self.record(EventKind::Removed, timer, now);
+        }
+        removed.len()
+    }
+
+    /// Clears the history of completed timers.
+    ///
+    /// This removes all entries from the history list, providing a fresh start
+    /// for tracking recently completed timers.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Trims the history to its `keep` most recent entries.
+    ///
+    /// # Returns
+    ///
+    /// The number of entries removed.
+    pub fn truncate_history(&mut self, keep: usize) -> usize {
+        let original_count = self.history.len();
+        // History is stored most recent first
+        self.history.truncate(keep);
+        original_count - self.history.len()
+    }
+
+    /// Returns all timers that have expired (due_at is in the past).
+    ///
+    /// This is used by the daemon to identify which timers need to fire notifications.
+    /// Timers are considered expired when their `due_at` time is less than or equal
+    /// to the current UTC time.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing clones of all expired timers. Returns an empty vector
+    /// if no timers have expired.
+    pub fn get_expired_timers(&self) -> Vec<Timer> {
+        let now = OffsetDateTime::now_utc();
+        self.timers
+            .iter()
+            .filter(|t| t.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the path of the database file (`~/.local/share/break/timers.json` on Linux,
+    /// or `timers.json` in `BREAK_DATA_DIR` when set).
+    pub fn db_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(config::data_dir().join("timers.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_database() {
+        let db = Database::new();
+        assert_eq!(db.timers.len(), 0);
+        assert_eq!(db.history.len(), 0);
+        assert_eq!(db.next_id, 1);
+    }
+
+    #[test]
+    fn test_add_timer() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Test".to_string(), 300, false, false, false)
+            .unwrap();
+
+        assert_eq!(timer.id, 1);
+        assert_eq!(timer.message, "Test");
+        assert_eq!(timer.duration_seconds, 300);
+        assert_eq!(db.timers.len(), 1);
+        assert_eq!(db.next_id, 2);
+    }
+
+    #[test]
+    fn test_next_available_id_reuses_freed_ids() {
+        let mut db = Database::new();
+        for message in ["a", "b", "c", "d"] {
+            db.add_timer(message.to_string(), 300, false, false, false)
+                .unwrap();
+        }
+        assert_eq!(db.next_available_id(IdStrategy::Sequential), 5);
+        assert_eq!(db.next_available_id(IdStrategy::LowestAvailable), 5);
+
+        // Removed and completed timers free their IDs; the smallest comes first
+        db.remove_timer(3);
+        db.complete_timer(2);
+        assert_eq!(db.next_available_id(IdStrategy::Sequential), 5);
+        assert_eq!(db.next_available_id(IdStrategy::LowestAvailable), 2);
+
+        db.remove_timer(1);
+        assert_eq!(db.next_available_id(IdStrategy::LowestAvailable), 1);
+        db.clear_all();
+        assert_eq!(db.next_available_id(IdStrategy::LowestAvailable), 1);
+        assert_eq!(db.next_available_id(IdStrategy::Sequential), 5);
+    }
+
+    #[test]
+    fn test_add_timer_max_duration() {
+        let mut db = Database::new();
+
+        // Should succeed at max duration
+        let result = db.add_timer("Max".to_string(), SECONDS_PER_YEAR, false, false, false);
+        assert!(result.is_ok());
+
+        // Should fail above max duration
+        let result = db.add_timer(
+            "Too long".to_string(),
+            SECONDS_PER_YEAR + 1,
+            false,
+            false,
+            false,
+        );
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Duration too large"));
+    }
+
+    #[test]
+    fn test_remove_timer() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Test".to_string(), 300, false, false, false)
+            .unwrap();
+
+        let removed = db.remove_timer(timer.id);
+        assert!(removed.is_some());
+        assert_eq!(removed.unwrap().id, timer.id);
+        assert_eq!(db.timers.len(), 0);
+
+        // Removing non-existent timer should return None
+        let removed = db.remove_timer(999);
+        assert!(removed.is_none());
+    }
+
+    #[test]
+    fn test_edit_message() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Old".to_string(), 300, true, false, true)
+            .unwrap();
+
+        let old = db.edit_message(timer.id, "New".to_string());
+        assert_eq!(old.as_deref(), Some("Old"));
+        assert_eq!(db.timers[0].message, "New");
+        assert_eq!(db.timers[0].due_at, timer.due_at);
+
+        assert!(db.edit_message(999, "Missing".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_update_timer() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Quiet".to_string(), 300, false, false, false)
+            .unwrap();
+
+        let updated = db.update_timer(timer.id, |t| t.silent = true).unwrap();
+        assert!(updated.silent);
+        assert!(db.timers[0].silent);
+
+        assert!(db.update_timer(999, |t| t.silent = true).is_none());
+    }
+
+    #[test]
+    fn test_reset_anchored_timer_keeps_its_clock_time() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Lunch".to_string(), SECONDS_PER_DAY, false, false, true)
+            .unwrap();
+        let lunch = time::macros::datetime!(2025-01-24 12:30:00 UTC);
+        db.update_timer(timer.id, |t| {
+            t.anchored = true;
+            t.due_at = lunch;
+        });
+
+        // Fired a few seconds late: the next one is still exactly a day later
+        let fired = db
+            .reset_timer_at(timer.id, lunch + time::Duration::seconds(3))
+            .unwrap();
+        assert_eq!(fired.due_at, lunch + time::Duration::days(1));
+
+        // Days missed while the daemon was off are skipped
+        let fired = db
+            .reset_timer_at(timer.id, lunch + time::Duration::hours(75))
+            .unwrap();
+        assert_eq!(fired.due_at, lunch + time::Duration::days(4));
+
+        // Woken just before the next interval: skip it rather than fire twice
+        db.update_timer(timer.id, |t| t.due_at = lunch);
+        let fired = db
+            .reset_timer_at(timer.id, lunch + time::Duration::hours(23))
+            .unwrap();
+        assert_eq!(fired.due_at, lunch + time::Duration::days(2));
+
+        // Regular recurring timers restart from when they fired
+        db.update_timer(timer.id, |t| t.anchored = false);
+        let now = lunch + time::Duration::days(5);
+        let fired = db.reset_timer_at(timer.id, now).unwrap();
+        assert_eq!(fired.due_at, now + time::Duration::days(1));
+    }
+
+    #[test]
+    fn test_recurring_cooldown() {
+        assert_eq!(recurring_cooldown_secs(0), 1);
+        assert_eq!(recurring_cooldown_secs(1), 1);
+        assert_eq!(recurring_cooldown_secs(60), 30);
+
+        // A zero-length interval still waits out the cooldown
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Spin".to_string(), 0, false, false, true)
+            .unwrap();
+        let now = OffsetDateTime::now_utc();
+        let reset = db.reset_timer_at(timer.id, now).unwrap();
+        assert_eq!(reset.due_at, now + time::Duration::seconds(1));
+    }
+
+    #[test]
+    fn test_problems() {
+        let mut db = Database::new();
+        for message in ["One", "Two", "Three"] {
+            db.add_timer(message.to_string(), 300, false, false, false)
+                .unwrap();
+        }
+        assert!(db.problems().is_empty());
+
+        db.timers[1].id = 1;
+        db.timers[2].uuid = db.timers[0].uuid;
+        db.timers[2].due_at = db.timers[2].created_at - time::Duration::seconds(1);
+        db.timers.push(Timer {
+            id: 9,
+            uuid: Uuid::nil(),
+            message: " ".to_string(),
+            ..db.timers[0].clone()
+        });
+
+        assert_eq!(
+            db.problems(),
+            vec![
+                "Duplicate timer ID #1".to_string(),
+                format!("Timer #3 reuses UUID {}", db.timers[0].uuid),
+                "Timer #3 is due before it was created".to_string(),
+                "Timer #9 has a nil UUID".to_string(),
+                "Timer #9 is not below next_id 4".to_string(),
+                "Timer #9 is invalid and would be removed on load".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_expired_timers() {
+        let mut db = Database::new();
+        for (message, seconds) in [("Past", 0), ("Future", 300), ("Also past", 0)] {
+            db.add_timer(message.to_string(), seconds, false, false, false)
+                .unwrap();
+        }
+        db.timers[2].due_at -= time::Duration::minutes(10);
+
+        let removed = db.remove_expired_timers();
+        let removed: Vec<&str> = removed.iter().map(|t| t.message.as_str()).collect();
+        assert_eq!(removed, vec!["Past", "Also past"]);
+
+        assert_eq!(db.timers.len(), 1);
+        assert_eq!(db.timers[0].message, "Future");
+        assert!(db.history.is_empty());
+
+        assert!(db.remove_expired_timers().is_empty());
+    }
+
+    #[test]
+    fn test_clear_tagged() {
+        let mut db = Database::new();
+        for (message, tags) in [
+            ("Standup", vec!["work"]),
+            ("Laundry", vec!["home"]),
+            ("Review", vec!["work", "focus"]),
+            ("Tea", vec![]),
+        ] {
+            let timer = db
+                .add_timer(message.to_string(), 300, false, false, false)
+                .unwrap();
+            db.set_tags(timer.id, tags.into_iter().map(String::from).collect());
+        }
+
+        assert_eq!(db.clear_tagged("work"), 2);
+        let left: Vec<&str> = db.timers.iter().map(|t| t.message.as_str()).collect();
+        assert_eq!(left, vec!["Laundry", "Tea"]);
+        assert!(db.history.is_empty());
+
+        assert_eq!(db.clear_tagged("work"), 0);
+        assert_eq!(db.timers.len(), 2);
+    }
+
+    #[test]
+    fn test_move_timer() {
+        let mut db = Database::new();
+        let first = db
+            .add_timer("First".to_string(), 300, false, false, false)
+            .unwrap();
+        db.add_timer("Second".to_string(), 300, false, false, false)
+            .unwrap();
+
+        let moved = db.move_timer(1, 10).unwrap().unwrap();
+        assert_eq!(moved.id, 10);
+        assert_eq!(moved.uuid, first.uuid);
+        assert_eq!(db.timers[0].id, 10);
+
+        // Later timers continue after the new ID
+        let next = db
+            .add_timer("Third".to_string(), 300, false, false, false)
+            .unwrap();
+        assert_eq!(next.id, 11);
+
+        // Moving to a lower, free ID leaves next_id alone
+        db.move_timer(10, 1).unwrap();
+        assert_eq!(db.next_id, 12);
+
+        assert!(db.move_timer(99, 5).unwrap().is_none());
+        assert!(db.move_timer(1, 0).is_err());
+    }
+
+    #[test]
+    fn test_replay_timer_from_history() {
         let mut db = Database::new();
-        let timer = db
-            .add_timer("Test".to_string(), 300, false, false, false)
+        let original = db
+            .add_timer("Stretch".to_string(), 600, true, true, false)
             .unwrap();
+        db.update_timer(original.id, |t| {
+            t.silent = true;
+            t.label = Some("stretch".to_string());
+            t.icon = Some("face-smile".to_string());
+            t.timeout_secs = Some(10);
+            t.nag_interval_secs = Some(30);
+        });
+        db.set_tags(original.id, vec!["health".to_string()]);
+        db.complete_timer(original.id);
+        assert!(db.timers.is_empty());
+
+        let replayed = db.replay_timer(original.id).unwrap().unwrap();
+        assert_ne!(replayed.uuid, original.uuid);
+        assert_eq!(replayed.id, 2);
+        assert_eq!(replayed.message, "Stretch");
+        assert_eq!(replayed.duration_seconds, 600);
+        assert!(replayed.urgent && replayed.sound && replayed.silent && !replayed.recurring);
+        assert_eq!(replayed.tags, vec!["health"]);
+        assert_eq!(replayed.label.as_deref(), Some("stretch"));
+        assert_eq!(replayed.icon.as_deref(), Some("face-smile"));
+        assert_eq!(replayed.timeout_secs, Some(10));
+        assert_eq!(replayed.nag_interval_secs, Some(30));
+        // A fresh timer: due a full duration from now, never fired
+        assert_eq!(replayed.remaining_seconds(replayed.created_at), 600);
+        assert!(replayed.created_at > original.created_at);
+        assert_eq!(replayed.fire_count, 0);
+        assert_eq!(replayed.completed_at, None);
 
-        assert_eq!(timer.id, 1);
-        assert_eq!(timer.message, "Test");
-        assert_eq!(timer.duration_seconds, 300);
         assert_eq!(db.timers.len(), 1);
-        assert_eq!(db.next_id, 2);
+        assert_eq!(db.history.len(), 1);
+        assert!(db.replay_timer(99).unwrap().is_none());
     }
 
     #[test]
-    fn test_add_timer_max_duration() {
+    fn test_extend_all() {
         let mut db = Database::new();
+        let once = db
+            .add_timer("Once".to_string(), 300, false, false, false)
+            .unwrap();
+        let repeat = db
+            .add_timer("Repeat".to_string(), 1800, false, false, true)
+            .unwrap();
+        let held = db
+            .add_timer("Held".to_string(), 600, false, false, false)
+            .unwrap();
+        db.pause_timer(held.id, held.created_at, None);
 
-        // Should succeed at max duration
-        let result = db.add_timer("Max".to_string(), SECONDS_PER_YEAR, false, false, false);
-        assert!(result.is_ok());
+        assert_eq!(db.extend_all(300), Ok(3));
+        for (timer, before) in db.timers.iter().zip([&once, &repeat, &held]) {
+            assert_eq!(timer.due_at, before.due_at + time::Duration::minutes(5));
+            assert_eq!(timer.duration_seconds, before.duration_seconds + 300);
+        }
+        // The paused timer has five more minutes once it resumes
+        assert_eq!(db.timers[2].remaining_seconds(held.created_at), 900);
 
-        // Should fail above max duration
-        let result = db.add_timer(
-            "Too long".to_string(),
-            SECONDS_PER_YEAR + 1,
+        // Growing past the maximum changes nothing
+        let before: Vec<_> = db.timers.iter().map(|t| t.due_at).collect();
+        assert!(db.extend_all(SECONDS_PER_YEAR).is_err());
+        assert!(db.timers.iter().map(|t| t.due_at).eq(before));
+
+        assert_eq!(Database::new().extend_all(300), Ok(0));
+    }
+
+    #[test]
+    fn test_snooze_all() {
+        let mut db = Database::new();
+        let tea = db
+            .add_timer("Tea".to_string(), 0, false, false, false)
+            .unwrap();
+        let call = db
+            .add_timer("Call".to_string(), 0, true, false, false)
+            .unwrap();
+        let later = db
+            .add_timer("Later".to_string(), 3600, false, false, false)
+            .unwrap();
+        let stretch = db
+            .add_timer("Stretch".to_string(), 0, false, false, false)
+            .unwrap();
+        let quiet = db
+            .add_timer("Quiet".to_string(), 0, false, false, false)
+            .unwrap();
+        db.timers[4].silent = true;
+        db.timers[3].label = Some("stretch".to_string());
+        let old = db
+            .add_timer("Old".to_string(), 0, false, false, false)
+            .unwrap();
+        db.complete_timer(stretch.id);
+        db.complete_timer(quiet.id);
+        db.complete_timer(old.id);
+        let now = OffsetDateTime::now_utc();
+        db.history[0].completed_at = Some(now - time::Duration::hours(2));
+
+        let snoozed = db
+            .snooze_all(600, now - time::Duration::hours(1), now)
+            .unwrap();
+        let snoozed_ids: Vec<u32> = snoozed.iter().map(|t| t.id).collect();
+        assert_eq!(snoozed_ids, vec![tea.id, call.id, stretch.id]);
+
+        // Every snoozed timer is due again after the snooze, the rest untouched
+        let due_at = now + time::Duration::seconds(600);
+        for id in [tea.id, call.id, stretch.id] {
+            let timer = db.timers.iter().find(|t| t.id == id).unwrap();
+            assert_eq!(timer.due_at, due_at, "#{}", id);
+            assert!(timer.fired_at.is_none());
+        }
+        assert_eq!(db.timers[2].due_at, later.due_at);
+        assert!(db.timers[1].urgent);
+
+        // The completed timer came back as itself, keeping its duration
+        let restarted = db.timers.iter().find(|t| t.id == stretch.id).unwrap();
+        assert_eq!(restarted.uuid, stretch.uuid);
+        assert_eq!(restarted.message, "Stretch");
+        assert_eq!(restarted.label.as_deref(), Some("stretch"));
+        assert_eq!(restarted.duration_seconds, 0);
+        assert_eq!(db.history.len(), 3);
+
+        // Snoozing again only pushes the same timers back, never duplicating them
+        let again = db
+            .snooze_all(300, now - time::Duration::hours(1), due_at)
+            .unwrap();
+        assert_eq!(again.len(), 3);
+        assert_eq!(db.timers.len(), 4);
+
+        assert!(db.snooze_all(0, now, now).is_err());
+        assert!(db.snooze_all(SECONDS_PER_YEAR + 1, now, now).is_err());
+    }
+
+    #[test]
+    fn test_move_timer_rejects_taken_id() {
+        let mut db = Database::new();
+        db.add_timer("First".to_string(), 300, false, false, false)
+            .unwrap();
+        db.add_timer("Second".to_string(), 300, false, false, false)
+            .unwrap();
+
+        let err = db.move_timer(1, 2).unwrap_err();
+        assert!(err.contains("#2 already exists"));
+        assert_eq!(db.timers[0].id, 1);
+        assert_eq!(db.timers[1].id, 2);
+    }
+
+    #[test]
+    fn test_icon_round_trips() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Standup".to_string(), 300, false, false, false)
+            .unwrap();
+        db.update_timer(timer.id, |t| t.icon = Some("appointment-soon".to_string()));
+
+        let json = serde_json::to_string(&db).unwrap();
+        let loaded: Database = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.timers[0].icon.as_deref(), Some("appointment-soon"));
+
+        // Timers saved before icons existed load without one
+        let legacy = json.replace(r#","icon":"appointment-soon""#, "");
+        let loaded: Database = serde_json::from_str(&legacy).unwrap();
+        assert_eq!(loaded.timers[0].icon, None);
+    }
+
+    #[test]
+    fn test_binary_format_round_trips() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Standup".to_string(), 300, true, false, true)
+            .unwrap();
+        db.set_tags(timer.id, vec!["work".to_string()]);
+        db.add_timer("Tea".to_string(), 60, false, false, false)
+            .unwrap();
+        db.complete_timer(2);
+        let path = Path::new("timers.json");
+
+        let binary = db
+            .encode(DatabaseFormat::Binary, HistoryCompression::None)
+            .unwrap();
+        let json = db
+            .encode(DatabaseFormat::Json, HistoryCompression::None)
+            .unwrap();
+        assert!(binary.len() < json.len());
+
+        // Either format is detected on load
+        for contents in [&binary, &json] {
+            let loaded = Database::decode(contents, path).unwrap();
+            assert_eq!(
+                serde_json::to_value(&loaded).unwrap(),
+                serde_json::to_value(&db).unwrap()
+            );
+        }
+
+        let err = Database::decode(b"not a database", path).unwrap_err();
+        assert!(err.to_string().contains("corrupted or invalid"));
+    }
+
+    #[test]
+    fn test_compressed_history_round_trips() {
+        let mut db = Database::new();
+        db.add_timer("Standup".to_string(), 300, false, false, false)
+            .unwrap();
+        let template = db
+            .add_timer("Tea".to_string(), 60, false, false, false)
+            .unwrap();
+        db.complete_timer(template.id);
+        // A history far larger than the default, as a long-lived database might hold
+        for n in 0..500 {
+            let mut entry = template.clone();
+            entry.uuid = Uuid::new_v4();
+            entry.message = format!("Break number {}", n);
+            entry.tags = vec!["health".to_string()];
+            db.history.push(entry);
+        }
+        let path = Path::new("timers.json");
+
+        let plain = db
+            .encode(DatabaseFormat::Json, HistoryCompression::None)
+            .unwrap();
+        let compressed = db
+            .encode(DatabaseFormat::Json, HistoryCompression::Gzip)
+            .unwrap();
+        assert!(compressed.len() * 4 < plain.len());
+
+        // Active timers stay readable; the history is only in `history_gz`
+        let stored: serde_json::Value = serde_json::from_slice(&compressed).unwrap();
+        assert_eq!(stored["timers"][0]["message"], "Standup");
+        assert!(stored.get("history").is_none());
+        assert!(stored["history_gz"].is_string());
+
+        let binary = db
+            .encode(DatabaseFormat::Binary, HistoryCompression::Gzip)
+            .unwrap();
+        for contents in [&compressed, &binary] {
+            let loaded = Database::decode(contents, path).unwrap();
+            assert_eq!(loaded.history.len(), 501);
+            assert!(loaded.history_gz.is_none());
+            assert_eq!(
+                serde_json::to_value(&loaded).unwrap(),
+                serde_json::to_value(&db).unwrap()
+            );
+        }
+
+        // Saving without compression writes a plain history again
+        let loaded = Database::decode(&compressed, path).unwrap();
+        let replain = loaded
+            .encode(DatabaseFormat::Json, HistoryCompression::None)
+            .unwrap();
+        assert_eq!(replain, plain);
+
+        let mut corrupted = stored;
+        corrupted["history_gz"] = "bm90IGd6aXA=".into();
+        let err = Database::decode(&serde_json::to_vec(&corrupted).unwrap(), path).unwrap_err();
+        assert!(err.to_string().contains("Compressed history is corrupted"));
+    }
+
+    #[test]
+    fn test_interrupted_write_keeps_previous_file() {
+        let root = std::env::temp_dir().join(format!("breakrs-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("timers.json");
+
+        let mut db = Database::new();
+        db.add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        let good = serde_json::to_string_pretty(&db).unwrap();
+        Database::write_atomic(&path, good.as_bytes()).unwrap();
+
+        // Crash after writing half of the next save, before the rename
+        let next = serde_json::to_string_pretty(&Database::new()).unwrap();
+        Database::write_temp(&path, &next.as_bytes()[..next.len() / 2]).unwrap();
+
+        let on_disk = fs::read_to_string(&path).unwrap();
+        assert_eq!(on_disk, good);
+        let loaded: Database = serde_json::from_str(&on_disk).unwrap();
+        assert_eq!(loaded.timers[0].message, "Tea");
+
+        // The next save replaces the stale temporary file
+        Database::write_atomic(&path, next.as_bytes()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), next);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_notified_flag() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Tea".to_string(), 0, false, false, true)
+            .unwrap();
+        assert!(!timer.notified);
+
+        db.mark_fired(timer.id, OffsetDateTime::now_utc());
+        assert!(db.timers[0].notified);
+        db.add_to_history(db.timers[0].clone());
+
+        // The next interval starts un-notified; the history entry keeps its flag
+        assert!(!db.reset_timer(timer.id).unwrap().notified);
+        assert!(db.history[0].notified);
+
+        // History saved before the flag existed counts as notified
+        let json = serde_json::to_string(&db).unwrap();
+        let legacy = json
+            .replace(r#","notified":true"#, "")
+            .replace(r#","notified":false"#, "");
+        let loaded: Database = serde_json::from_str(&legacy).unwrap();
+        assert!(loaded.history[0].notified);
+    }
+
+    #[test]
+    fn test_pause_and_resume_keep_remaining_time() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        let paused_at = timer.created_at + time::Duration::seconds(60);
+
+        let paused = db.pause_timer(timer.id, paused_at, None).unwrap();
+        assert!(paused.is_paused());
+        // Frozen with 240s left, and never due while paused
+        let much_later = paused_at + time::Duration::hours(2);
+        assert_eq!(paused.remaining_seconds(much_later), 240);
+        assert!(!paused.is_expired(much_later));
+        assert!(db.get_expired_timers().is_empty());
+
+        let resumed = db.resume_timer(timer.id, much_later).unwrap();
+        assert!(!resumed.is_paused());
+        assert_eq!(resumed.remaining_seconds(much_later), 240);
+        assert_eq!(resumed.due_at, much_later + time::Duration::seconds(240));
+
+        assert!(db.pause_timer(999, paused_at, None).is_none());
+        assert!(db.resume_timer(999, paused_at).is_none());
+    }
+
+    #[test]
+    fn test_pause_for_resumes_at_scheduled_time() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Focus".to_string(), 1500, false, false, false)
+            .unwrap();
+        let other = db
+            .add_timer("Other".to_string(), 600, false, false, false)
+            .unwrap();
+        let now = timer.created_at + time::Duration::seconds(500);
+        let resume_at = now + time::Duration::minutes(10);
+        db.pause_timer(timer.id, now, Some(resume_at));
+        db.pause_timer(other.id, now, None);
+
+        // Stored as Unix timestamps like the other times
+        let stored = serde_json::to_value(&db).unwrap();
+        assert_eq!(stored["timers"][0]["paused_at"], now.unix_timestamp());
+        assert_eq!(stored["timers"][0]["resume_at"], resume_at.unix_timestamp());
+        assert_eq!(stored["timers"][1]["resume_at"], serde_json::Value::Null);
+
+        // Not yet
+        assert!(
+            db.resume_due_timers(resume_at - time::Duration::seconds(1))
+                .is_empty()
+        );
+        assert!(db.timers[0].is_paused());
+
+        // Checked late, it still resumes as of the scheduled time with 1000s left
+        let checked = resume_at + time::Duration::seconds(30);
+        let resumed = db.resume_due_timers(checked);
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].id, timer.id);
+        assert_eq!(resumed[0].due_at, resume_at + time::Duration::seconds(1000));
+        assert_eq!(resumed[0].resume_at, None);
+
+        // Timers paused without --for stay paused
+        assert!(db.timers[1].is_paused());
+        assert!(
+            db.resume_due_timers(checked + time::Duration::days(1))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_replace_timer() {
+        let mut db = Database::new();
+        let old = db
+            .add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        db.add_timer("Walk".to_string(), 600, false, false, false)
+            .unwrap();
+
+        // The old timer is gone and the new one has its ID
+        let (timer, replaced) = db
+            .replace_timer(old.id, "Green tea".to_string(), 120, true, false, false)
+            .unwrap();
+        assert_eq!(replaced.map(|t| t.uuid), Some(old.uuid));
+        assert_eq!(timer.id, old.id);
+        assert_ne!(timer.uuid, old.uuid);
+        assert_eq!(db.timers.len(), 2);
+        assert!(db.timers.iter().all(|t| t.uuid != old.uuid));
+        let found = db.timers.iter().find(|t| t.id == old.id).unwrap();
+        assert_eq!(found.message, "Green tea");
+        assert!(found.urgent);
+        assert!(db.history.is_empty());
+        let events: Vec<EventKind> = db.take_journal().iter().map(|e| e.event).collect();
+        assert_eq!(events, vec![EventKind::Removed]);
+
+        // A missing ID just adds the timer
+        let (timer, replaced) = db
+            .replace_timer(9, "Stretch".to_string(), 60, false, false, false)
+            .unwrap();
+        assert!(replaced.is_none());
+        assert_eq!(timer.id, 3);
+        assert_eq!(db.timers.len(), 3);
+
+        // A timer that can't be added leaves the old one in place
+        assert!(
+            db.replace_timer(
+                old.id,
+                "Nap".to_string(),
+                SECONDS_PER_YEAR + 1,
+                false,
+                false,
+                false
+            )
+            .is_err()
+        );
+        assert_eq!(db.timers.len(), 3);
+        assert!(db.timers.iter().any(|t| t.message == "Green tea"));
+    }
+
+    #[test]
+    fn test_pin_due_at() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Call".to_string(), 600, false, false, false)
+            .unwrap();
+        let now = timer.created_at;
+
+        // 17:30 at +02:00, stored in UTC but the same instant
+        let target = (now + time::Duration::hours(3))
+            .to_offset(time::macros::offset!(+2))
+            .replace_time(time::macros::time!(17:30));
+        let target = if target > now {
+            target
+        } else {
+            target + time::Duration::days(1)
+        };
+        let pinned = db.pin_due_at(timer.id, target, now).unwrap().unwrap();
+        assert_eq!(pinned.due_at, target);
+        assert_eq!(
+            pinned.due_at.to_offset(time::macros::offset!(+2)).time(),
+            time::macros::time!(17:30)
+        );
+        assert_eq!(
+            pinned.duration_seconds as i64,
+            (target - timer.created_at).whole_seconds()
+        );
+
+        assert!(db.pin_due_at(timer.id, now, now).is_err());
+        assert!(db.pin_due_at(99, target, now).unwrap().is_none());
+        db.timers[0].recurring = true;
+        assert!(db.pin_due_at(timer.id, target, now).is_err());
+    }
+
+    #[test]
+    fn test_set_recurring_toggles_recurrence() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Stretch".to_string(), 600, false, false, false)
+            .unwrap();
+
+        // Recurring with its own duration as the interval, due time untouched
+        let recurring = db.set_recurring(timer.id, true, None).unwrap().unwrap();
+        assert!(recurring.recurring);
+        assert_eq!(recurring.duration_seconds, 600);
+        assert_eq!(recurring.due_at, timer.due_at);
+
+        // A new interval only changes later intervals
+        let recurring = db
+            .set_recurring(timer.id, true, Some(1800))
+            .unwrap()
+            .unwrap();
+        assert_eq!(recurring.duration_seconds, 1800);
+        assert_eq!(recurring.due_at, timer.due_at);
+
+        // Back to a one-off, dropping any fixed schedule
+        db.timers[0].anchored = true;
+        let once = db.set_recurring(timer.id, false, None).unwrap().unwrap();
+        assert!(!once.recurring);
+        assert!(!once.anchored);
+
+        assert!(db.set_recurring(timer.id, true, Some(0)).is_err());
+        assert!(
+            db.set_recurring(timer.id, true, Some(SECONDS_PER_YEAR + 1))
+                .is_err()
+        );
+        assert!(!db.timers[0].recurring);
+        assert!(db.set_recurring(999, true, None).unwrap().is_none());
+
+        // Nagging and escalating timers stay one-off
+        db.timers[0].nag_interval_secs = Some(30);
+        assert_eq!(
+            db.set_recurring(timer.id, true, None).unwrap_err(),
+            "--nag can't be combined with --recurring"
+        );
+        db.timers[0].nag_interval_secs = None;
+        db.timers[0].urgent_after_secs = Some(60);
+        assert_eq!(
+            db.set_recurring(timer.id, true, None).unwrap_err(),
+            "--urgent-after can't be combined with --recurring"
+        );
+        assert!(!db.timers[0].recurring);
+        assert!(db.set_recurring(timer.id, false, None).is_ok());
+    }
+
+    #[test]
+    fn test_set_flags_sets_and_clears_each_flag() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Test".to_string(), 300, false, false, false)
+            .unwrap();
+        let flags = |t: &Timer| (t.urgent, t.sound, t.recurring, t.silent);
+
+        let set = [
+            FlagChanges {
+                urgent: Some(true),
+                ..Default::default()
+            },
+            FlagChanges {
+                sound: Some(true),
+                ..Default::default()
+            },
+            FlagChanges {
+                recurring: Some(true),
+                ..Default::default()
+            },
+            FlagChanges {
+                silent: Some(true),
+                ..Default::default()
+            },
+        ];
+        let expected = [
+            (true, false, false, false),
+            (true, true, false, false),
+            (true, true, true, false),
+            (true, true, true, true),
+        ];
+        for (changes, expected) in set.into_iter().zip(expected) {
+            assert_eq!(flags(&db.set_flags(timer.id, changes).unwrap()), expected);
+        }
+
+        let clear = [
+            FlagChanges {
+                urgent: Some(false),
+                ..Default::default()
+            },
+            FlagChanges {
+                sound: Some(false),
+                ..Default::default()
+            },
+            FlagChanges {
+                recurring: Some(false),
+                ..Default::default()
+            },
+            FlagChanges {
+                silent: Some(false),
+                ..Default::default()
+            },
+        ];
+        let expected = [
+            (false, true, true, true),
+            (false, false, true, true),
+            (false, false, false, true),
+            (false, false, false, false),
+        ];
+        for (changes, expected) in clear.into_iter().zip(expected) {
+            assert_eq!(flags(&db.set_flags(timer.id, changes).unwrap()), expected);
+        }
+
+        // Nothing to change keeps everything, including the message and due time
+        let unchanged = db.set_flags(timer.id, FlagChanges::default()).unwrap();
+        assert_eq!(flags(&unchanged), (false, false, false, false));
+        assert_eq!(unchanged.message, "Test");
+        assert_eq!(unchanged.due_at, timer.due_at);
+        assert!(db.set_flags(999, set[0]).is_none());
+    }
+
+    #[test]
+    fn test_set_tags() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Test".to_string(), 300, false, false, false)
+            .unwrap();
+        assert!(timer.tags.is_empty());
+
+        let tags = vec![
+            " work ".to_string(),
+            "".to_string(),
+            "focus".to_string(),
+            "work".to_string(),
+        ];
+        let updated = db.set_tags(timer.id, tags).unwrap();
+        assert_eq!(updated.tags, vec!["work", "focus"]);
+        assert_eq!(db.timers[0].tags, vec!["work", "focus"]);
+
+        assert!(db.set_tags(999, vec!["work".to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_timers_due_within() {
+        let mut db = Database::new();
+        db.add_timer("Soon".to_string(), 600, false, false, false)
+            .unwrap();
+        db.add_timer("Boundary".to_string(), 900, false, false, false)
+            .unwrap();
+        db.add_timer(
+            "Later".to_string(),
+            2 * SECONDS_PER_HOUR,
             false,
             false,
             false,
-        );
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Duration too large"));
+        )
+        .unwrap();
+        db.add_timer("Expired".to_string(), 0, false, false, false)
+            .unwrap();
+
+        // Measure from the first timer's creation so the boundary is exact
+        let now = db.timers[0].created_at;
+        let mut boundary_due = now + time::Duration::seconds(900);
+        db.timers[1].due_at = boundary_due;
+
+        let messages: Vec<&str> = db
+            .timers_due_within_at(900, now)
+            .iter()
+            .map(|t| t.message.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Soon", "Boundary", "Expired"]);
+
+        // One second past the threshold is excluded
+        boundary_due += time::Duration::seconds(1);
+        db.timers[1].due_at = boundary_due;
+        let messages: Vec<&str> = db
+            .timers_due_within_at(900, now)
+            .iter()
+            .map(|t| t.message.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Soon", "Expired"]);
+
+        // A window too long to represent covers everything
+        assert_eq!(db.timers_due_within_at(u64::MAX, now).len(), 4);
     }
 
     #[test]
-    fn test_remove_timer() {
+    fn test_timer_remaining_seconds() {
         let mut db = Database::new();
         let timer = db
             .add_timer("Test".to_string(), 300, false, false, false)
             .unwrap();
+        let created = timer.created_at;
 
-        let removed = db.remove_timer(timer.id);
-        assert!(removed.is_some());
-        assert_eq!(removed.unwrap().id, timer.id);
-        assert_eq!(db.timers.len(), 0);
+        // Positive: time left
+        assert_eq!(timer.remaining_seconds(created), 300);
+        assert_eq!(
+            timer.remaining_seconds(created + time::Duration::milliseconds(100_500)),
+            199
+        );
+        assert!(!timer.is_expired(created));
 
-        // Removing non-existent timer should return None
-        let removed = db.remove_timer(999);
-        assert!(removed.is_none());
+        // Zero: due exactly now
+        assert_eq!(timer.remaining_seconds(timer.due_at), 0);
+        assert!(timer.is_expired(timer.due_at));
+
+        // Negative: overdue
+        let later = timer.due_at + time::Duration::seconds(45);
+        assert_eq!(timer.remaining_seconds(later), -45);
+        assert!(timer.is_expired(later));
+    }
+
+    #[test]
+    fn test_reset_timer_clears_fired_at() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Stretch".to_string(), 300, false, false, true)
+            .unwrap();
+
+        assert!(db.mark_fired(timer.id, OffsetDateTime::now_utc()));
+        assert!(db.timers[0].fired_at.is_some());
+
+        let reset = db.reset_timer(timer.id).unwrap();
+        assert!(reset.fired_at.is_none());
+        assert!(!db.mark_fired(999, OffsetDateTime::now_utc()));
+    }
+
+    #[test]
+    fn test_set_duration() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Old".to_string(), 300, true, false, true)
+            .unwrap();
+        // Pretend the timer was created long ago and is overdue
+        db.timers[0].due_at = timer.due_at - time::Duration::hours(2);
+
+        let now = OffsetDateTime::now_utc();
+        let updated = db.set_duration_at(timer.id, 600, now).unwrap().unwrap();
+        assert_eq!(updated.remaining_seconds(now), 600);
+        assert_eq!(updated.duration_seconds, 600);
+        assert_eq!(updated.message, "Old");
+        assert!(updated.urgent && updated.recurring);
+
+        assert!(db.set_duration(999, 600).unwrap().is_none());
+        assert!(db.set_duration(timer.id, SECONDS_PER_YEAR + 1).is_err());
     }
 
     #[test]
@@ -614,6 +2784,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         });
         assert_eq!(db.history.len(), 1);
         db.clear_all();
@@ -633,6 +2820,111 @@ mod tests {
         assert_eq!(db.history.len(), 0);
     }
 
+    #[test]
+    fn test_add_to_history_sets_completed_at() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Test".to_string(), 300, false, false, false)
+            .unwrap();
+        assert!(timer.completed_at.is_none());
+
+        db.complete_timer(timer.id);
+        assert!(db.history[0].completed_at.is_some());
+    }
+
+    #[test]
+    fn test_truncate_history_keeps_newest() {
+        let mut db = Database::new();
+
+        for id in 1..=5 {
+            let timer = db.add_timer(format!("Timer {}", id), 300, false, false, false);
+            db.add_to_history(timer.unwrap());
+        }
+
+        assert_eq!(db.truncate_history(3), 2);
+        let ids: Vec<u32> = db.history.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![5, 4, 3]);
+
+        // Keeping more than there is removes nothing
+        assert_eq!(db.truncate_history(10), 0);
+        assert_eq!(db.history.len(), 3);
+    }
+
+    #[test]
+    fn test_purge_history() {
+        let mut db = Database::new();
+        let now = OffsetDateTime::now_utc();
+
+        for (id, age_days) in [(1, 40), (2, 10), (3, 31), (4, 0)] {
+            let completed = now - time::Duration::days(age_days);
+            db.history.push(Timer {
+                uuid: Uuid::new_v4(),
+                id,
+                message: format!("Timer {}", id),
+                duration_seconds: 300,
+                created_at: completed,
+                due_at: completed,
+                urgent: false,
+                sound: false,
+                recurring: false,
+                completed_at: Some(completed),
+                tags: Vec::new(),
+                fired_at: None,
+                silent: false,
+                icon: None,
+                fire_count: 0,
+                anchored: false,
+                notified: false,
+                timeout_secs: None,
+                urgent_after_secs: None,
+                pinned: false,
+                messages: Vec::new(),
+                nag_interval_secs: None,
+                paused_at: None,
+                resume_at: None,
+                transient: None,
+                label: None,
+            });
+        }
+
+        // Legacy entry without completed_at falls back to due_at
+        let old_due = now - time::Duration::days(60);
+        db.history.push(Timer {
+            uuid: Uuid::new_v4(),
+            id: 5,
+            message: "Legacy".to_string(),
+            duration_seconds: 300,
+            created_at: old_due,
+            due_at: old_due,
+            urgent: false,
+            sound: false,
+            recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
+        });
+
+        let removed = db.purge_history(now - time::Duration::days(30));
+        assert_eq!(removed, 3);
+
+        let remaining: Vec<u32> = db.history.iter().map(|t| t.id).collect();
+        assert_eq!(remaining, vec![2, 4]);
+    }
+
     #[test]
     fn test_get_expired_timers() {
         let mut db = Database::new();
@@ -737,6 +3029,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         };
 
         assert!(!Database::is_valid_timer(&timer));
@@ -757,6 +3066,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         };
 
         assert!(!Database::is_valid_timer(&timer));
@@ -775,6 +3101,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         };
 
         assert!(!Database::is_valid_timer(&timer));
@@ -795,6 +3138,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         };
 
         assert!(!Database::is_valid_timer(&timer));
@@ -813,6 +3173,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         };
 
         assert!(Database::is_valid_timer(&timer));
@@ -834,6 +3211,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         });
 
         // Add an invalid timer (empty message)
@@ -847,6 +3241,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         });
 
         // Add another invalid timer (too old)
@@ -860,6 +3271,23 @@ mod tests {
             urgent: false,
             sound: false,
             recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
         });
 
         assert_eq!(db.timers.len(), 3);
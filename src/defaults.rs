@@ -0,0 +1,145 @@
+//! Timer flags applied to every new timer, saved by `break set-default` in
+//! `defaults.json` next to the database.
+//!
+//! This is the one setting breakrs writes for you; everything else is read from
+//! `BREAK_*` environment variables (see `config`). A missing file means no
+//! defaults, and flags given on the command line still apply on top.
+
+use crate::database::{Database, FlagChanges};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The flags every new timer starts with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Defaults {
+    #[serde(default)]
+    pub urgent: bool,
+    #[serde(default)]
+    pub sound: bool,
+    #[serde(default)]
+    pub recurring: bool,
+    #[serde(default)]
+    pub silent: bool,
+}
+
+impl Defaults {
+    /// Sets or clears the defaults named in `changes`, leaving the others as they are.
+    pub fn apply(&mut self, changes: FlagChanges) {
+        self.urgent = changes.urgent.unwrap_or(self.urgent);
+        self.sound = changes.sound.unwrap_or(self.sound);
+        self.recurring = changes.recurring.unwrap_or(self.recurring);
+        self.silent = changes.silent.unwrap_or(self.silent);
+    }
+
+    /// Returns the names of the flags that are on, e.g. `["urgent", "sound"]`.
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            ("urgent", self.urgent),
+            ("sound", self.sound),
+            ("recurring", self.recurring),
+            ("silent", self.silent),
+        ]
+        .into_iter()
+        .filter_map(|(name, on)| on.then_some(name))
+        .collect()
+    }
+}
+
+/// Returns the path of the defaults file for the database at `db_path`.
+pub fn path_for(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("defaults.json")
+}
+
+/// Returns the path of the defaults file next to the database.
+pub fn defaults_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(path_for(&Database::db_path()?))
+}
+
+/// Reads the defaults at `path`; a missing file means no defaults.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but can't be read or isn't valid JSON.
+pub fn read(path: &Path) -> Result<Defaults, Box<dyn std::error::Error>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(serde_json::from_str(&contents)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Defaults::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes `defaults` to `path`, creating its directory if needed.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be written.
+pub fn write(path: &Path, defaults: &Defaults) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(defaults)?)?;
+    Ok(())
+}
+
+/// Returns the saved defaults, for a new timer.
+///
+/// An unreadable file prints a warning and counts as no defaults, so a broken
+/// file never stops timers from being added.
+pub fn load() -> Defaults {
+    let path = match defaults_path() {
+        Ok(path) => path,
+        Err(_) => return Defaults::default(),
+    };
+    read(&path).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: Ignoring timer defaults in {}: {}",
+            path.display(),
+            e
+        );
+        Defaults::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_set_and_unset_defaults() {
+        let root = std::env::temp_dir().join(format!("breakrs-test-{}", Uuid::new_v4()));
+        let path = path_for(&root.join("timers.json"));
+
+        // No file yet: no defaults
+        let mut defaults = read(&path).unwrap();
+        assert_eq!(defaults, Defaults::default());
+
+        // `set-default --sound --urgent`
+        defaults.apply(FlagChanges {
+            sound: Some(true),
+            urgent: Some(true),
+            ..Default::default()
+        });
+        write(&path, &defaults).unwrap();
+        assert_eq!(read(&path).unwrap().names(), vec!["urgent", "sound"]);
+
+        // `set-default --no-sound` keeps urgent
+        let mut defaults = read(&path).unwrap();
+        defaults.apply(FlagChanges {
+            sound: Some(false),
+            ..Default::default()
+        });
+        write(&path, &defaults).unwrap();
+        let stored: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(stored["urgent"], true);
+        assert_eq!(stored["sound"], false);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(read(&path).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+//! Append-only journal of timer events, kept in `events.jsonl` next to the database.
+//!
+//! History only remembers the last 20 completed timers, and only once they
+//! completed. The journal records every time a timer fired, completed or was
+//! removed, one JSON object per line, so `break journal` (or any tool that reads
+//! JSON lines) can look further back. Once the file reaches `MAX_JOURNAL_BYTES`
+//! it is rotated to `events.jsonl.1`, replacing the previous rotation, so the
+//! journal never takes more than about twice that on disk.
+
+use crate::database::{Database, Timer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Size at which the journal is rotated (about ten thousand events).
+const MAX_JOURNAL_BYTES: u64 = 1024 * 1024;
+
+/// What happened to a timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    /// A notification for the timer was shown
+    Fired,
+    /// The timer moved to history (a recurring timer completes each round)
+    Completed,
+    /// The timer was removed or cleared before completing
+    Removed,
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Fired => "fired",
+            Self::Completed => "completed",
+            Self::Removed => "removed",
+        };
+        f.pad(name)
+    }
+}
+
+/// One line of the journal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEvent {
+    #[serde(with = "time::serde::timestamp")]
+    pub at: OffsetDateTime,
+    pub event: EventKind,
+    pub id: u32,
+    pub uuid: Uuid,
+    pub message: String,
+}
+
+impl JournalEvent {
+    /// Records `event` for `timer`, happening at `at`.
+    pub fn new(event: EventKind, timer: &Timer, at: OffsetDateTime) -> Self {
+        Self {
+            at,
+            event,
+            id: timer.id,
+            uuid: timer.uuid,
+            message: timer.message.clone(),
+        }
+    }
+}
+
+/// Returns the path of the journal for the database at `db_path`.
+pub fn path_for(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("events.jsonl")
+}
+
+/// Returns the path of the journal next to the database.
+pub fn journal_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(path_for(&Database::db_path()?))
+}
+
+/// Returns where a full journal is moved to when it is rotated.
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+/// Appends events to the journal, one JSON line each, rotating it first if it
+/// has grown past `MAX_JOURNAL_BYTES`.
+///
+/// Callers hold the database lock, so appends and rotation never interleave.
+///
+/// # Arguments
+///
+/// * `path` - The journal file (created if missing)
+/// * `events` - The events to append, oldest first
+///
+/// # Errors
+///
+/// Returns an error if the journal can't be rotated or written.
+pub fn append(path: &Path, events: &[JournalEvent]) -> io::Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    if fs::metadata(path).is_ok_and(|m| m.len() >= MAX_JOURNAL_BYTES) {
+        fs::rename(path, rotated_path(path))?;
+    }
+
+    let mut lines = String::new();
+    for event in events {
+        lines.push_str(&serde_json::to_string(event)?);
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(lines.as_bytes())
+}
+
+/// Reads the journal, including its rotated part, oldest event first.
+///
+/// Lines that aren't valid events (e.g. cut short by a crash mid-write) are
+/// skipped. A missing journal reads as empty.
+///
+/// # Arguments
+///
+/// * `path` - The journal file
+/// * `since` - Only events at or after this instant are returned
+///
+/// # Errors
+///
+/// Returns an error if a journal file exists but can't be read.
+pub fn read(path: &Path, since: Option<OffsetDateTime>) -> io::Result<Vec<JournalEvent>> {
+    let mut events = Vec::new();
+
+    for file in [rotated_path(path), path.to_path_buf()] {
+        let contents = match fs::read_to_string(&file) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        events.extend(
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str::<JournalEvent>(line).ok())
+                .filter(|event| since.is_none_or(|since| event.at >= since)),
+        );
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event: EventKind, id: u32, at: OffsetDateTime) -> JournalEvent {
+        JournalEvent {
+            at,
+            event,
+            id,
+            uuid: Uuid::new_v4(),
+            message: format!("Timer {}", id),
+        }
+    }
+
+    #[test]
+    fn test_append_read_and_rotate() {
+        let root = std::env::temp_dir().join(format!("breakrs-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("events.jsonl");
+        let now = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+
+        // Missing journal reads as empty
+        assert!(read(&path, None).unwrap().is_empty());
+
+        let earlier = event(EventKind::Fired, 1, now - time::Duration::hours(2));
+        let later = event(EventKind::Removed, 2, now);
+        append(&path, &[earlier.clone(), later.clone()]).unwrap();
+        append(&path, &[]).unwrap();
+
+        assert_eq!(
+            read(&path, None).unwrap(),
+            vec![earlier.clone(), later.clone()]
+        );
+        let since = now - time::Duration::hours(1);
+        assert_eq!(read(&path, Some(since)).unwrap(), vec![later.clone()]);
+
+        // Torn lines are skipped
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"at\":12")
+            .unwrap();
+        assert_eq!(read(&path, None).unwrap().len(), 2);
+
+        // A full journal is rotated before the next append, and both parts are read
+        let mut contents = fs::read_to_string(&path).unwrap();
+        contents.truncate(contents.len() - "{\"at\":12".len());
+        contents.push_str(&" ".repeat(MAX_JOURNAL_BYTES as usize));
+        contents.push('\n');
+        fs::write(&path, contents).unwrap();
+        let newest = event(EventKind::Completed, 3, now);
+        append(&path, std::slice::from_ref(&newest)).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(read(&path, None).unwrap(), vec![earlier, later, newest]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
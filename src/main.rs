@@ -1,17 +1,60 @@
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{Shell, generate};
-use std::io;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
 
+mod config;
+mod cron;
 mod daemon;
 mod database;
+mod defaults;
+mod journal;
 mod parser;
+mod text;
 
 use database::Database;
 
 // Time constants to avoid magic numbers
 const SECONDS_PER_MINUTE: i64 = 60;
 const SECONDS_PER_HOUR: i64 = 60 * SECONDS_PER_MINUTE; // 3600
+const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR; // 86400
+
+/// Placeholders understood by `--format` templates
+const TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "id",
+    "message",
+    "label",
+    "remaining",
+    "due",
+    "duration",
+    "flags",
+];
+
+/// Default templates reproducing the built-in add/list output
+const DEFAULT_ADD_FORMAT: &str = "Timer #{id} set for \"{message}\" ({duration} seconds){flags}";
+const DEFAULT_LIST_FORMAT: &str = "  #{id}: \"{label}\" - {remaining} remaining{flags}";
+const DEFAULT_EXPIRED_LIST_FORMAT: &str = "  #{id}: \"{label}\" - EXPIRED{flags}";
+
+/// Number of timers shown by `top` unless `--limit` is given
+const DEFAULT_TOP_LIMIT: usize = 10;
+
+/// How often `wait` checks whether any timers are still active, in seconds
+const WAIT_POLL_SECS: u64 = 1;
+
+/// How long a `--background` timer's notification stays on screen, in seconds
+const BACKGROUND_TIMEOUT_SECS: u64 = 3;
+
+/// How often `status --watch` refreshes, in seconds
+const STATUS_WATCH_SECS: u64 = 2;
+
+/// ANSI sequence that clears the screen and moves the cursor to the top left
+const ANSI_CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
 
 #[derive(Parser)]
 #[command(name = "breakrs")]
@@ -36,48 +79,466 @@ struct Cli {
     #[arg(long, short = 'r')]
     recurring: bool,
 
+    /// Track the timer without showing a notification when it expires
+    #[arg(long)]
+    silent: bool,
+
+    /// Ambient preset: a quiet, non-urgent notification that disappears quickly
+    #[arg(long)]
+    background: bool,
+
+    /// Ask before creating a timer when the input has several separate durations
+    #[arg(long)]
+    confirm: bool,
+
+    /// Wait in the foreground and notify inline (no daemon, nothing saved)
+    #[arg(long)]
+    no_daemon: bool,
+
+    /// Tag the timer (repeatable, e.g. --tag work --tag focus)
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Replace this timer with the new one, which takes over its ID (a missing
+    /// timer is ignored)
+    #[arg(long, value_name = "ID")]
+    replace: Option<u32>,
+
+    /// Short name to list the timer under; the notification still shows the message
+    #[arg(long, value_name = "LABEL")]
+    label: Option<String>,
+
+    /// Notification icon: a file path or an icon name (e.g. "appointment-soon")
+    #[arg(long, value_name = "ICON")]
+    icon: Option<String>,
+
+    /// How long the notification stays on screen (e.g. "10s", or "never")
+    #[arg(long, value_name = "DURATION")]
+    timeout: Option<String>,
+
+    /// Fire again as urgent after this long (e.g. "2m") unless the timer is removed
+    #[arg(long, value_name = "DURATION")]
+    urgent_after: Option<String>,
+
+    /// Notify again this often (e.g. "30s") until the timer is removed
+    #[arg(long, value_name = "DURATION")]
+    nag: Option<String>,
+
+    /// Keep the notification in the notification center (Linux)
+    #[arg(long, conflicts_with = "transient")]
+    persist: bool,
+
+    /// Keep the notification out of the notification center (Linux)
+    #[arg(long)]
+    transient: bool,
+
+    /// Messages a recurring timer shows in turn, one per fire (e.g. --messages "a" "b")
+    #[arg(long, value_name = "MESSAGE", num_args = 1..)]
+    messages: Vec<String>,
+
+    /// Allow input with only a duration (uses "Break" as the message)
+    #[arg(long)]
+    allow_empty_message: bool,
+
+    /// Also read durations written unit first, like "m5" for 5 minutes
+    #[arg(long)]
+    lenient: bool,
+
+    /// Message used for duration-only input (implies --allow-empty-message)
+    #[arg(long, value_name = "TEXT")]
+    default_message: Option<String>,
+
+    /// Exact duration in whole seconds ("-" reads it from stdin); the rest of the
+    /// input is taken literally as the message
+    #[arg(long, value_name = "N")]
+    seconds: Option<String>,
+
+    /// Template for timer lines printed by add and list
+    /// (placeholders: {id}, {message}, {label}, {remaining}, {due}, {duration}, {flags}),
+    /// or the file format for export (ics)
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    format: Option<String>,
+
+    /// Show durations in minutes instead of rolling them up into hours (90m, not 1h 30m)
+    #[arg(long, global = true)]
+    raw: bool,
+
+    /// Print --json output on a single line instead of pretty-printed
+    #[arg(long, global = true)]
+    json_compact: bool,
+
+    /// Never start the daemon; timers are still saved (same as BREAK_NO_DAEMON=1)
+    #[arg(long, global = true)]
+    no_daemon_spawn: bool,
+
+    /// Don't start the daemon from read-only commands like list and status
+    /// (same as BREAK_NO_AUTOSTART=1)
+    #[arg(long, global = true)]
+    no_autostart: bool,
+
     /// Run in daemon mode (internal use)
     #[arg(long, hide = true)]
     daemon_mode: bool,
 }
 
+/// Order of the entries shown by `history`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum HistorySort {
+    /// Most recently completed first
+    #[default]
+    Recent,
+    /// Least recently completed first
+    Oldest,
+    /// Longest duration first
+    Duration,
+}
+
+/// How `list --group-by` groups timers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ListGroup {
+    /// Under their first tag, with untagged timers last
+    Tag,
+}
+
+/// File format written by `export`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    /// iCalendar (.ics) with one event and alarm per timer
+    #[default]
+    Ics,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// List all active timers
     #[command(aliases = ["l", "li", "lis", "sh", "sho", "show", "dis", "display"])]
-    List,
+    List {
+        /// Print only the number of active timers
+        #[arg(long)]
+        count: bool,
+        /// Only show timers due within this duration (e.g. "15m"), including expired ones
+        #[arg(long, value_name = "DURATION")]
+        due_within: Option<String>,
+        /// Only show timers created at or after this time (e.g. "9:00", or "2h" for the last 2 hours)
+        #[arg(long, value_name = "TIME")]
+        created_after: Option<String>,
+        /// Only show timers created before this time (e.g. "12:00", or "30m" for over 30 minutes ago)
+        #[arg(long, value_name = "TIME")]
+        created_before: Option<String>,
+        /// Show timers under headings, ordered by due time within each
+        #[arg(long, value_enum, value_name = "KEY")]
+        group_by: Option<ListGroup>,
+        /// Print the groups as a JSON object keyed by tag ("" for untagged timers)
+        #[arg(long, requires = "group_by")]
+        json: bool,
+    },
+    /// Live view of the soonest timers, refreshed every second
+    #[command(aliases = ["t", "to", "watch"])]
+    Top {
+        /// Maximum number of timers to show
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = DEFAULT_TOP_LIMIT,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        limit: usize,
+        /// Emit one JSON object per tick with all active timers (for other tools)
+        #[arg(long)]
+        jsonl: bool,
+    },
+    /// Block until no one-off timers are active (for scripts), then exit 0
+    #[command(aliases = ["wa", "wai", "until-empty"])]
+    Wait {
+        /// Give up after this long (e.g. "30m") and exit non-zero
+        #[arg(long, value_name = "DURATION")]
+        timeout: Option<String>,
+    },
     /// Show recently completed timers
     #[command(aliases = ["h", "hi", "his", "hist", "histo", "histor"])]
-    History,
+    History {
+        /// Show at most this many entries (taken after sorting)
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+        /// Print the entries as a JSON array
+        #[arg(long)]
+        json: bool,
+        /// Order of the entries
+        #[arg(long, value_enum, default_value_t = HistorySort::Recent)]
+        sort: HistorySort,
+        /// Reverse the sort order
+        #[arg(long)]
+        reverse: bool,
+        /// Only show timers created at or after this time (e.g. "9:00", or "2h" for the last 2 hours)
+        #[arg(long, value_name = "TIME")]
+        created_after: Option<String>,
+        /// Only show timers created before this time (e.g. "12:00", or "30m" for over 30 minutes ago)
+        #[arg(long, value_name = "TIME")]
+        created_before: Option<String>,
+    },
+    /// Export active timers for other tools (e.g. a calendar)
+    #[command(aliases = ["ex", "exp", "expo", "expor"])]
+    Export {
+        /// Write the export to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Make a timer due at an exact clock time instead of after its duration
+    #[command(aliases = ["co", "con", "conv", "conve", "conver"])]
+    Convert {
+        id: u32,
+        /// The time it should be due (e.g. "17:30", "5pm", "tomorrow at 9am")
+        #[arg(long, value_name = "TIME")]
+        to_absolute: String,
+    },
+    /// Add recurring timers for the simple schedules in a crontab
+    #[command(aliases = ["im", "imp", "impo", "impor"])]
+    Import {
+        /// Crontab to read ("-" for stdin, e.g. `crontab -l | break import --from-cron -`)
+        #[arg(long, value_name = "FILE")]
+        from_cron: PathBuf,
+    },
     /// Remove a timer by ID
     #[command(aliases = ["r", "rm", "rem", "remo", "remov", "del", "dele", "delet", "delete"])]
-    Remove { id: u32 },
+    Remove {
+        #[arg(required_unless_present = "all_expired")]
+        id: Option<u32>,
+        /// Remove all expired timers instead (they are not added to history)
+        #[arg(long, conflicts_with = "id")]
+        all_expired: bool,
+    },
+    /// Edit an active timer's remaining time or flags
+    #[command(aliases = ["e", "ed", "edi"])]
+    #[command(group(clap::ArgGroup::new("changes").required(true).multiple(true)))]
+    Edit {
+        id: u32,
+        /// Set the remaining time to exactly this duration (e.g. "10m")
+        #[arg(long, value_name = "DURATION", group = "changes")]
+        duration: Option<String>,
+        /// Mark the timer urgent
+        #[arg(long, group = "changes")]
+        urgent: bool,
+        /// Stop marking the timer urgent
+        #[arg(long, group = "changes", conflicts_with = "urgent")]
+        no_urgent: bool,
+        /// Play a sound when the timer fires
+        #[arg(long, group = "changes")]
+        sound: bool,
+        /// Stop playing a sound
+        #[arg(long, group = "changes", conflicts_with = "sound")]
+        no_sound: bool,
+        /// Make the timer recurring
+        #[arg(long, group = "changes")]
+        recurring: bool,
+        /// Make the timer fire only once
+        #[arg(long, group = "changes", conflicts_with = "recurring")]
+        no_recurring: bool,
+        /// Skip the notification when the timer fires
+        #[arg(long, group = "changes")]
+        silent: bool,
+        /// Show the notification again
+        #[arg(long, group = "changes", conflicts_with = "silent")]
+        no_silent: bool,
+    },
+    /// Set the flags every new timer starts with (no flags: show them)
+    #[command(aliases = ["sd", "defaults"])]
+    SetDefault {
+        /// Make new timers urgent
+        #[arg(long)]
+        urgent: bool,
+        /// Stop making new timers urgent
+        #[arg(long, conflicts_with = "urgent")]
+        no_urgent: bool,
+        /// Play a sound for new timers
+        #[arg(long)]
+        sound: bool,
+        /// Stop playing a sound for new timers
+        #[arg(long, conflicts_with = "sound")]
+        no_sound: bool,
+        /// Make new timers recurring
+        #[arg(long)]
+        recurring: bool,
+        /// Make new timers fire only once
+        #[arg(long, conflicts_with = "recurring")]
+        no_recurring: bool,
+        /// Skip the notification for new timers
+        #[arg(long)]
+        silent: bool,
+        /// Show the notification for new timers
+        #[arg(long, conflicts_with = "silent")]
+        no_silent: bool,
+    },
+    /// Change a timer's message (flags are never touched)
+    #[command(aliases = ["ren", "rena", "renam"])]
+    Rename {
+        id: u32,
+        /// The new message, taken literally (flag-like words are kept as text)
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        message: Vec<String>,
+    },
+    /// Edit a timer's message, remaining time, and flags in $EDITOR
+    #[command(aliases = ["am", "ame", "amen"])]
+    Amend { id: u32 },
+    /// Print only a timer's remaining seconds (0 once due), for scripts and prompts
+    #[command(aliases = ["left", "remain"])]
+    Remaining {
+        id: u32,
+        /// Print it like `list` does (e.g. "4m 30s") instead of whole seconds
+        #[arg(long)]
+        human: bool,
+    },
+    /// Pin a timer so `list` shows it first (display only; firing is unchanged)
+    #[command(aliases = ["pi", "bump"])]
+    Pin { id: u32 },
+    /// Unpin a timer
+    #[command(aliases = ["unp", "unpi"])]
+    Unpin { id: u32 },
+    /// Start a timer from history again with the same message, duration and flags
+    #[command(aliases = ["rep", "repl", "repla", "again"])]
+    Replay { id: u32 },
+    /// Pause a timer, freezing its remaining time until it is resumed
+    #[command(aliases = ["pa", "pau", "paus", "hold"])]
+    Pause {
+        id: u32,
+        /// Resume automatically after this long (e.g. "10m")
+        #[arg(long = "for", value_name = "DURATION")]
+        for_duration: Option<String>,
+    },
+    /// Resume a paused timer with the time it had left
+    #[command(aliases = ["res", "resu", "resum", "unpause"])]
+    Resume { id: u32 },
+    /// Make a timer repeat, optionally at a new interval
+    #[command(aliases = ["rec", "recu", "repeat"])]
+    Recur {
+        id: u32,
+        /// Repeat at this interval instead of the timer's duration (e.g. "25m")
+        #[arg(long, value_name = "DURATION")]
+        every: Option<String>,
+    },
+    /// Stop a timer from repeating; it completes the next time it fires
+    #[command(aliases = ["norec", "once"])]
+    Norecur { id: u32 },
+    /// Add time to every active timer (e.g. "5m" when a meeting runs long)
+    #[command(aliases = ["ea", "slide"])]
+    ExtendAll { duration: String },
+    /// Snooze every timer that went off (expired, or completed recently) at once
+    #[command(aliases = ["sa", "snooze"])]
+    SnoozeAll {
+        /// How long to snooze (e.g. "10m")
+        duration: String,
+        /// Also snooze timers completed within this long (e.g. "30m")
+        #[arg(long, value_name = "DURATION", default_value = "1h")]
+        within: String,
+    },
+    /// Give a timer a new ID (fails if the ID is already in use)
+    #[command(aliases = ["mo", "mov"])]
+    Move { id: u32, new_id: u32 },
     /// Clear all timers
     #[command(aliases = ["c", "cl", "cle", "clea"])]
-    Clear,
+    Clear {
+        /// Only clear timers with this tag
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+    },
+    /// Delete history entries completed more than a given time ago
+    Purge {
+        /// Age cutoff (e.g. "30d", "12h")
+        #[arg(long, value_name = "DURATION")]
+        older_than: String,
+    },
     /// Clear history
     #[command(aliases = ["ch", "clh", "clear-h", "clear-hi", "clear-his", "clear-hist", "clear-histo", "clear-histor"])]
-    ClearHistory,
+    ClearHistory {
+        /// Keep the N most recent entries instead of clearing everything
+        #[arg(long, value_name = "N", alias = "max-history")]
+        keep: Option<usize>,
+    },
+    /// Check the database for inconsistencies (read-only)
+    #[command(aliases = ["v", "va", "val"])]
+    Validate,
+    /// Exit 0 if any timers are active and 1 if none (silent, for shell conditionals)
+    #[command(aliases = ["chk"])]
+    Check {
+        /// Also print the number of active timers
+        #[arg(long)]
+        verbose: bool,
+    },
+    /// Show timers fired, added, or removed since the last diff
+    #[command(aliases = ["di", "dif"])]
+    Diff,
+    /// Show the event journal of timers fired, completed, or removed
+    #[command(aliases = ["jo", "jou", "log", "events"])]
+    Journal {
+        /// Only show events at or after this time (e.g. "9:00", or "2h" for the last 2 hours)
+        #[arg(long, value_name = "TIME")]
+        since: Option<String>,
+    },
+    /// Print Prometheus text-format metrics (read-only)
+    #[command(aliases = ["me", "met", "metr", "metri"])]
+    Metrics,
     /// Show daemon status
     #[command(aliases = ["s", "st", "sta", "stat", "statu", "stats"])]
-    Status,
+    Status {
+        /// Print only the number of active timers
+        #[arg(long)]
+        count: bool,
+        /// Keep refreshing the status until interrupted (never restarts the daemon)
+        #[arg(long, conflicts_with = "count")]
+        watch: bool,
+        /// Instead, show when timers get completed: a count per hour of the day
+        /// (local time), from history and the journal
+        #[arg(long, conflicts_with_all = ["count", "watch"])]
+        by_hour: bool,
+        /// Print the 24 hourly counts as a JSON array
+        #[arg(long, requires = "by_hour")]
+        json: bool,
+    },
     /// Manually start the daemon
     #[command(aliases = ["d", "da", "dae", "daem", "daemo"])]
     Daemon,
+    /// Show paths, settings, and daemon state for bug reports
+    #[command(aliases = ["i", "in", "inf"])]
+    Info {
+        /// Print the information as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Generate shell completions (bash, zsh, fish, powershell)
     #[command(hide = true)]
-    Completions { shell: Shell },
+    Completions {
+        /// The shell to generate completions for (see --list)
+        #[arg(required_unless_present = "list")]
+        shell: Option<String>,
+        /// List the supported shells
+        #[arg(long)]
+        list: bool,
+        /// Write the completions to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Serializes `--json` output: pretty-printed for reading, or on a single line
+/// with `--json-compact` for pipelines.
+fn to_json<T: Serialize + ?Sized>(value: &T, compact: bool) -> serde_json::Result<String> {
+    if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    }
 }
 
 /// Formats seconds into a human-readable duration string.
 ///
 /// Shows hours and minutes for all durations, and includes seconds only if the
-/// total duration is less than the specified threshold.
+/// total duration is less than the specified threshold. With `raw`, minutes are
+/// never rolled up into hours (`--raw`).
 ///
 /// # Arguments
 ///
 /// * `seconds` - Total number of seconds to format
 /// * `show_seconds_threshold_mins` - Only show seconds if duration < this many minutes
+/// * `raw` - Show whole minutes instead of hours and minutes
 ///
 /// # Returns
 ///
@@ -86,13 +547,20 @@ enum Commands {
 /// # Examples
 ///
 /// ```ignore
-/// assert_eq!(format_duration(3665, 5), "1h 1m 5s");  // < 5 mins from hours, shows seconds
-/// assert_eq!(format_duration(360, 5), "6m");          // >= 5 mins, no seconds
-/// assert_eq!(format_duration(45, 5), "0m 45s");       // < 5 mins, shows seconds
+/// assert_eq!(format_duration(3665, 5, false), "1h 1m 5s");  // < 5 mins from hours, shows seconds
+/// assert_eq!(format_duration(360, 5, false), "6m");          // >= 5 mins, no seconds
+/// assert_eq!(format_duration(45, 5, false), "45s");          // < 5 mins, shows seconds
+/// assert_eq!(format_duration(5400, 5, true), "90m");         // raw: no hours
 /// ```
-fn format_duration(seconds: i64, show_seconds_threshold_mins: i64) -> String {
-    let hours = seconds / SECONDS_PER_HOUR;
-    let minutes = (seconds % SECONDS_PER_HOUR) / SECONDS_PER_MINUTE;
+fn format_duration(seconds: i64, show_seconds_threshold_mins: i64, raw: bool) -> String {
+    let (hours, minutes) = if raw {
+        (0, seconds / SECONDS_PER_MINUTE)
+    } else {
+        (
+            seconds / SECONDS_PER_HOUR,
+            (seconds % SECONDS_PER_HOUR) / SECONDS_PER_MINUTE,
+        )
+    };
     let secs = seconds % SECONDS_PER_MINUTE;
 
     let mut parts = Vec::new();
@@ -130,27 +598,204 @@ fn format_duration(seconds: i64, show_seconds_threshold_mins: i64) -> String {
 /// assert_eq!(format_flags(&timer), " [urgent]");
 /// ```
 fn format_flags(timer: &database::Timer) -> String {
-    if !timer.urgent && !timer.sound && !timer.recurring {
+    let escalating = timer.urgent_after_secs.is_some();
+    let nagging = timer.nag_interval_secs.is_some();
+    if !timer.urgent
+        && !timer.sound
+        && !timer.recurring
+        && !timer.silent
+        && !escalating
+        && !nagging
+        && !timer.pinned
+        && !timer.is_paused()
+    {
         return String::new();
     }
 
     let mut flags = Vec::new();
+    if timer.is_paused() {
+        flags.push("paused");
+    }
     if timer.urgent {
         flags.push("urgent");
     }
+    if escalating {
+        flags.push("escalating");
+    }
+    if nagging {
+        flags.push("nagging");
+    }
+    if timer.pinned {
+        flags.push("pinned");
+    }
     if timer.sound {
         flags.push("sound");
     }
     if timer.recurring {
         flags.push("recurring");
     }
+    if timer.silent {
+        flags.push("silent");
+    }
 
     format!(" [{}]", flags.join(", "))
 }
 
+/// Formats the number of active timers for `--count` output.
+///
+/// The output is a bare integer so status bars and scripts can use it directly.
+fn format_count(db: &Database) -> String {
+    db.timers.len().to_string()
+}
+
+/// Checks that a `--format` template only uses known placeholders.
+///
+/// # Arguments
+///
+/// * `template` - The user-supplied template string
+///
+/// # Returns
+///
+/// Returns `Ok(())` if every `{...}` placeholder is recognized, or an error naming
+/// the first unknown or unterminated placeholder.
+fn validate_template(template: &str) -> Result<(), String> {
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("Unterminated placeholder in format: '{}'", template))?;
+        let name = &after[..end];
+
+        if !TEMPLATE_PLACEHOLDERS.contains(&name) {
+            return Err(format!(
+                "Unknown placeholder '{{{}}}' in format (expected one of: {})",
+                name,
+                TEMPLATE_PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!("{{{}}}", p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    Ok(())
+}
+
+/// Renders a `--format` template for a single timer.
+///
+/// `{remaining}` is the time left (or "EXPIRED"), `{due}` is the RFC 3339 due time,
+/// `{duration}` is the total duration in seconds and `{flags}` matches `format_flags`.
+/// `{label}` is the timer's `--label`, or its message shortened when it has none.
+///
+/// # Arguments
+///
+/// * `template` - A template previously checked with `validate_template`
+/// * `timer` - The timer to render
+/// * `now` - The current time, used for `{remaining}`
+/// * `seconds_threshold_mins` - `{remaining}` shows seconds only below this many
+///   minutes (see `format_duration`)
+/// * `raw` - Show `{remaining}` in minutes without rolling up into hours
+///
+/// # Returns
+///
+/// The rendered line, or an error if the template contains unknown placeholders.
+///
+/// # Examples
+///
+/// ```ignore
+/// let line = render_template("#{id} {message}", &timer, now, i64::MAX, false)?;
+/// assert_eq!(line, "#1 Tea");
+/// ```
+fn render_template(
+    template: &str,
+    timer: &database::Timer,
+    now: time::OffsetDateTime,
+    seconds_threshold_mins: i64,
+    raw: bool,
+) -> Result<String, String> {
+    validate_template(template)?;
+
+    let remaining_secs = timer.remaining_seconds(now);
+    let remaining = if remaining_secs > 0 {
+        format_duration(remaining_secs, seconds_threshold_mins, raw)
+    } else {
+        "EXPIRED".to_string()
+    };
+    let due = timer
+        .due_at
+        .replace_nanosecond(0)
+        .unwrap_or(timer.due_at)
+        .format(&time::format_description::well_known::Rfc3339)
+        .map_err(|e| format!("Failed to format due time: {}", e))?;
+
+    Ok(template
+        .replace("{id}", &timer.id.to_string())
+        .replace("{remaining}", &remaining)
+        .replace("{due}", &due)
+        .replace("{duration}", &timer.duration_seconds.to_string())
+        .replace("{flags}", &format_flags(timer))
+        // Substitute the label and message last so braces inside them are left alone
+        .replace("{label}", &timer.label())
+        .replace("{message}", &timer.message))
+}
+
+/// A timer asked for by ID that doesn't exist, for commands that fail on it
+/// (reported with code `not_found` by `--json` errors).
+#[derive(Debug)]
+struct NotFoundError(String);
+
+impl std::fmt::Display for NotFoundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFoundError {}
+
+/// Returns a stable, machine-readable category for an error, printed as `code`
+/// with `--json` errors.
+///
+/// The category comes from the error's type: `parse` for input the parser
+/// rejected (including option values like `--due-within`), `not_found` for a
+/// timer ID that doesn't exist, `io` for file and process errors, `data` for
+/// JSON that couldn't be read or written, and `other` for everything else
+/// (invalid option combinations, database limits, and the like).
+fn error_code(error: &(dyn std::error::Error + 'static)) -> &'static str {
+    if error.is::<parser::ParseError>() {
+        "parse"
+    } else if error.is::<NotFoundError>() {
+        "not_found"
+    } else if error.is::<io::Error>() {
+        "io"
+    } else if error.is::<serde_json::Error>() {
+        "data"
+    } else {
+        "other"
+    }
+}
+
+/// Renders an error as the single-line `{"error": ..., "code": ...}` object
+/// printed instead of `Error: ...` when `--json` output was asked for.
+fn error_json(error: &(dyn std::error::Error + 'static)) -> String {
+    serde_json::json!({
+        "error": error.to_string(),
+        "code": error_code(error),
+    })
+    .to_string()
+}
+
 fn main() {
     let cli = Cli::parse();
 
+    if cli.no_daemon_spawn {
+        daemon::disable_spawning();
+    }
+
     // Handle daemon mode (internal use)
     if cli.daemon_mode {
         if let Err(e) = daemon::run_daemon() {
@@ -160,51 +805,592 @@ fn main() {
         return;
     }
 
+    if let Some(format) = &cli.format
+        && let Err(e) = validate_template(format)
+    {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+    let format = cli.format.as_deref();
+    let raw = cli.raw;
+    let json_compact = cli.json_compact;
+    // Scripts reading `--json` output get errors as JSON too
+    let json_errors = matches!(
+        cli.command,
+        Some(
+            Commands::List { json: true, .. }
+                | Commands::History { json: true, .. }
+                | Commands::Info { json: true }
+                | Commands::Status { json: true, .. }
+        )
+    );
+
+    let mut autostart = daemon::Autostart::configured(cli.no_autostart);
     let result = match cli.command {
-        Some(Commands::List) => list_timers(),
-        Some(Commands::History) => show_history(),
-        Some(Commands::Remove { id }) => remove_timer(id),
-        Some(Commands::Clear) => clear_timers(),
-        Some(Commands::ClearHistory) => clear_history(),
-        Some(Commands::Status) => show_status(),
+        Some(Commands::List {
+            count,
+            due_within,
+            created_after,
+            created_before,
+            group_by,
+            json,
+        }) => CreatedRange::parse(created_after.as_deref(), created_before.as_deref()).and_then(
+            |created| {
+                let json = json.then_some(json_compact);
+                let filter = ListFilter {
+                    due_within: due_within.as_deref(),
+                    created,
+                };
+                list_timers(format, count, filter, group_by, json, raw, &mut autostart)
+            },
+        ),
+        Some(Commands::Top { limit, jsonl }) => show_top(limit, format, jsonl, raw, &mut autostart),
+        Some(Commands::Wait { timeout }) => wait_for_timers(timeout.as_deref()),
+        Some(Commands::History {
+            limit,
+            json,
+            sort,
+            reverse,
+            created_after,
+            created_before,
+        }) => CreatedRange::parse(created_after.as_deref(), created_before.as_deref()).and_then(
+            |created| {
+                let json = json.then_some(json_compact);
+                show_history(limit, json, sort, reverse, created, raw)
+            },
+        ),
+        Some(Commands::Export { output }) => export_timers(format, output.as_deref()),
+        Some(Commands::Convert { id, to_absolute }) => convert_to_absolute(id, &to_absolute, raw),
+        Some(Commands::Import { from_cron }) => import_cron(&from_cron, raw),
+        // Without an ID, clap guarantees --all-expired was given
+        Some(Commands::Remove { id, .. }) => match id {
+            Some(id) => remove_timer(id),
+            None => remove_expired_timers(),
+        },
+        Some(Commands::Edit {
+            id,
+            duration,
+            urgent,
+            no_urgent,
+            sound,
+            no_sound,
+            recurring,
+            no_recurring,
+            silent,
+            no_silent,
+        }) => {
+            let changes = database::FlagChanges {
+                urgent: flag_change(urgent, no_urgent),
+                sound: flag_change(sound, no_sound),
+                recurring: flag_change(recurring, no_recurring),
+                silent: flag_change(silent, no_silent),
+            };
+            edit_timer(id, duration.as_deref(), changes, raw)
+        }
+        Some(Commands::SetDefault {
+            urgent,
+            no_urgent,
+            sound,
+            no_sound,
+            recurring,
+            no_recurring,
+            silent,
+            no_silent,
+        }) => set_defaults(database::FlagChanges {
+            urgent: flag_change(urgent, no_urgent),
+            sound: flag_change(sound, no_sound),
+            recurring: flag_change(recurring, no_recurring),
+            silent: flag_change(silent, no_silent),
+        }),
+        Some(Commands::Rename { id, message }) => rename_timer(id, &message.join(" ")),
+        Some(Commands::Amend { id }) => amend_timer(id),
+        Some(Commands::Remaining { id, human }) => show_remaining(id, human, raw),
+        Some(Commands::Pin { id }) => pin_timer(id, true),
+        Some(Commands::Unpin { id }) => pin_timer(id, false),
+        Some(Commands::Pause { id, for_duration }) => pause_timer(id, for_duration.as_deref(), raw),
+        Some(Commands::Resume { id }) => resume_timer(id, raw),
+        Some(Commands::Recur { id, every }) => set_timer_recurring(id, true, every.as_deref(), raw),
+        Some(Commands::Norecur { id }) => set_timer_recurring(id, false, None, raw),
+        Some(Commands::Move { id, new_id }) => move_timer(id, new_id),
+        Some(Commands::ExtendAll { duration }) => extend_all_timers(&duration, raw),
+        Some(Commands::SnoozeAll { duration, within }) => {
+            snooze_all_timers(&duration, &within, raw)
+        }
+        Some(Commands::Replay { id }) => replay_timer(id, raw),
+        Some(Commands::Clear { tag }) => clear_timers(tag.as_deref()),
+        Some(Commands::ClearHistory { keep }) => clear_history(keep),
+        Some(Commands::Purge { older_than }) => purge_history(&older_than),
+        Some(Commands::Validate) => validate_database(),
+        Some(Commands::Status {
+            by_hour: true,
+            json,
+            ..
+        }) => show_stats_by_hour(json.then_some(json_compact)),
+        Some(Commands::Status { watch: true, .. }) => watch_status(),
+        Some(Commands::Status { count, .. }) => show_status(count, &mut autostart),
+        Some(Commands::Check { verbose }) => check_timers(verbose),
+        Some(Commands::Metrics) => show_metrics(),
+        Some(Commands::Diff) => show_diff(),
+        Some(Commands::Journal { since }) => show_journal(since.as_deref()),
         Some(Commands::Daemon) => start_daemon(),
-        Some(Commands::Completions { shell }) => {
-            generate_completions(shell);
-            return;
+        Some(Commands::Info { json }) => show_info(json.then_some(json_compact)),
+        Some(Commands::Completions {
+            shell,
+            list,
+            output,
+        }) => {
+            if list {
+                println!("{}", supported_shells().join("\n"));
+                Ok(())
+            } else {
+                generate_completions(shell.as_deref().unwrap_or_default(), output.as_deref())
+            }
         }
         None => {
             // Default: add a timer
-            if cli.input.is_empty() {
-                eprintln!("Error: Please provide duration and message");
-                eprintln!("Usage: break [FLAGS] <input with duration and message>");
-                eprintln!("Examples:");
-                eprintln!("  break 5m Tea is ready");
-                eprintln!("  break 15mins 1 hour 20s take a break");
-                eprintln!("  break --urgent 5m get coffee");
-                eprintln!("  break 5m get coffee --urgent");
-                eprintln!("  break --recurring --sound 1h stretch");
+            if cli.input.is_empty() && cli.seconds.is_none() {
+                print_usage();
                 process::exit(1);
             }
 
-            // Extract flags from input if present
-            let (input_cleaned, urgent_flag, sound_flag, recurring_flag) =
-                extract_flags_from_input(&cli.input);
+            // Extract tags and flags from input if present (either source works)
+            let (input_cleaned, flags, tags, notification) = resolve_timer_input(&cli);
+            let flags = with_defaults(flags, &notification, defaults::load());
+
+            // Flags alone (e.g. `break -u -s`) are as incomplete as no input at all
+            if !has_timer_input(&input_cleaned) && cli.seconds.is_none() {
+                print_usage();
+                process::exit(1);
+            }
 
-            // Combine with CLI flags (either source works)
-            let urgent = cli.urgent || urgent_flag;
-            let sound = cli.sound || sound_flag;
-            let recurring = cli.recurring || recurring_flag;
+            // A custom default message implies allowing duration-only input, and
+            // `--messages` stands in for a message
+            let first_message = notification.messages.first().cloned();
+            let default_message = match cli.default_message.as_deref() {
+                Some(message) => Some(message),
+                None if first_message.is_some() => first_message.as_deref(),
+                None if cli.allow_empty_message => Some(parser::DEFAULT_MESSAGE),
+                None => None,
+            };
+            // Only look up the local time when the input refers to it (`at 5pm`, `{date}`)
+            let now = [Some(input_cleaned.as_str()), default_message]
+                .into_iter()
+                .flatten()
+                .any(parser::uses_local_time)
+                .then(local_now);
+            let seconds = cli
+                .seconds
+                .as_deref()
+                .map(|value| resolve_seconds(value, io::stdin().lock()))
+                .transpose()
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                });
+            let options = parser::ParseOptions {
+                default_message,
+                now,
+                seconds,
+                lenient: cli.lenient,
+                decimal_comma: config::decimal_comma(),
+            };
 
-            add_timer(&input_cleaned, urgent, sound, recurring)
+            if flags.no_daemon && flags.replace.is_some() {
+                Err("--replace can't be combined with --no-daemon".into())
+            } else if flags.no_daemon {
+                run_foreground_timer(&input_cleaned, &options, flags, tags, notification, raw)
+            } else {
+                add_timer(
+                    &input_cleaned,
+                    &options,
+                    flags,
+                    tags,
+                    notification,
+                    format,
+                    raw,
+                )
+            }
         }
     };
 
     if let Err(e) = result {
-        eprintln!("Error: {}", e);
+        if json_errors {
+            eprintln!("{}", error_json(e.as_ref()));
+        } else {
+            eprintln!("Error: {}", e);
+        }
         process::exit(1);
     }
 }
 
+/// The system's local UTC offset, determined once per process by `local_offset`.
+static LOCAL_OFFSET: OnceLock<time::UtcOffset> = OnceLock::new();
+
+/// Returns the system's local UTC offset, determining it on the first call.
+///
+/// The offset is read once from `date +%z`, because the `time` crate can't
+/// determine it safely on its own. Only commands that parse or print local
+/// times call this, so others never start the process. When it can't be read
+/// (e.g. on Windows) a warning is printed and local times use UTC. A daemon
+/// keeps the offset it first read across a daylight saving change.
+fn local_offset() -> time::UtcOffset {
+    *LOCAL_OFFSET.get_or_init(|| {
+        process::Command::new("date")
+            .arg("+%z")
+            .output()
+            .ok()
+            .and_then(|output| parse_utc_offset(String::from_utf8_lossy(&output.stdout).trim()))
+            .unwrap_or_else(|| {
+                eprintln!("Warning: Could not determine the local UTC offset; using UTC");
+                time::UtcOffset::UTC
+            })
+    })
+}
+
+/// Returns the current time in the system's local UTC offset (see `local_offset`).
+fn local_now() -> time::OffsetDateTime {
+    time::OffsetDateTime::now_utc().to_offset(local_offset())
+}
+
+/// Parses a numeric UTC offset as printed by `date +%z` (e.g. `+0200`, `-0530`).
+fn parse_utc_offset(text: &str) -> Option<time::UtcOffset> {
+    let (sign, digits) = match text.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i8 = digits[..2].parse().ok()?;
+    let minutes: i8 = digits[2..].parse().ok()?;
+    time::UtcOffset::from_hms(sign * hours, sign * minutes, 0).ok()
+}
+
+/// Prints the usage help shown when no duration or message was given.
+fn print_usage() {
+    eprintln!("Error: Please provide duration and message");
+    eprintln!("Usage: break [FLAGS] <input with duration and message>");
+    eprintln!("Examples:");
+    eprintln!("  break 5m Tea is ready");
+    eprintln!("  break 15mins 1 hour 20s take a break");
+    eprintln!("  break --urgent 5m get coffee");
+    eprintln!("  break 5m get coffee --urgent");
+    eprintln!("  break --recurring --sound 1h stretch");
+}
+
+/// Reads the `--seconds` value: a whole number of seconds, or `-` to read one
+/// from `stdin` (e.g. `echo 300 | break --seconds - coffee`).
+///
+/// # Errors
+///
+/// Returns an error if the value (or the first line of stdin) is not a whole
+/// number, or stdin can't be read.
+fn resolve_seconds(value: &str, mut stdin: impl io::BufRead) -> Result<u64, String> {
+    let mut line = String::new();
+    let value = if value == "-" {
+        stdin
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read --seconds from stdin: {}", e))?;
+        line.trim()
+    } else {
+        value.trim()
+    };
+
+    value.parse().map_err(|_| {
+        format!(
+            "Invalid --seconds: expected a whole number of seconds, got '{}'",
+            value
+        )
+    })
+}
+
+/// Returns whether anything besides flags is left to parse as a timer.
+///
+/// # Arguments
+///
+/// * `input_cleaned` - The input after `extract_flags_from_input` removed the flags
+fn has_timer_input(input_cleaned: &str) -> bool {
+    !input_cleaned.trim().is_empty()
+}
+
+/// Per-timer flags given on the command line.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct TimerFlags {
+    /// Mark the notification as urgent/critical
+    urgent: bool,
+    /// Play a sound with the notification
+    sound: bool,
+    /// Repeat the timer after completion
+    recurring: bool,
+    /// Skip the notification entirely (the timer still completes into history)
+    silent: bool,
+    /// Block in the foreground instead of handing the timer to the daemon
+    no_daemon: bool,
+    /// Keep the notification in the notification center
+    persist: bool,
+    /// Keep the notification out of the notification center
+    transient: bool,
+    /// Quiet, non-urgent notification that disappears quickly
+    background: bool,
+    /// Ask before creating a timer from several separate durations
+    confirm: bool,
+    /// Remove this timer in the same transaction and give its ID to the new one
+    replace: Option<u32>,
+}
+
+impl TimerFlags {
+    /// Returns the timer's `transient` setting from `--transient`/`--persist`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if both were given.
+    fn transient(&self) -> Result<Option<bool>, String> {
+        match (self.transient, self.persist) {
+            (true, true) => Err("--persist can't be combined with --transient".to_string()),
+            (true, false) => Ok(Some(true)),
+            (false, true) => Ok(Some(false)),
+            (false, false) => Ok(None),
+        }
+    }
+
+    /// Returns the notification timeout to store, applying the `--background`
+    /// preset.
+    ///
+    /// Background timers get a `BACKGROUND_TIMEOUT_SECS` timeout unless `--timeout`
+    /// is given, so `BREAK_NOTIFICATION_TIMEOUT` never applies to them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `--timeout` is invalid, or if `--background` is combined
+    /// with `--urgent`, `--sound` or `--urgent-after`.
+    fn notification_timeout(
+        &self,
+        notification: &NotificationOptions,
+    ) -> Result<Option<u64>, String> {
+        let timeout_secs = notification.timeout_secs()?;
+        if !self.background {
+            return Ok(timeout_secs);
+        }
+        if self.urgent || self.sound || notification.urgent_after.is_some() {
+            return Err(
+                "--background can't be combined with --urgent, --sound or --urgent-after"
+                    .to_string(),
+            );
+        }
+        Ok(timeout_secs.or(Some(BACKGROUND_TIMEOUT_SECS)))
+    }
+}
+
+/// Which active timers `list` shows.
+#[derive(Debug, Default)]
+struct ListFilter<'a> {
+    /// Optional duration (e.g. "15m"); only timers due within it are shown
+    due_within: Option<&'a str>,
+    /// Only timers created within these bounds are shown
+    created: CreatedRange,
+}
+
+/// Creation-time bounds from `--created-after` and `--created-before`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct CreatedRange {
+    /// Keep timers created at or after this instant
+    after: Option<time::OffsetDateTime>,
+    /// Keep timers created strictly before this instant
+    before: Option<time::OffsetDateTime>,
+}
+
+impl CreatedRange {
+    /// Parses the option values with `parser::parse_past_time`, relative to the
+    /// local time now.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the option if either value is not a clock time or
+    /// a duration.
+    fn parse(
+        after: Option<&str>,
+        before: Option<&str>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let parse = |value: Option<&str>, option: &str| {
+            value
+                .map(|value| parser::parse_past_time(value, local_now()))
+                .transpose()
+                .map_err(|e| e.context(&format!("Invalid --{}", option)))
+        };
+
+        Ok(Self {
+            after: parse(after, "created-after")?,
+            before: parse(before, "created-before")?,
+        })
+    }
+
+    /// Returns whether either bound is set.
+    fn is_set(&self) -> bool {
+        self.after.is_some() || self.before.is_some()
+    }
+
+    /// Returns whether the timer's `created_at` lies within the bounds.
+    fn contains(&self, timer: &database::Timer) -> bool {
+        self.after.is_none_or(|after| timer.created_at >= after)
+            && self.before.is_none_or(|before| timer.created_at < before)
+    }
+}
+
+/// Per-timer notification options given on the command line.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct NotificationOptions {
+    /// Name shown by `list` in place of the message, which stays the notification text
+    label: Option<String>,
+    /// Notification icon: a file path or an icon name
+    icon: Option<String>,
+    /// How long the notification stays on screen, as given (e.g. "10s" or "never")
+    timeout: Option<String>,
+    /// Delay before an unacknowledged timer fires again as urgent, as given (e.g. "2m")
+    urgent_after: Option<String>,
+    /// Interval between repeated notifications until acknowledged, as given (e.g. "30s")
+    nag: Option<String>,
+    /// Messages shown in turn on each fire (`--messages`)
+    messages: Vec<String>,
+}
+
+impl NotificationOptions {
+    /// Parses the `--timeout` value into seconds (`0` = never expires).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the timeout is neither a duration nor `never`.
+    fn timeout_secs(&self) -> Result<Option<u64>, String> {
+        self.timeout
+            .as_deref()
+            .map(parser::parse_timeout)
+            .transpose()
+            .map_err(|e| format!("Invalid --timeout: {}", e))
+    }
+
+    /// Parses the `--urgent-after` value into seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not a duration or is longer than a year.
+    fn urgent_after_secs(&self) -> Result<Option<u64>, String> {
+        self.urgent_after
+            .as_deref()
+            .map(|value| {
+                parser::parse_duration(value)
+                    .map_err(|e| e.to_string())
+                    .and_then(database::check_duration_limit)
+            })
+            .transpose()
+            .map_err(|e| format!("Invalid --urgent-after: {}", e))
+    }
+
+    /// Parses the `--nag` value into seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not a duration or is longer than a year.
+    fn nag_secs(&self) -> Result<Option<u64>, String> {
+        self.nag
+            .as_deref()
+            .map(|value| {
+                parser::parse_duration(value)
+                    .map_err(|e| e.to_string())
+                    .and_then(database::check_duration_limit)
+            })
+            .transpose()
+            .map_err(|e| format!("Invalid --nag: {}", e))
+    }
+
+    /// Returns the `--label` to store, trimmed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the label is blank.
+    fn label(&self) -> Result<Option<String>, String> {
+        match self.label.as_deref().map(str::trim) {
+            Some("") => Err("--label can't be empty".to_string()),
+            label => Ok(label.map(str::to_string)),
+        }
+    }
+
+    /// Returns the `--messages` a timer should rotate through.
+    ///
+    /// A single message is just the timer's message, so only two or more are
+    /// returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if several messages are given for a timer that isn't
+    /// recurring, since it only fires once.
+    fn rotating_messages(&self, recurring: bool) -> Result<Vec<String>, String> {
+        match self.messages.len() {
+            0 | 1 => Ok(Vec::new()),
+            _ if !recurring => Err("--messages needs --recurring to rotate".to_string()),
+            _ => Ok(self.messages.clone()),
+        }
+    }
+}
+
+/// Checks whether `arg` is a group of short timer flags like `-u` or `-usr`.
+///
+/// Any other letter makes the whole word message text (no flags are taken from it),
+/// so `-ux` never half-applies.
+fn is_short_flag_group(arg: &str) -> bool {
+    arg.strip_prefix('-')
+        .is_some_and(|letters| !letters.is_empty() && letters.chars().all(|c| "usr".contains(c)))
+}
+
+/// Combines the timer flags, tags, and notification options given as clap options
+/// (before the input) with those found inside the trailing input.
+///
+/// Clap only parses flags that come before the first input word; everything after
+/// it lands in `cli.input`. Merging both sources makes `-usr 5m x`, `5m x -usr`
+/// and `-us 5m x -r` all mean the same thing.
+///
+/// # Returns
+///
+/// Returns the cleaned input, the combined flags, the combined tags, and the
+/// notification options (the last value of each given wins).
+fn resolve_timer_input(cli: &Cli) -> (String, TimerFlags, Vec<String>, NotificationOptions) {
+    let (input, input_tags) = extract_option_from_input(&cli.input, "--tag");
+    let (input, input_labels) = extract_option_from_input(&input, "--label");
+    let (input, input_icons) = extract_option_from_input(&input, "--icon");
+    let (input, input_timeouts) = extract_option_from_input(&input, "--timeout");
+    let (input, input_escalations) = extract_option_from_input(&input, "--urgent-after");
+    let (input, input_nags) = extract_option_from_input(&input, "--nag");
+    let (input, input_messages) = extract_list_option_from_input(&input, "--messages");
+    let (input_cleaned, input_flags) = extract_flags_from_input(&input);
+
+    let flags = TimerFlags {
+        urgent: cli.urgent || input_flags.urgent,
+        sound: cli.sound || input_flags.sound,
+        recurring: cli.recurring || input_flags.recurring,
+        silent: cli.silent || input_flags.silent,
+        background: cli.background || input_flags.background,
+        confirm: cli.confirm || input_flags.confirm,
+        replace: cli.replace,
+        no_daemon: cli.no_daemon || input_flags.no_daemon,
+        persist: cli.persist || input_flags.persist,
+        transient: cli.transient || input_flags.transient,
+    };
+    let tags = [cli.tags.clone(), input_tags].concat();
+    let notification = NotificationOptions {
+        label: input_labels.last().or(cli.label.as_ref()).cloned(),
+        icon: input_icons.last().or(cli.icon.as_ref()).cloned(),
+        timeout: input_timeouts.last().or(cli.timeout.as_ref()).cloned(),
+        urgent_after: input_escalations
+            .last()
+            .or(cli.urgent_after.as_ref())
+            .cloned(),
+        nag: input_nags.last().or(cli.nag.as_ref()).cloned(),
+        messages: [cli.messages.clone(), input_messages].concat(),
+    };
+
+    (input_cleaned, flags, tags, notification)
+}
+
 /// Extracts flag arguments from mixed input and returns cleaned input plus flag states.
 ///
 /// This function allows users to place flags anywhere in their input, including at the end.
@@ -219,40 +1405,40 @@ fn main() {
 ///
 /// Returns a tuple of:
 /// - `String` - The cleaned input with all flags removed, joined with spaces
-/// - `bool` - Whether `--urgent` or `-u` was found
-/// - `bool` - Whether `--sound` or `-s` was found
-/// - `bool` - Whether `--recurring` or `-r` was found
+/// - `TimerFlags` - Which of `--urgent`/`-u`, `--sound`/`-s`, `--recurring`/`-r`,
+///   `--silent`, `--background`, `--confirm`, `--no-daemon`, `--persist` and
+///   `--transient` were found
 ///
 /// # Examples
 ///
 /// ```ignore
-/// let (clean, u, s, r) = extract_flags_from_input(&["5m", "coffee", "--urgent"]);
+/// let (clean, flags) = extract_flags_from_input(&["5m", "coffee", "--urgent"]);
 /// assert_eq!(clean, "5m coffee");
-/// assert!(u); // urgent flag found
+/// assert!(flags.urgent); // urgent flag found
 /// ```
-fn extract_flags_from_input(input: &[String]) -> (String, bool, bool, bool) {
-    let mut urgent = false;
-    let mut sound = false;
-    let mut recurring = false;
+fn extract_flags_from_input(input: &[String]) -> (String, TimerFlags) {
+    let mut flags = TimerFlags::default();
     let mut cleaned_input = Vec::new();
 
     for arg in input {
         match arg.as_str() {
-            "--urgent" => urgent = true,
-            "--sound" => sound = true,
-            "--recurring" => recurring = true,
-            s if s.starts_with('-') && !s.starts_with("--") => {
+            "--urgent" => flags.urgent = true,
+            "--sound" => flags.sound = true,
+            "--recurring" => flags.recurring = true,
+            "--silent" => flags.silent = true,
+            "--background" => flags.background = true,
+            "--confirm" => flags.confirm = true,
+            "--no-daemon" => flags.no_daemon = true,
+            "--persist" => flags.persist = true,
+            "--transient" => flags.transient = true,
+            s if is_short_flag_group(s) => {
                 // Handle short flags (single dash) including combined flags like -us
                 for ch in s.chars().skip(1) {
                     match ch {
-                        'u' => urgent = true,
-                        's' => sound = true,
-                        'r' => recurring = true,
-                        _ => {
-                            // Unknown flag, treat whole arg as input
-                            cleaned_input.push(arg.clone());
-                            break;
-                        }
+                        'u' => flags.urgent = true,
+                        's' => flags.sound = true,
+                        'r' => flags.recurring = true,
+                        _ => unreachable!("checked by is_short_flag_group"),
                     }
                 }
             }
@@ -260,21 +1446,151 @@ fn extract_flags_from_input(input: &[String]) -> (String, bool, bool, bool) {
         }
     }
 
-    (cleaned_input.join(" "), urgent, sound, recurring)
+    (cleaned_input.join(" "), flags)
+}
+
+/// Extracts a multi-value option such as `--messages a b c` from mixed input.
+///
+/// The values run until the next option or flag group (`--urgent`, `-r`), so the
+/// option is best placed last. A `--messages` given more than once collects the
+/// values of all of them.
+///
+/// # Returns
+///
+/// Returns the input without the option and its values, and the values found.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (rest, messages) = extract_list_option_from_input(&["1h", "--messages", "a", "b", "-r"], "--messages");
+/// assert_eq!(rest, vec!["1h", "-r"]);
+/// assert_eq!(messages, vec!["a", "b"]);
+/// ```
+fn extract_list_option_from_input(input: &[String], option: &str) -> (Vec<String>, Vec<String>) {
+    let mut rest = Vec::new();
+    let mut values = Vec::new();
+    let mut in_values = false;
+
+    for arg in input {
+        if arg == option {
+            in_values = true;
+        } else if in_values && !arg.starts_with("--") && !is_short_flag_group(arg) {
+            values.push(arg.clone());
+        } else {
+            in_values = false;
+            rest.push(arg.clone());
+        }
+    }
+
+    (rest, values)
+}
+
+/// Extracts `<option> <VALUE>` and `<option>=<VALUE>` arguments (such as `--tag`)
+/// from mixed input.
+///
+/// Like the other flags, these options can appear anywhere in the input, including
+/// after the message where clap hands them over as plain input words.
+///
+/// # Arguments
+///
+/// * `input` - Slice of input strings that may contain the option
+/// * `option` - The long option name including dashes, e.g. `--tag`
+///
+/// # Returns
+///
+/// Returns a tuple of the remaining input words and the values found, in order.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (rest, tags) = extract_option_from_input(&["5m", "stretch", "--tag", "health"], "--tag");
+/// assert_eq!(rest, vec!["5m", "stretch"]);
+/// assert_eq!(tags, vec!["health"]);
+/// ```
+fn extract_option_from_input(input: &[String], option: &str) -> (Vec<String>, Vec<String>) {
+    let mut rest = Vec::new();
+    let mut values = Vec::new();
+    let mut args = input.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == option {
+            match args.next() {
+                Some(value) => values.push(value.clone()),
+                // A trailing option without a value is kept as message text
+                None => rest.push(arg.clone()),
+            }
+        } else if let Some(value) = arg
+            .strip_prefix(option)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            values.push(value.to_string());
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+
+    (rest, values)
+}
+
+/// Expands the creation-time placeholders in a timer message: `{date}`, `{time}`
+/// (the local date and time `now`) and `{id}`.
+///
+/// `{fire_date}` and `{fire_time}` are kept, so the daemon can expand them when
+/// the timer fires.
+fn expand_creation_placeholders(message: &str, id: u32, now: time::OffsetDateTime) -> String {
+    parser::expand_time_placeholders(message, "", now).replace("{id}", &id.to_string())
+}
+
+/// How `add` handles a timer shorter than the configured minimum duration
+#[derive(Debug, PartialEq, Eq)]
+enum ShortTimerAction {
+    /// Long enough; add it as given
+    Keep,
+    /// Add it with this many seconds instead
+    Bump(u64),
+    /// Run it in the foreground instead of through the daemon
+    Inline,
+}
+
+/// Decides what to do with a new timer of `duration_seconds`.
+///
+/// Timers that need the daemon (recurring or escalating) are always bumped, since
+/// they can't run in the foreground.
+fn short_timer_action(
+    duration_seconds: u64,
+    min_secs: u64,
+    policy: config::ShortTimerPolicy,
+    needs_daemon: bool,
+) -> ShortTimerAction {
+    if duration_seconds >= min_secs {
+        return ShortTimerAction::Keep;
+    }
+    match policy {
+        config::ShortTimerPolicy::Inline if !needs_daemon => ShortTimerAction::Inline,
+        _ => ShortTimerAction::Bump(min_secs),
+    }
 }
 
 /// Creates a new timer from user input with specified flags.
 ///
 /// Parses the input string to extract duration and message, creates a timer in the
 /// database using a transaction for atomicity, displays confirmation to the user,
-/// and ensures the daemon is running to monitor the timer.
+/// and ensures the daemon is running to monitor the timer. Timers shorter than
+/// `BREAK_MIN_DURATION_SECS` are lengthened or run in the foreground, depending on
+/// `BREAK_SHORT_TIMER_POLICY`. Creation-time placeholders in the message (`{date}`,
+/// `{time}`, `{id}`) are expanded before the timer is saved.
 ///
 /// # Arguments
 ///
 /// * `input` - The input string containing duration and message (e.g., "5m get coffee")
-/// * `urgent` - Whether to mark the notification as urgent/critical
-/// * `sound` - Whether to play a sound when the notification fires
-/// * `recurring` - Whether the timer should automatically repeat after completion
+/// * `options` - Parser options (e.g. the default message for duration-only input)
+/// * `flags` - Urgent/sound/recurring/silent flags for the timer, and the timer it
+///   replaces (`--replace`)
+/// * `tags` - Tags used to group the timer (may be empty)
+/// * `notification` - Optional notification icon (file path or icon name), timeout,
+///   and urgent escalation delay
+/// * `format` - Optional `--format` template for the confirmation line
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
 ///
 /// # Returns
 ///
@@ -284,40 +1600,159 @@ fn extract_flags_from_input(input: &[String]) -> (String, bool, bool, bool) {
 /// # Examples
 ///
 /// ```ignore
-/// add_timer("5m coffee break", true, false, false)?; // Urgent 5-minute timer
-/// add_timer("1h meeting", false, true, true)?;       // Recurring hourly timer with sound
+/// let options = parser::ParseOptions::default();
+/// let urgent = TimerFlags { urgent: true, ..Default::default() };
+/// add_timer("5m coffee break", &options, urgent, vec![], Default::default(), None, false)?; // Urgent 5-minute timer
 /// ```
 fn add_timer(
     input: &str,
-    urgent: bool,
-    sound: bool,
-    recurring: bool,
+    options: &parser::ParseOptions,
+    flags: TimerFlags,
+    tags: Vec<String>,
+    notification: NotificationOptions,
+    format: Option<&str>,
+    raw: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let (duration_seconds, message) = parser::parse_input(input)?;
+    let timeout_secs = flags.notification_timeout(&notification)?;
+    let transient = flags.transient()?;
+    let urgent_after_secs = notification.urgent_after_secs()?;
+    if urgent_after_secs.is_some() && flags.recurring {
+        return Err("--urgent-after can't be combined with --recurring".into());
+    }
+    let nag_interval_secs = notification.nag_secs()?;
+    if nag_interval_secs.is_some() && flags.recurring {
+        return Err("--nag can't be combined with --recurring".into());
+    }
+    let messages = notification.rotating_messages(flags.recurring)?;
+    let label = notification.label()?;
 
-    // Use transaction to ensure atomic load-modify-save
-    let timer = Database::with_transaction(|db| {
-        db.add_timer(message.clone(), duration_seconds, urgent, sound, recurring)
-            .map_err(|e| format!("Failed to add timer: {}", e).into())
-    })?;
+    // `every day at 12:30` first waits for the next 12:30, then repeats daily
+    let (duration_seconds, message, daily) = match parser::parse_daily_schedule(input, options)? {
+        Some((first_delay, message)) => (first_delay, message, true),
+        None => {
+            let (duration_seconds, message) = parse_timer_input(input, options, flags, raw)?;
+            (duration_seconds, message, false)
+        }
+    };
 
-    println!(
-        "Timer #{} set for \"{}\" ({} seconds){}",
-        timer.id,
-        message,
+    let min_secs = config::min_duration_secs();
+    let duration_seconds = match short_timer_action(
         duration_seconds,
-        format_flags(&timer)
-    );
-
-    // Show relative time (e.g., "in 5 minutes")
+        min_secs,
+        config::short_timer_policy(),
+        // Replacing a timer needs the new one stored, so it can't run in the foreground
+        flags.recurring
+            || urgent_after_secs.is_some()
+            || nag_interval_secs.is_some()
+            || flags.replace.is_some(),
+    ) {
+        ShortTimerAction::Keep => duration_seconds,
+        _ if daily => duration_seconds,
+        ShortTimerAction::Bump(seconds) => {
+            eprintln!(
+                "Warning: {}s is shorter than the {}s minimum; using {}s",
+                duration_seconds, min_secs, seconds
+            );
+            seconds
+        }
+        ShortTimerAction::Inline => {
+            eprintln!(
+                "Warning: {}s is shorter than the {}s minimum; running it in the foreground",
+                duration_seconds, min_secs
+            );
+            return run_foreground_timer(input, options, flags, tags, notification, raw);
+        }
+    };
+
+    // Use transaction to ensure atomic load-modify-save
+    let created = options.now.unwrap_or_else(time::OffsetDateTime::now_utc);
+    let (timer, replaced) = Database::with_transaction(|db| {
+        let (timer, replaced) = match flags.replace {
+            Some(id) => db.replace_timer(
+                id,
+                message.clone(),
+                duration_seconds,
+                flags.urgent,
+                flags.sound,
+                flags.recurring,
+            ),
+            None => db
+                .add_timer(
+                    message.clone(),
+                    duration_seconds,
+                    flags.urgent,
+                    flags.sound,
+                    flags.recurring,
+                )
+                .map(|timer| (timer, None)),
+        }
+        .map_err(|e| format!("Failed to add timer: {}", e))?;
+        db.update_timer(timer.id, |t| {
+            t.message = expand_creation_placeholders(&t.message, t.id, created);
+            t.label = label
+                .as_deref()
+                .map(|label| expand_creation_placeholders(label, t.id, created));
+            t.silent = flags.silent;
+            t.icon = notification.icon.clone();
+            t.timeout_secs = timeout_secs;
+            t.transient = transient;
+            t.urgent_after_secs = urgent_after_secs;
+            t.nag_interval_secs = nag_interval_secs;
+            t.messages = messages
+                .iter()
+                .map(|message| expand_creation_placeholders(message, t.id, created))
+                .collect();
+            if daily {
+                t.duration_seconds = SECONDS_PER_DAY as u64;
+                t.recurring = true;
+                t.anchored = true;
+            }
+        });
+        Ok((
+            db.set_tags(timer.id, tags.clone()).unwrap_or(timer),
+            replaced,
+        ))
+    })?;
+
+    if let Some(old) = replaced {
+        println!("Replaced timer #{}: \"{}\"", old.id, old.message);
+    }
     let now = time::OffsetDateTime::now_utc();
-    let duration_until = timer.due_at - now;
-    let seconds = duration_until.whole_seconds();
+    println!(
+        "{}",
+        render_template(
+            format.unwrap_or(DEFAULT_ADD_FORMAT),
+            &timer,
+            now,
+            config::show_seconds_threshold_mins(),
+            raw
+        )?
+    );
 
-    if seconds > 0 {
-        println!("Break will notify you in {}", format_duration(seconds, 5));
-    } else {
-        println!("Break notification is ready!");
+    // Show relative time (e.g., "in 5 minutes")
+    let seconds = timer.remaining_seconds(now);
+
+    // Custom formats fully control the output
+    if format.is_none() {
+        if seconds > 0 && timer.silent {
+            println!(
+                "Timer will complete silently in {}",
+                format_duration(seconds, 5, raw)
+            );
+        } else if seconds > 0 {
+            println!(
+                "Break will notify you in {}",
+                format_duration(seconds, 5, raw)
+            );
+        } else {
+            println!("Break notification is ready!");
+        }
+        if timer.anchored {
+            println!("Repeats every day at the same time");
+        }
+        if !timer.tags.is_empty() {
+            println!("Tags: {}", timer.tags.join(", "));
+        }
     }
 
     // Ensure daemon is running
@@ -326,18 +1761,223 @@ fn add_timer(
     Ok(())
 }
 
+/// Parses timer input, first asking for confirmation when `--confirm` (or
+/// `BREAK_CONFIRM_AMBIGUOUS`) is on and the input holds several separate
+/// durations, since `5m 3h meeting` adding up to 3h 5m may not be what was meant.
+///
+/// Without a terminal to ask on (scripts, pipes) the timer is created as parsed.
+///
+/// # Returns
+///
+/// Returns the duration in seconds and the message.
+///
+/// # Errors
+///
+/// Returns an error if the input can't be parsed or the user declines.
+fn parse_timer_input(
+    input: &str,
+    options: &parser::ParseOptions,
+    flags: TimerFlags,
+    raw: bool,
+) -> Result<(u64, String), Box<dyn std::error::Error>> {
+    let parsed = parser::parse_input_parts(input, options)?;
+
+    let confirm = flags.confirm || config::confirm_ambiguous();
+    if confirm
+        && parsed.parts.len() > 1
+        && io::stdin().is_terminal()
+        && !confirm_durations(&parsed, raw, io::stdin().lock(), io::stderr())?
+    {
+        return Err("Cancelled; no timer was created".into());
+    }
+
+    Ok((parsed.seconds, parsed.message))
+}
+
+/// Shows how several separate durations were added up and asks whether to
+/// create the timer.
+///
+/// # Arguments
+///
+/// * `parsed` - The parsed input
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+/// * `answer` - Where the answer is read from
+/// * `prompt` - Where the question is written
+///
+/// # Returns
+///
+/// Returns `true` if the answer was yes; an empty answer counts as no.
+fn confirm_durations(
+    parsed: &parser::ParsedInput,
+    raw: bool,
+    mut answer: impl io::BufRead,
+    mut prompt: impl Write,
+) -> io::Result<bool> {
+    let parts: Vec<String> = parsed
+        .parts
+        .iter()
+        .map(|seconds| format_duration(*seconds as i64, 5, raw))
+        .collect();
+    write!(
+        prompt,
+        "Found {} separate durations: {} = {} for \"{}\". Create this timer? [y/N] ",
+        parts.len(),
+        parts.join(" + "),
+        format_duration(parsed.seconds as i64, 5, raw),
+        parsed.message
+    )?;
+    prompt.flush()?;
+
+    let mut line = String::new();
+    answer.read_line(&mut line)?;
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Runs a one-shot timer in the foreground, without the daemon or the database.
+///
+/// Blocks until the timer is due, shows its notification (using the daemon's
+/// notification code and retry settings), and returns. Nothing is written to disk,
+/// so Ctrl-C cancels the timer cleanly.
+///
+/// # Arguments
+///
+/// * `input` - The duration and message, with flags already removed
+/// * `options` - Parsing options (default message, reference time)
+/// * `flags` - Flags for the timer; `--recurring` is rejected
+/// * `tags` - Tags for the notification grouping and default icon
+/// * `notification` - Optional notification icon and timeout
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the timer has fired, or an error if parsing fails, the
+/// timer is recurring, or the notification could not be shown.
+fn run_foreground_timer(
+    input: &str,
+    options: &parser::ParseOptions,
+    flags: TimerFlags,
+    tags: Vec<String>,
+    notification: NotificationOptions,
+    raw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if flags.recurring {
+        return Err("--recurring can't be combined with --no-daemon".into());
+    }
+    if notification.urgent_after.is_some() {
+        return Err("--urgent-after can't be combined with --no-daemon".into());
+    }
+    if notification.nag.is_some() {
+        return Err("--nag can't be combined with --no-daemon".into());
+    }
+    notification.rotating_messages(false)?;
+    let timeout_secs = flags.notification_timeout(&notification)?;
+    let transient = flags.transient()?;
+
+    let (duration_seconds, message) = parse_timer_input(input, options, flags, raw)?;
+
+    // Build the timer in a scratch database that is never saved, so it gets the
+    // same validation as a daemon-managed timer
+    let created = options.now.unwrap_or_else(time::OffsetDateTime::now_utc);
+    let mut scratch = Database::new();
+    let timer = scratch.add_timer(message, duration_seconds, flags.urgent, flags.sound, false)?;
+    let timer = scratch
+        .update_timer(timer.id, |t| {
+            t.message = expand_creation_placeholders(&t.message, t.id, created);
+            t.silent = flags.silent;
+            t.icon = notification.icon;
+            t.timeout_secs = timeout_secs;
+            t.transient = transient;
+        })
+        .unwrap_or(timer);
+    let timer = scratch.set_tags(timer.id, tags).unwrap_or(timer);
+
+    println!(
+        "Waiting {} for \"{}\" (Ctrl-C to cancel)",
+        format_duration(duration_seconds as i64, 5, raw),
+        timer.message
+    );
+    if !wait_and_fire(&timer, daemon::notify_timer) && !timer.silent {
+        return Err("Notification could not be shown".into());
+    }
+
+    Ok(())
+}
+
+/// Sleeps until `timer` is due, then calls `notify` once unless it is silent.
+///
+/// # Returns
+///
+/// Returns whether a notification was shown.
+fn wait_and_fire<N: FnOnce(&database::Timer) -> bool>(timer: &database::Timer, notify: N) -> bool {
+    // Sleep for the exact remainder; whole seconds would fire up to a second early
+    let remaining = timer.due_at - time::OffsetDateTime::now_utc();
+    if remaining.is_positive() {
+        thread::sleep(remaining.unsigned_abs());
+    }
+
+    !timer.silent && notify(timer)
+}
+
 /// Lists all active timers with their remaining time and flags.
 ///
 /// Loads the timer database, displays each active timer with formatted time remaining,
 /// marks expired timers as "EXPIRED", shows any flags (urgent/sound/recurring), and
 /// ensures the daemon is running if there are active timers.
 ///
+/// # Arguments
+///
+/// * `format` - Optional `--format` template; when set, only the timer lines are printed
+/// * `count` - Print only the number of (matching) active timers
+/// * `filter` - Which timers to show (`--due-within`, `--created-after/before`)
+/// * `group_by` - Show the timers under group headings (see `group_timers_by_tag`)
+/// * `json` - With `group_by`, print the groups as a JSON object keyed by tag (`""`
+///   for untagged timers); `Some(true)` prints it on a single line
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+/// * `autostart` - Starts the daemon when there are active timers
+///
 /// # Returns
 ///
-/// Returns `Ok(())` on success, or an error if the database cannot be loaded or
-/// the daemon cannot be started.
-fn list_timers() -> Result<(), Box<dyn std::error::Error>> {
+/// Returns `Ok(())` on success, or an error if the duration is invalid, the database
+/// cannot be loaded, or the daemon cannot be started.
+fn list_timers<S>(
+    format: Option<&str>,
+    count: bool,
+    filter: ListFilter,
+    group_by: Option<ListGroup>,
+    json: Option<bool>,
+    raw: bool,
+    autostart: &mut daemon::Autostart<S>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: FnMut() -> Result<(), Box<dyn std::error::Error>>,
+{
+    let ListFilter {
+        due_within,
+        created,
+    } = filter;
+    let due_within_seconds = due_within
+        .map(parser::parse_duration)
+        .transpose()
+        .map_err(|e| e.context("Invalid --due-within"))?;
+
     let db = Database::load()?;
+    let mut timers = match due_within_seconds {
+        Some(seconds) => db.timers_due_within(seconds),
+        None => db.timers.iter().collect(),
+    };
+    timers.retain(|timer| created.contains(timer));
+    pinned_first(&mut timers);
+
+    if count {
+        println!("{}", timers.len());
+        return autostart.for_timers(&db);
+    }
+
+    let groups = group_by.map(|ListGroup::Tag| group_timers_by_tag(&timers));
+    if let (Some(compact), Some(groups)) = (json, &groups) {
+        println!("{}", to_json(&groups_by_key(groups), compact)?);
+        return autostart.for_timers(&db);
+    }
 
     if db.timers.is_empty() {
         println!("No active timers");
@@ -345,210 +1985,3902 @@ fn list_timers() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Ensure daemon is running if there are active timers
-    daemon::ensure_daemon_running()?;
+    autostart.for_timers(&db)?;
+
+    if let Some(window) = due_within
+        && timers.is_empty()
+    {
+        println!("No timers due within {}", window);
+        return Ok(());
+    }
+    if created.is_set() && timers.is_empty() {
+        println!("No timers created in that range");
+        return Ok(());
+    }
 
-    println!("Active timers:");
-    for timer in &db.timers {
+    if format.is_none() {
+        println!("Active timers:");
+    }
+    let seconds_threshold_mins = config::show_seconds_threshold_mins();
+    let render = |timer: &database::Timer| {
         let now = time::OffsetDateTime::now_utc();
-        let remaining = timer.due_at - now;
-        let remaining_secs = remaining.whole_seconds();
+        let remaining_secs = timer.remaining_seconds(now);
 
-        if remaining_secs > 0 {
-            println!(
-                "  #{}: \"{}\" - {} remaining{}",
-                timer.id,
-                timer.message,
-                format_duration(remaining_secs, i64::MAX), // Always show seconds for active timers
-                format_flags(timer)
-            );
-        } else {
-            println!(
-                "  #{}: \"{}\" - EXPIRED{}",
-                timer.id,
-                timer.message,
-                format_flags(timer)
-            );
+        let template = match format {
+            Some(format) => format,
+            None if remaining_secs > 0 => DEFAULT_LIST_FORMAT,
+            None => DEFAULT_EXPIRED_LIST_FORMAT,
+        };
+        render_template(template, timer, now, seconds_threshold_mins, raw)
+    };
+
+    match groups {
+        Some(groups) => {
+            for (tag, timers) in groups {
+                println!("{}:", tag.unwrap_or("(untagged)"));
+                for timer in timers {
+                    println!("  {}", render(timer)?);
+                }
+            }
+        }
+        None => {
+            for timer in timers {
+                println!("{}", render(timer)?);
+            }
         }
     }
 
     Ok(())
 }
 
-/// Removes a timer by its ID.
+/// Groups timers for `list --group-by tag`.
 ///
-/// Uses a database transaction to atomically remove the specified timer.
-/// The timer is removed without adding it to history (unlike timer completion).
+/// A timer with several tags is listed once, under its first tag (the same group
+/// its notification uses). Tagged groups come in alphabetical order followed by
+/// the untagged timers (`None`); within a group timers are ordered by due time,
+/// with pinned timers first.
+fn group_timers_by_tag<'a>(
+    timers: &[&'a database::Timer],
+) -> Vec<(Option<&'a str>, Vec<&'a database::Timer>)> {
+    let mut tagged: BTreeMap<&str, Vec<&database::Timer>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for timer in timers {
+        match timer.tags.first() {
+            Some(tag) => tagged.entry(tag.as_str()).or_default().push(*timer),
+            None => untagged.push(*timer),
+        }
+    }
+
+    let mut groups: Vec<(Option<&str>, Vec<&database::Timer>)> = tagged
+        .into_iter()
+        .map(|(tag, timers)| (Some(tag), timers))
+        .collect();
+    if !untagged.is_empty() {
+        groups.push((None, untagged));
+    }
+    for (_, timers) in &mut groups {
+        timers.sort_by_key(|timer| timer.due_at);
+        pinned_first(timers);
+    }
+
+    groups
+}
+
+/// Keys timer groups by tag for `list --group-by tag --json`, with untagged
+/// timers under `""`.
+fn groups_by_key<'a>(
+    groups: &'a [(Option<&str>, Vec<&'a database::Timer>)],
+) -> BTreeMap<&'a str, &'a [&'a database::Timer]> {
+    groups
+        .iter()
+        .map(|(tag, timers)| (tag.unwrap_or(""), timers.as_slice()))
+        .collect()
+}
+
+/// Renders one frame of the `top` view.
+///
+/// Timers are sorted by due time (soonest first) and at most `limit` are shown,
+/// followed by a count of the hidden ones.
 ///
 /// # Arguments
 ///
-/// * `id` - The numeric ID of the timer to remove
+/// * `timers` - The active timers
+/// * `now` - The reference time for the countdowns
+/// * `limit` - Maximum number of timers to show
+/// * `format` - Optional `--format` template for the timer lines
+/// * `seconds_threshold_mins` - Countdowns show seconds only below this many minutes
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on success (whether or not the timer was found), or an error
-/// if the database transaction fails.
-fn remove_timer(id: u32) -> Result<(), Box<dyn std::error::Error>> {
-    let timer_opt = Database::with_transaction(|db| Ok(db.remove_timer(id)))?;
+/// The frame text (ending with a newline), or an error if a template fails to render.
+fn render_top_frame(
+    timers: &[database::Timer],
+    now: time::OffsetDateTime,
+    limit: usize,
+    format: Option<&str>,
+    seconds_threshold_mins: i64,
+    raw: bool,
+) -> Result<String, String> {
+    let mut sorted: Vec<&database::Timer> = timers.iter().collect();
+    sorted.sort_by_key(|t| t.due_at);
 
-    if let Some(timer) = timer_opt {
-        println!("Removed timer #{}: \"{}\"", timer.id, timer.message);
-    } else {
-        println!("Timer #{} not found", id);
+    let mut frame = format!("Active timers: {} (soonest first)\n", timers.len());
+    for timer in sorted.iter().take(limit) {
+        let template = match format {
+            Some(format) => format,
+            None if timer.remaining_seconds(now) > 0 => DEFAULT_LIST_FORMAT,
+            None => DEFAULT_EXPIRED_LIST_FORMAT,
+        };
+        frame.push_str(&render_template(
+            template,
+            timer,
+            now,
+            seconds_threshold_mins,
+            raw,
+        )?);
+        frame.push('\n');
+    }
+    if sorted.len() > limit {
+        frame.push_str(&format!("  ... and {} more\n", sorted.len() - limit));
     }
 
-    Ok(())
+    Ok(frame)
 }
 
-/// Displays the history of recently completed timers.
+/// Writes one JSON Lines tick describing all active timers, soonest first.
+///
+/// Each line is a JSON object like
+/// `{"time":1737721800,"timers":[{"id":1,"message":"Tea","remaining_seconds":42,...}]}`
+/// and is flushed immediately so consumers see live updates.
+fn write_jsonl_tick(
+    out: &mut impl Write,
+    timers: &[database::Timer],
+    now: time::OffsetDateTime,
+) -> io::Result<()> {
+    let mut sorted: Vec<&database::Timer> = timers.iter().collect();
+    sorted.sort_by_key(|t| t.due_at);
+
+    let timers: Vec<serde_json::Value> = sorted
+        .iter()
+        .map(|timer| {
+            serde_json::json!({
+                "id": timer.id,
+                "message": timer.message,
+                "remaining_seconds": timer.remaining_seconds(now),
+                "due_at": timer.due_at.unix_timestamp(),
+                "urgent": timer.urgent,
+                "sound": timer.sound,
+                "recurring": timer.recurring,
+                "tags": timer.tags,
+            })
+        })
+        .collect();
+    let tick = serde_json::json!({
+        "time": now.unix_timestamp(),
+        "timers": timers,
+    });
+
+    writeln!(out, "{}", tick)?;
+    out.flush()
+}
+
+/// Shows a live, self-refreshing view of the soonest timers.
+///
+/// Reloads the database every second so changes made from other terminals show
+/// up, redraws using plain ANSI escapes, and exits once no timers remain.
 ///
-/// Shows the last 20 completed timers (most recent first) with information about
-/// when they were completed and their flags. This allows users to see timers they
-/// may have missed if notifications were disabled.
+/// With `jsonl`, each tick is instead written as one JSON line covering all active
+/// timers (ignoring `limit`), ending with a tick with no timers. The stream also
+/// stops cleanly when the consumer closes the pipe.
+///
+/// # Arguments
+///
+/// * `limit` - Maximum number of timers to show
+/// * `format` - Optional `--format` template for the timer lines
+/// * `jsonl` - Emit JSON Lines instead of redrawing the screen
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+/// * `autostart` - Starts the daemon while there are active timers
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on success, or an error if the database cannot be loaded.
-fn show_history() -> Result<(), Box<dyn std::error::Error>> {
-    let db = Database::load()?;
-
-    if db.history.is_empty() {
-        println!("No completed timers in history");
-        return Ok(());
-    }
+/// Returns `Ok(())` when no timers remain, or an error if the database cannot be
+/// loaded or the daemon cannot be started.
+fn show_top<S>(
+    limit: usize,
+    format: Option<&str>,
+    jsonl: bool,
+    raw: bool,
+    autostart: &mut daemon::Autostart<S>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: FnMut() -> Result<(), Box<dyn std::error::Error>>,
+{
+    let mut daemon_checked = false;
+    let mut stdout = io::stdout().lock();
+    let seconds_threshold_mins = config::show_seconds_threshold_mins();
 
-    println!("Recently completed timers:");
-    for timer in &db.history {
+    loop {
+        let db = Database::load()?;
         let now = time::OffsetDateTime::now_utc();
-        let elapsed = now - timer.due_at;
-        let elapsed_secs = elapsed.whole_seconds().abs();
 
-        let time_ago = if elapsed_secs < SECONDS_PER_MINUTE {
-            "< 1m".to_string()
+        if jsonl {
+            match write_jsonl_tick(&mut stdout, &db.timers, now) {
+                Ok(()) => {}
+                // The consumer went away (e.g. `| head`), which is a normal way to stop
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+            if db.timers.is_empty() {
+                return Ok(());
+            }
         } else {
-            format_duration(elapsed_secs, i64::MAX)
-        };
+            if db.timers.is_empty() {
+                writeln!(stdout, "No active timers")?;
+                return Ok(());
+            }
 
-        println!(
-            "  #{}: \"{}\" - completed {} ago{}",
-            timer.id,
-            timer.message,
-            time_ago,
-            format_flags(timer)
-        );
-    }
+            let frame =
+                render_top_frame(&db.timers, now, limit, format, seconds_threshold_mins, raw)?;
+            write!(stdout, "{}{}", ANSI_CLEAR_SCREEN, frame)?;
+            stdout.flush()?;
+        }
 
-    Ok(())
+        // Ensure daemon is running so the timers shown actually fire
+        if !daemon_checked {
+            autostart.for_timers(&db)?;
+            daemon_checked = true;
+        }
+
+        thread::sleep(Duration::from_secs(1));
+    }
 }
 
-/// Clears all active timers from the database.
+/// Calls `active_count` every `poll` until it reports no active timers or
+/// `timeout` has passed.
 ///
-/// Uses a database transaction to atomically remove all timers. Timers are not
-/// added to history. Displays the count of cleared timers.
+/// # Arguments
+///
+/// * `active_count` - Returns how many timers are active right now
+/// * `timeout` - Give up after this long (`None` = wait as long as it takes)
+/// * `poll` - Time between checks
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on success, or an error if the database transaction fails.
-fn clear_timers() -> Result<(), Box<dyn std::error::Error>> {
-    let count = Database::with_transaction(|db| {
-        let count = db.timers.len();
-        db.clear_all();
-        Ok(count)
-    })?;
+/// Returns `Ok(true)` once no timers are active, or `Ok(false)` if the timeout
+/// passed first.
+///
+/// # Errors
+///
+/// Returns the first error from `active_count`.
+fn wait_until_empty<C>(
+    mut active_count: C,
+    timeout: Option<Duration>,
+    poll: Duration,
+) -> Result<bool, Box<dyn std::error::Error>>
+where
+    C: FnMut() -> Result<usize, Box<dyn std::error::Error>>,
+{
+    let start = std::time::Instant::now();
 
-    println!("Cleared {} timer(s)", count);
+    loop {
+        if active_count()? == 0 {
+            return Ok(true);
+        }
 
-    Ok(())
+        let elapsed = start.elapsed();
+        match timeout {
+            Some(timeout) if elapsed >= timeout => return Ok(false),
+            Some(timeout) => thread::sleep(poll.min(timeout - elapsed)),
+            None => thread::sleep(poll),
+        }
+    }
 }
 
-/// Clears the history of completed timers.
+/// Returns how many active timers will finish on their own; recurring timers
+/// never do, so `wait` doesn't wait for them.
+fn one_off_count(db: &Database) -> usize {
+    db.timers.iter().filter(|t| !t.recurring).count()
+}
+
+/// Blocks until no one-off timers are active, for scripts that should continue
+/// only once every break is over. Recurring timers are left running.
 ///
-/// Uses a database transaction to atomically remove all entries from the history.
-/// Displays the count of cleared history entries. Does not affect active timers.
+/// # Arguments
+///
+/// * `timeout` - Give up after this long (e.g. "30m")
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on success, or an error if the database transaction fails.
-fn clear_history() -> Result<(), Box<dyn std::error::Error>> {
-    let count = Database::with_transaction(|db| {
-        let count = db.history.len();
-        db.clear_history();
-        Ok(count)
-    })?;
+/// Returns `Ok(())` once no one-off timers are active, or an error if the timeout
+/// passes first, the timeout is invalid, or the database cannot be loaded.
+fn wait_for_timers(timeout: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let timeout_secs = timeout.map(parser::parse_duration).transpose()?;
 
-    println!("Cleared {} completed timer(s) from history", count);
+    // The daemon has to run for the timers to ever finish
+    if one_off_count(&Database::load()?) > 0 {
+        daemon::ensure_daemon_running()?;
+    }
+
+    let done = wait_until_empty(
+        || Ok(one_off_count(&Database::load()?)),
+        timeout_secs.map(Duration::from_secs),
+        Duration::from_secs(WAIT_POLL_SECS),
+    )?;
+    if !done {
+        let remaining = one_off_count(&Database::load()?);
+        return Err(format!(
+            "Timed out with {} timer{} still active",
+            remaining,
+            if remaining == 1 { "" } else { "s" }
+        )
+        .into());
+    }
 
+    println!("No active one-off timers");
     Ok(())
 }
 
-/// Shows the status of the daemon and active timers.
+/// Removes all expired timers without adding them to history.
 ///
-/// Checks if the daemon is running and displays the count of active timers.
-/// If the daemon is not running but there are active timers, automatically
-/// restarts the daemon to ensure timers are monitored.
+/// Unlike `clear`, timers that are still counting down are kept. Useful for
+/// timers that expired while the daemon wasn't running.
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on success, or an error if the database cannot be loaded
-/// or the daemon cannot be started.
-fn show_status() -> Result<(), Box<dyn std::error::Error>> {
-    let db = Database::load()?;
-    let timer_count = db.timers.len();
+/// Returns `Ok(())` on success, or an error if the database transaction fails.
+fn remove_expired_timers() -> Result<(), Box<dyn std::error::Error>> {
+    let removed = Database::with_transaction(|db| Ok(db.remove_expired_timers()))?;
 
-    if daemon::is_daemon_running()? {
-        println!("Daemon is running");
-        println!("Active timers: {}", timer_count);
-    } else {
-        println!("Daemon is not running");
-        if timer_count > 0 {
-            println!("Active timers: {} (restarting daemon...)", timer_count);
-            daemon::ensure_daemon_running()?;
-            println!("Daemon restarted");
-        } else {
-            println!("Active timers: 0");
+    println!("Removed {} expired timer(s)", removed.len());
+
+    Ok(())
+}
+
+/// Returns the active timer IDs closest to `id`, at most two, for suggesting
+/// what a mistyped ID was meant to be.
+///
+/// IDs are close when their digits differ by one edit (a digit added, dropped or
+/// changed, so `12` suggests `2` and `1`); ties go to the numerically nearest ID.
+fn nearest_ids(id: u32, ids: &[u32]) -> Vec<u32> {
+    let typed = id.to_string();
+    let mut close: Vec<(usize, u32, u32)> = ids
+        .iter()
+        .filter(|candidate| **candidate != id)
+        .map(|candidate| {
+            (
+                digit_edit_distance(&typed, &candidate.to_string()),
+                candidate.abs_diff(id),
+                *candidate,
+            )
+        })
+        .filter(|(distance, _, _)| *distance <= 1)
+        .collect();
+    close.sort_unstable();
+    close.into_iter().take(2).map(|(_, _, id)| id).collect()
+}
+
+/// Returns the Levenshtein distance between two strings of ASCII digits.
+fn digit_edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.bytes().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != *y);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
         }
+        previous = current;
     }
+    previous[b.len()]
+}
 
-    Ok(())
+/// Formats the message for an active timer ID that doesn't exist, suggesting
+/// the closest of `ids` (see `nearest_ids`).
+fn not_found_message(id: u32, ids: &[u32]) -> String {
+    match nearest_ids(id, ids).as_slice() {
+        [] => format!("Timer #{} not found", id),
+        [only] => format!("Timer #{} not found. Did you mean #{}?", id, only),
+        [first, second, ..] => format!(
+            "Timer #{} not found. Did you mean #{} or #{}?",
+            id, first, second
+        ),
+    }
 }
 
-/// Manually starts the daemon process.
+/// Returns `not_found_message` for `id` with the IDs of the active timers.
 ///
-/// Spawns a new daemon process to monitor timers. This is typically called
-/// automatically when timers are created, but can be manually invoked if needed.
-/// If the daemon is already running, this has no effect.
+/// A database that can't be loaded just means no suggestions.
+fn timer_not_found(id: u32) -> String {
+    let ids: Vec<u32> = Database::load()
+        .map(|db| db.timers.iter().map(|timer| timer.id).collect())
+        .unwrap_or_default();
+    not_found_message(id, &ids)
+}
+
+/// Removes a timer by its ID.
+///
+/// Uses a database transaction to atomically remove the specified timer.
+/// The timer is removed without adding it to history (unlike timer completion).
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer to remove
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on success, or an error if the daemon process cannot be spawned.
-fn start_daemon() -> Result<(), Box<dyn std::error::Error>> {
-    daemon::start_daemon_process()?;
-    println!("Daemon started");
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error
+/// if the database transaction fails.
+fn remove_timer(id: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let timer_opt = Database::with_transaction(|db| Ok(db.remove_timer(id)))?;
+
+    if let Some(timer) = timer_opt {
+        println!("Removed timer #{}: \"{}\"", timer.id, timer.message);
+    } else {
+        println!("{}", timer_not_found(id));
+    }
+
     Ok(())
 }
 
-/// Generates shell completion scripts for the specified shell.
+/// Sets a timer's remaining time to an exact duration and sets or clears its
+/// flags, all in one transaction.
 ///
-/// This function outputs the completion script to stdout, which can be saved
-/// or sourced directly. Supports bash, zsh, fish, and PowerShell.
+/// Unlike adding time, a new duration replaces the remaining time: the timer
+/// becomes due the given duration from now. The message is not changed.
 ///
 /// # Arguments
 ///
-/// * `shell` - The shell type to generate completions for
+/// * `id` - The numeric ID of the timer to edit
+/// * `duration` - The new remaining time (e.g. "10m", "1h30m"), if any
+/// * `changes` - The flags to set or clear
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
 ///
-/// # Examples
+/// # Returns
 ///
-/// ```bash
-/// # Generate and install bash completions
-/// breakrs completions bash > ~/.local/share/bash-completion/completions/breakrs
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error
+/// if the duration is invalid or the database transaction fails.
+fn edit_timer(
+    id: u32,
+    duration: Option<&str>,
+    changes: database::FlagChanges,
+    raw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let seconds = duration.map(parser::parse_duration).transpose()?;
+
+    let timer = Database::with_transaction(|db| {
+        if let Some(seconds) = seconds
+            && db
+                .set_duration(id, seconds)
+                .map_err(|e| format!("Failed to edit timer: {}", e))?
+                .is_none()
+        {
+            return Ok(None);
+        }
+        Ok(db.set_flags(id, changes))
+    })?;
+
+    let Some(timer) = timer else {
+        println!("{}", timer_not_found(id));
+        return Ok(());
+    };
+    if let Some(seconds) = seconds {
+        println!(
+            "Timer #{}: \"{}\" now due in {}",
+            timer.id,
+            timer.message,
+            format_duration(seconds as i64, 5, raw)
+        );
+    }
+    if changes != database::FlagChanges::default() {
+        let flags = format_flags(&timer);
+        println!(
+            "Timer #{}: \"{}\" flags now{}",
+            timer.id,
+            timer.message,
+            if flags.is_empty() { " [none]" } else { &flags }
+        );
+    }
+    if seconds.is_some() {
+        // Ensure daemon is running
+        daemon::ensure_daemon_running()?;
+    }
+
+    Ok(())
+}
+
+/// Turns a `--flag`/`--no-flag` pair into a flag change (`None` if neither was given).
+fn flag_change(set: bool, clear: bool) -> Option<bool> {
+    match (set, clear) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+/// Adds the saved `set-default` flags to the flags given for a new timer.
 ///
-/// # Generate and install zsh completions
-/// breakrs completions zsh > ~/.zsh/completion/_breakrs
+/// A default is skipped when the timer's own flags rule it out: `--background`
+/// timers keep their quiet preset, so they don't pick up the urgent or sound
+/// defaults, and `--nag`, `--urgent-after` and `--no-daemon` timers stay one-off
+/// rather than failing against a recurring default.
+fn with_defaults(
+    flags: TimerFlags,
+    notification: &NotificationOptions,
+    defaults: defaults::Defaults,
+) -> TimerFlags {
+    let one_off =
+        flags.no_daemon || notification.nag.is_some() || notification.urgent_after.is_some();
+    TimerFlags {
+        urgent: flags.urgent || (defaults.urgent && !flags.background),
+        sound: flags.sound || (defaults.sound && !flags.background),
+        recurring: flags.recurring || (defaults.recurring && !one_off),
+        silent: flags.silent || defaults.silent,
+        ..flags
+    }
+}
+
+/// Sets or clears the flags every new timer starts with and prints the result.
 ///
-/// # Generate and install fish completions
-/// breakrs completions fish > ~/.config/fish/completions/breakrs.fish
-/// ```
-fn generate_completions(shell: Shell) {
-    let mut cmd = Cli::command();
-    let bin_name = cmd.get_name().to_string();
-    generate(shell, &mut cmd, bin_name, &mut io::stdout());
+/// # Arguments
+///
+/// * `changes` - The defaults to set or clear (none just prints them)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the defaults file can't be read
+/// or written.
+fn set_defaults(changes: database::FlagChanges) -> Result<(), Box<dyn std::error::Error>> {
+    let path = defaults::defaults_path()?;
+    let mut saved = defaults::read(&path)?;
+    if changes != database::FlagChanges::default() {
+        saved.apply(changes);
+        defaults::write(&path, &saved)?;
+    }
+
+    let names = saved.names();
+    println!(
+        "New timers default to: {}",
+        if names.is_empty() {
+            "[none]".to_string()
+        } else {
+            names.join(", ")
+        }
+    );
+
+    Ok(())
+}
+
+/// Makes a timer repeat, or stops it from repeating, without recreating it.
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer
+/// * `recurring` - Whether the timer should repeat
+/// * `every` - A new interval (e.g. "25m"); the timer's duration is kept otherwise
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error
+/// if the interval is invalid or the database transaction fails.
+fn set_timer_recurring(
+    id: u32,
+    recurring: bool,
+    every: Option<&str>,
+    raw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let interval = every.map(parser::parse_duration).transpose()?;
+
+    let timer = Database::with_transaction(|db| {
+        db.set_recurring(id, recurring, interval)
+            .map_err(|e| format!("Failed to update timer: {}", e).into())
+    })?;
+
+    match timer {
+        Some(timer) if timer.recurring => println!(
+            "Timer #{}: \"{}\" now repeats every {}",
+            timer.id,
+            timer.message,
+            format_duration(timer.duration_seconds as i64, 5, raw)
+        ),
+        Some(timer) => println!(
+            "Timer #{}: \"{}\" no longer repeats",
+            timer.id, timer.message
+        ),
+        None => println!("{}", timer_not_found(id)),
+    }
+
+    Ok(())
+}
+
+/// Pins a timer to an absolute time, such as `17:30` or `tomorrow at 9am`.
+///
+/// The time is read on the local clock. The timer's duration becomes the time
+/// from its creation to then, so it reads as if it had been set for that time.
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer
+/// * `to_absolute` - The time it should be due
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error
+/// if the time is invalid or can't be pinned, or the database transaction fails.
+fn convert_to_absolute(
+    id: u32,
+    to_absolute: &str,
+    raw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let now = local_now();
+    let due_at = parser::parse_future_time(to_absolute, now)
+        .map_err(|e| e.context("Invalid --to-absolute"))?;
+
+    match Database::with_transaction(|db| Ok(db.pin_due_at(id, due_at, now)?))? {
+        Some(timer) => {
+            let when = if due_at.date() == now.date() {
+                due_at.format(time::macros::format_description!(
+                    "today at [hour]:[minute]"
+                ))?
+            } else {
+                due_at.format(time::macros::format_description!(
+                    "on [year]-[month]-[day] at [hour]:[minute]"
+                ))?
+            };
+            println!(
+                "Timer #{}: \"{}\" now due {} (in {})",
+                timer.id,
+                timer.message,
+                when,
+                format_duration(timer.remaining_seconds(now), 5, raw)
+            );
+            daemon::ensure_daemon_running()?;
+        }
+        None => println!("{}", timer_not_found(id)),
+    }
+
+    Ok(())
+}
+
+/// Renumbers a timer, keeping its message, flags and due time.
+///
+/// # Arguments
+///
+/// * `id` - The current numeric ID of the timer
+/// * `new_id` - The ID to assign
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error
+/// if the new ID is taken or the database transaction fails.
+fn move_timer(id: u32, new_id: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let timer = Database::with_transaction(|db| {
+        db.move_timer(id, new_id)
+            .map_err(|e| format!("Failed to move timer: {}", e).into())
+    })?;
+
+    if let Some(timer) = timer {
+        println!(
+            "Moved timer #{} to #{}: \"{}\"",
+            id, timer.id, timer.message
+        );
+    } else {
+        println!("{}", timer_not_found(id));
+    }
+
+    Ok(())
+}
+
+/// Pauses a timer, freezing its remaining time until it is resumed.
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer
+/// * `for_duration` - Resume automatically after this long (e.g. "10m"); without
+///   it the timer stays paused until `resume`
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error
+/// if the duration is invalid or longer than a year, or the database transaction
+/// fails.
+fn pause_timer(
+    id: u32,
+    for_duration: Option<&str>,
+    raw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let for_secs = for_duration
+        .map(|value| {
+            parser::parse_duration(value)
+                .map_err(|e| e.to_string())
+                .and_then(database::check_duration_limit)
+                .map_err(|e| format!("Invalid --for: {}", e))
+        })
+        .transpose()?;
+
+    let now = time::OffsetDateTime::now_utc();
+    let resume_at = for_secs.map(|secs| now + time::Duration::seconds(secs as i64));
+    let timer = Database::with_transaction(|db| Ok(db.pause_timer(id, now, resume_at)))?;
+
+    let Some(timer) = timer else {
+        println!("{}", timer_not_found(id));
+        return Ok(());
+    };
+    let left = format_duration(timer.remaining_seconds(now).max(0), 5, raw);
+    match for_secs {
+        Some(secs) => {
+            println!(
+                "Paused timer #{}: \"{}\" with {} left; resumes in {}",
+                timer.id,
+                timer.message,
+                left,
+                format_duration(secs as i64, 5, raw)
+            );
+            // The daemon does the resuming
+            daemon::ensure_daemon_running()?;
+        }
+        None => println!(
+            "Paused timer #{}: \"{}\" with {} left (resume with `breakrs resume {}`)",
+            timer.id, timer.message, left, timer.id
+        ),
+    }
+
+    Ok(())
+}
+
+/// Resumes a paused timer with the time it had left when it was paused.
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success (whether or not the timer was found or paused), or
+/// an error if the database transaction fails.
+fn resume_timer(id: u32, raw: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let now = time::OffsetDateTime::now_utc();
+    let resumed = Database::with_transaction(|db| {
+        let was_paused = db.timers.iter().any(|t| t.id == id && t.is_paused());
+        Ok(db.resume_timer(id, now).map(|timer| (was_paused, timer)))
+    })?;
+
+    match resumed {
+        Some((true, timer)) => {
+            println!(
+                "Resumed timer #{}: \"{}\" now due in {}",
+                timer.id,
+                timer.message,
+                format_duration(timer.remaining_seconds(now).max(0), 5, raw)
+            );
+            daemon::ensure_daemon_running()?;
+        }
+        Some((false, timer)) => println!("Timer #{} is not paused", timer.id),
+        None => println!("{}", timer_not_found(id)),
+    }
+
+    Ok(())
+}
+
+/// Starts a timer from history again with the same message, duration and
+/// settings.
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer in history
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if no history entry has the ID, the
+/// timer can't be added, or the daemon cannot be started.
+fn replay_timer(id: u32, raw: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let timer = Database::with_transaction(|db| {
+        db.replay_timer(id)
+            .map_err(|e| format!("Failed to replay timer: {}", e).into())
+    })?
+    .ok_or_else(|| format!("Timer #{} not found in history", id))?;
+
+    println!(
+        "Replaying #{} as timer #{}: \"{}\" ({}){}",
+        id,
+        timer.id,
+        timer.message,
+        format_duration(timer.duration_seconds as i64, 5, raw),
+        format_flags(&timer)
+    );
+    daemon::ensure_daemon_running()?;
+
+    Ok(())
+}
+
+/// Moves pinned timers ahead of the others, keeping the order within each group.
+fn pinned_first(timers: &mut [&database::Timer]) {
+    timers.sort_by_key(|timer| !timer.pinned);
+}
+
+/// Pins or unpins a timer.
+///
+/// Pinned timers are listed first by `list`; when and how they fire is unchanged.
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer
+/// * `pinned` - Whether to pin or unpin it
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error
+/// if the database transaction fails.
+fn pin_timer(id: u32, pinned: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let timer = Database::with_transaction(|db| Ok(db.update_timer(id, |t| t.pinned = pinned)))?;
+
+    match timer {
+        Some(timer) => println!(
+            "{} timer #{}: \"{}\"",
+            if pinned { "Pinned" } else { "Unpinned" },
+            id,
+            timer.message
+        ),
+        None => println!("{}", timer_not_found(id)),
+    }
+
+    Ok(())
+}
+
+/// Formats how long one timer has left, for `break remaining`.
+///
+/// # Arguments
+///
+/// * `db` - The database holding the timer
+/// * `id` - The numeric ID of the timer
+/// * `now` - The reference time
+/// * `human` - Format like `list` (`4m 30s`) instead of whole seconds
+/// * `raw` - With `human`, show minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns the remaining time (`0` once the timer is due), or `None` if no timer
+/// has that ID.
+fn remaining_text(
+    db: &Database,
+    id: u32,
+    now: time::OffsetDateTime,
+    human: bool,
+    raw: bool,
+) -> Option<String> {
+    let timer = db.timers.iter().find(|t| t.id == id)?;
+    let seconds = timer.remaining_seconds(now).max(0);
+
+    Some(if human {
+        format_duration(seconds, config::show_seconds_threshold_mins(), raw)
+    } else {
+        seconds.to_string()
+    })
+}
+
+/// Prints only how long one timer has left, for scripts and prompts.
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error (so the process exits non-zero) if
+/// the timer doesn't exist or the database cannot be loaded.
+fn show_remaining(id: u32, human: bool, raw: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::load()?;
+    let text = remaining_text(&db, id, time::OffsetDateTime::now_utc(), human, raw)
+        .ok_or_else(|| NotFoundError(timer_not_found(id)))?;
+    println!("{}", text);
+
+    Ok(())
+}
+
+/// Renames a timer by replacing its message.
+///
+/// Only the message changes: flag-like words in the new message (e.g. `--urgent`)
+/// are kept as text, so scripts can rely on the timer's flags staying as they were.
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer to rename
+/// * `message` - The new message
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error
+/// if the message is empty or the database transaction fails.
+fn rename_timer(id: u32, message: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err("New message cannot be empty".into());
+    }
+
+    let old_message =
+        Database::with_transaction(|db| Ok(db.edit_message(id, message.to_string())))?;
+
+    if let Some(old_message) = old_message {
+        println!(
+            "Renamed timer #{}: \"{}\" -> \"{}\"",
+            id, old_message, message
+        );
+    } else {
+        println!("{}", timer_not_found(id));
+    }
+
+    Ok(())
+}
+
+/// The editable fields of a timer, as shown in the `amend` form.
+#[derive(Debug, Clone, PartialEq)]
+struct AmendForm {
+    message: String,
+    /// Remaining time as displayed (e.g. "4m 30s"); only parsed if it was changed
+    remaining: String,
+    urgent: bool,
+    sound: bool,
+    recurring: bool,
+    silent: bool,
+}
+
+impl AmendForm {
+    /// Captures a timer's current values.
+    fn from_timer(timer: &database::Timer, now: time::OffsetDateTime) -> Self {
+        let remaining_secs = timer.remaining_seconds(now);
+        let remaining = if remaining_secs > 0 {
+            format_duration(remaining_secs, i64::MAX, false)
+        } else {
+            "EXPIRED".to_string()
+        };
+
+        Self {
+            message: timer.message.clone(),
+            remaining,
+            urgent: timer.urgent,
+            sound: timer.sound,
+            recurring: timer.recurring,
+            silent: timer.silent,
+        }
+    }
+
+    /// Renders the form text opened in the editor.
+    fn render(&self, id: u32) -> String {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        format!(
+            "# Amend timer #{}: edit the values, then save and close the editor.\n\
+             # Lines starting with '#' are ignored; unchanged values are kept.\n\
+             message: {}\n\
+             remaining: {}\n\
+             urgent: {}\n\
+             sound: {}\n\
+             recurring: {}\n\
+             silent: {}\n",
+            id,
+            self.message,
+            self.remaining,
+            yes_no(self.urgent),
+            yes_no(self.sound),
+            yes_no(self.recurring),
+            yes_no(self.silent)
+        )
+    }
+
+    /// Parses an edited form; fields missing from `text` keep their `original` value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for unknown fields, lines without a `field: value` shape,
+    /// flags that aren't yes/no, or an empty message.
+    fn parse(text: &str, original: &Self) -> Result<Self, String> {
+        let mut form = original.clone();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (field, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("Expected 'field: value', got '{}'", line))?;
+            let value = value.trim();
+            let flag = || match value.to_lowercase().as_str() {
+                "yes" | "y" | "true" | "on" => Ok(true),
+                "no" | "n" | "false" | "off" => Ok(false),
+                _ => Err(format!("'{}' must be yes or no, got '{}'", field, value)),
+            };
+
+            match field.trim().to_lowercase().as_str() {
+                "message" => form.message = value.to_string(),
+                "remaining" => form.remaining = value.to_string(),
+                "urgent" => form.urgent = flag()?,
+                "sound" => form.sound = flag()?,
+                "recurring" => form.recurring = flag()?,
+                "silent" => form.silent = flag()?,
+                other => return Err(format!("Unknown field '{}'", other)),
+            }
+        }
+
+        if form.message.is_empty() {
+            return Err("Message cannot be empty".to_string());
+        }
+        Ok(form)
+    }
+}
+
+/// Applies the differences between two `amend` forms to a timer.
+///
+/// A changed remaining time goes through `Database::set_duration` (like
+/// `break edit`), a changed message through `Database::edit_message` (like
+/// `break rename`); flags are set directly.
+///
+/// # Returns
+///
+/// Returns `Ok(Some(Timer))` with the amended timer, or `Ok(None)` if no timer
+/// with the given ID exists.
+///
+/// # Errors
+///
+/// Returns an error if the new remaining time isn't a valid duration.
+fn apply_amendment(
+    db: &mut Database,
+    id: u32,
+    original: &AmendForm,
+    edited: &AmendForm,
+) -> Result<Option<database::Timer>, String> {
+    if !db.timers.iter().any(|t| t.id == id) {
+        return Ok(None);
+    }
+
+    if edited.remaining != original.remaining {
+        let seconds = parser::parse_duration(&edited.remaining)
+            .map_err(|e| format!("Invalid remaining time '{}': {}", edited.remaining, e))?;
+        db.set_duration(id, seconds)?;
+    }
+    if edited.message != original.message {
+        db.edit_message(id, edited.message.clone());
+    }
+
+    Ok(db.update_timer(id, |t| {
+        t.urgent = edited.urgent;
+        t.sound = edited.sound;
+        t.recurring = edited.recurring;
+        t.silent = edited.silent;
+    }))
+}
+
+/// Opens `text` in the user's editor (`$VISUAL`, then `$EDITOR`) and returns the
+/// saved result.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file can't be written or read, or the editor
+/// can't be started or exits unsuccessfully.
+fn edit_in_editor(text: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let default_editor = if cfg!(windows) { "notepad" } else { "vi" };
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| default_editor.to_string());
+
+    // Editors given with arguments (e.g. "code --wait") are split on whitespace
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().ok_or("$EDITOR is empty")?;
+
+    let path = std::env::temp_dir().join(format!("breakrs-amend-{}.txt", process::id()));
+    std::fs::write(&path, text)?;
+    let status = process::Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status();
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    match status {
+        Ok(status) if status.success() => Ok(edited?),
+        Ok(status) => Err(format!("Editor exited with {}; timer left unchanged", status).into()),
+        Err(e) => Err(format!("Failed to start editor '{}': {} (set $EDITOR)", program, e).into()),
+    }
+}
+
+/// Edits a timer's message, remaining time, and flags in one go through `$EDITOR`.
+///
+/// The editor opens a small form pre-filled with the timer's current values; saving
+/// applies whatever changed. Requires an interactive terminal.
+///
+/// # Arguments
+///
+/// * `id` - The numeric ID of the timer to amend
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success (whether or not the timer was found), or an error if
+/// not run in a terminal, the editor fails, or the edited form is invalid.
+fn amend_timer(id: u32) -> Result<(), Box<dyn std::error::Error>> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(format!(
+            "amend needs an interactive terminal; use `break edit {id} --duration <DURATION>` \
+             or `break rename {id} <MESSAGE>` in scripts"
+        )
+        .into());
+    }
+
+    let db = Database::load()?;
+    let Some(timer) = db.timers.iter().find(|t| t.id == id) else {
+        println!("{}", timer_not_found(id));
+        return Ok(());
+    };
+    let original = AmendForm::from_timer(timer, time::OffsetDateTime::now_utc());
+
+    let edited = AmendForm::parse(&edit_in_editor(&original.render(id))?, &original)?;
+    if edited == original {
+        println!("No changes to timer #{}", id);
+        return Ok(());
+    }
+
+    let amended =
+        Database::with_transaction(|db| Ok(apply_amendment(db, id, &original, &edited)?))?;
+    match amended {
+        Some(timer) => {
+            println!("Amended timer #{}:", id);
+            println!(
+                "{}",
+                render_template(
+                    DEFAULT_LIST_FORMAT,
+                    &timer,
+                    time::OffsetDateTime::now_utc(),
+                    config::show_seconds_threshold_mins(),
+                    false
+                )?
+            );
+            daemon::ensure_daemon_running()?;
+        }
+        // Fired or removed while the editor was open
+        None => println!("{}", timer_not_found(id)),
+    }
+
+    Ok(())
+}
+
+/// Returns the history entries created within `created`, in the requested order,
+/// at most `limit` of them.
+///
+/// Sorting only affects what is displayed; the stored history stays newest-first.
+/// Entries recorded without a `completed_at` (older versions) sort by their
+/// `due_at`, and ties keep their stored order.
+///
+/// # Arguments
+///
+/// * `db` - The database whose history is shown
+/// * `limit` - Keep at most this many entries after sorting (clamped to what is stored)
+/// * `sort` - The order of the entries
+/// * `reverse` - Reverse the order before the limit is applied
+/// * `created` - Only entries created within these bounds are kept
+fn history_entries(
+    db: &Database,
+    limit: Option<usize>,
+    sort: HistorySort,
+    reverse: bool,
+    created: CreatedRange,
+) -> Vec<&database::Timer> {
+    let mut entries: Vec<&database::Timer> =
+        db.history.iter().filter(|t| created.contains(t)).collect();
+    let completed_at = |t: &database::Timer| t.completed_at.unwrap_or(t.due_at);
+
+    match sort {
+        HistorySort::Recent => entries.sort_by_key(|t| std::cmp::Reverse(completed_at(t))),
+        HistorySort::Oldest => entries.sort_by_key(|t| completed_at(t)),
+        HistorySort::Duration => entries.sort_by_key(|t| std::cmp::Reverse(t.duration_seconds)),
+    }
+    if reverse {
+        entries.reverse();
+    }
+
+    entries.truncate(limit.unwrap_or(entries.len()));
+    entries
+}
+
+/// Displays the history of recently completed timers.
+///
+/// Shows the last 20 completed timers (most recent first unless `sort` says
+/// otherwise) with information about when they were completed and their flags.
+/// This allows users to see timers they may have missed if notifications were
+/// disabled.
+///
+/// # Arguments
+///
+/// * `limit` - Show at most this many entries
+/// * `json` - Print the entries as a JSON array (including `completed_at`) for
+///   scripts; `Some(true)` prints it on a single line
+/// * `sort` - The order of the entries
+/// * `reverse` - Reverse the sort order
+/// * `created` - Only entries created within these bounds are shown
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the database cannot be loaded.
+fn show_history(
+    limit: Option<usize>,
+    json: Option<bool>,
+    sort: HistorySort,
+    reverse: bool,
+    created: CreatedRange,
+    raw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::load()?;
+    let entries = history_entries(&db, limit, sort, reverse, created);
+
+    if let Some(compact) = json {
+        println!("{}", to_json(&entries, compact)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No completed timers in history");
+        return Ok(());
+    }
+
+    println!("Recently completed timers:");
+    for timer in entries {
+        let now = time::OffsetDateTime::now_utc();
+        let elapsed_secs = timer.remaining_seconds(now).abs();
+
+        let time_ago = if elapsed_secs < SECONDS_PER_MINUTE {
+            "< 1m".to_string()
+        } else {
+            format_duration(elapsed_secs, i64::MAX, raw)
+        };
+
+        println!(
+            "  #{}: \"{}\" - completed {} ago{}",
+            timer.id,
+            timer.message,
+            time_ago,
+            format_flags(timer)
+        );
+    }
+
+    Ok(())
+}
+
+/// Adds the same amount of time to every active timer, e.g. when a meeting runs
+/// long and all breaks should slide.
+///
+/// # Arguments
+///
+/// * `duration` - The time to add (e.g. "5m")
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the duration is invalid, a timer
+/// would grow too long, or the database transaction fails.
+fn extend_all_timers(duration: &str, raw: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let seconds = parser::parse_duration(duration)?;
+
+    let count = Database::with_transaction(|db| {
+        db.extend_all(seconds)
+            .map_err(|e| format!("Failed to extend timers: {}", e).into())
+    })?;
+
+    println!(
+        "Extended {} timer(s) by {}",
+        count,
+        format_duration(seconds as i64, 5, raw)
+    );
+
+    Ok(())
+}
+
+/// Snoozes every timer that went off: expired active timers and timers completed
+/// within `within` are all due again after `duration`, in one transaction.
+///
+/// # Arguments
+///
+/// * `duration` - The snooze length (e.g. "10m")
+/// * `within` - How far back completed timers count (e.g. "1h")
+/// * `raw` - Show durations in minutes without rolling up into hours (`--raw`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if a duration is invalid, too many
+/// timers would be active, or the database transaction fails.
+fn snooze_all_timers(
+    duration: &str,
+    within: &str,
+    raw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let seconds = parser::parse_duration(duration)?;
+    let within = parser::parse_duration(within).map_err(|e| e.context("Invalid --within"))?;
+    let now = time::OffsetDateTime::now_utc();
+    // A lookback past the earliest representable date counts every completed timer
+    let completed_since = now.saturating_sub(time::Duration::seconds(
+        i64::try_from(within).unwrap_or(i64::MAX),
+    ));
+
+    let snoozed = Database::with_transaction(|db| {
+        db.snooze_all(seconds, completed_since, now)
+            .map_err(|e| format!("Failed to snooze timers: {}", e).into())
+    })?;
+
+    if snoozed.is_empty() {
+        println!("No expired timers to snooze");
+        return Ok(());
+    }
+
+    println!(
+        "Snoozed {} timer(s) for {}",
+        snoozed.len(),
+        format_duration(seconds as i64, 5, raw)
+    );
+    daemon::ensure_daemon_running()?;
+
+    Ok(())
+}
+
+/// Clears all active timers from the database, or only those with a tag.
+///
+/// Uses a database transaction to atomically remove the timers. Timers are not
+/// added to history. Displays the count of cleared timers.
+///
+/// # Arguments
+///
+/// * `tag` - When set, only timers carrying this tag are cleared
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the database transaction fails.
+fn clear_timers(tag: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let count = Database::with_transaction(|db| match tag {
+        Some(tag) => Ok(db.clear_tagged(tag)),
+        None => {
+            let count = db.timers.len();
+            db.clear_all();
+            Ok(count)
+        }
+    })?;
+
+    match tag {
+        Some(tag) => println!("Cleared {} timer(s) tagged '{}'", count, tag),
+        None => println!("Cleared {} timer(s)", count),
+    }
+
+    Ok(())
+}
+
+/// Clears the history of completed timers.
+///
+/// Uses a database transaction to atomically remove all entries from the history.
+/// Displays the count of cleared history entries. Does not affect active timers.
+///
+/// # Arguments
+///
+/// * `keep` - When set, only entries beyond the `keep` most recent are removed
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the database transaction fails.
+fn clear_history(keep: Option<usize>) -> Result<(), Box<dyn std::error::Error>> {
+    let count = Database::with_transaction(|db| match keep {
+        Some(keep) => Ok(db.truncate_history(keep)),
+        None => {
+            let count = db.history.len();
+            db.clear_history();
+            Ok(count)
+        }
+    })?;
+
+    match keep {
+        Some(keep) => println!(
+            "Cleared {} completed timer(s) from history, kept the {} most recent",
+            count, keep
+        ),
+        None => println!("Cleared {} completed timer(s) from history", count),
+    }
+
+    Ok(())
+}
+
+/// Deletes history entries older than a user-supplied age.
+///
+/// The age is parsed with the regular duration parser (e.g. "30d", "1h 30m") and
+/// matching entries are removed inside a database transaction.
+///
+/// # Arguments
+///
+/// * `older_than` - The age cutoff as duration text
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the duration cannot be parsed,
+/// reaches back before the earliest representable date, or the database
+/// transaction fails.
+fn purge_history(older_than: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let seconds = parser::parse_duration(older_than)?;
+    let cutoff = i64::try_from(seconds)
+        .ok()
+        .and_then(|seconds| {
+            time::OffsetDateTime::now_utc().checked_sub(time::Duration::seconds(seconds))
+        })
+        .ok_or_else(|| format!("Invalid --older-than: {} is too far back", older_than))?;
+
+    let count = Database::with_transaction(|db| Ok(db.purge_history(cutoff)))?;
+
+    println!(
+        "Purged {} completed timer(s) older than {}",
+        count, older_than
+    );
+
+    Ok(())
+}
+
+/// Checks the database file for inconsistencies without modifying it.
+///
+/// Prints each problem found. Timers that a normal load would silently discard
+/// are reported too, since the file is read without cleaning.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the database is consistent, or an error (so the process
+/// exits non-zero) if problems were found or the file can't be read.
+fn validate_database() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Database::db_path()?;
+    let db = Database::load_unvalidated()?;
+    let problems = db.problems();
+
+    if problems.is_empty() {
+        println!(
+            "{}: OK ({} active timer(s))",
+            path.display(),
+            db.timers.len()
+        );
+        return Ok(());
+    }
+
+    println!("{}:", path.display());
+    for problem in &problems {
+        println!("  {}", problem);
+    }
+    Err(format!("{} problem(s) found", problems.len()).into())
+}
+
+/// Shows the status of the daemon and active timers.
+///
+/// Checks if the daemon is running, which notification backend it uses, and
+/// displays the count of active timers.
+/// If the daemon is not running but there are active timers, automatically
+/// restarts the daemon to ensure timers are monitored.
+///
+/// # Arguments
+///
+/// * `count` - Print only the number of active timers (the daemon is still restarted)
+/// * `autostart` - Restarts the daemon when there are active timers
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the database cannot be loaded
+/// or the daemon cannot be started.
+fn show_status<S>(
+    count: bool,
+    autostart: &mut daemon::Autostart<S>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: FnMut() -> Result<(), Box<dyn std::error::Error>>,
+{
+    let db = Database::load()?;
+    let timer_count = db.timers.len();
+
+    if count {
+        println!("{}", format_count(&db));
+        return autostart.for_timers(&db);
+    }
+
+    if daemon::is_daemon_running()? {
+        println!("Daemon is running");
+        if let Some(backend) = daemon::running_backend()? {
+            println!("Notifications: {}", backend);
+        }
+        println!("Active timers: {}", timer_count);
+    } else {
+        println!("Daemon is not running");
+        if timer_count > 0 && !autostart.disabled {
+            println!("Active timers: {} (restarting daemon...)", timer_count);
+            autostart.for_timers(&db)?;
+            println!("Daemon restarted");
+        } else {
+            println!("Active timers: {}", timer_count);
+        }
+    }
+
+    Ok(())
+}
+
+/// Daemon and timer state shown by each `status --watch` frame.
+#[derive(Debug, Clone, PartialEq)]
+struct StatusSnapshot {
+    daemon_up: bool,
+    /// Notification backend the running daemon reported, if any
+    backend: Option<String>,
+    timer_count: usize,
+}
+
+impl StatusSnapshot {
+    /// Reads the current daemon state and active timer count.
+    fn read() -> Result<Self, Box<dyn std::error::Error>> {
+        let daemon_up = daemon::is_daemon_running()?;
+        Ok(Self {
+            daemon_up,
+            backend: if daemon_up {
+                daemon::running_backend()?
+            } else {
+                None
+            },
+            timer_count: Database::load()?.timers.len(),
+        })
+    }
+}
+
+/// Renders one `status --watch` frame.
+///
+/// # Arguments
+///
+/// * `snapshot` - The state to show
+/// * `last_change` - When the daemon last started or stopped while watching,
+///   and whether it was running afterwards
+/// * `now` - The local time of this frame
+fn render_status_frame(
+    snapshot: &StatusSnapshot,
+    last_change: Option<(bool, time::OffsetDateTime)>,
+    now: time::OffsetDateTime,
+) -> Result<String, time::error::Format> {
+    let clock = time::macros::format_description!("[hour]:[minute]:[second]");
+    let mut frame = String::new();
+
+    if snapshot.daemon_up {
+        frame.push_str("Daemon is running\n");
+        if let Some(backend) = &snapshot.backend {
+            frame.push_str(&format!("Notifications: {}\n", backend));
+        }
+    } else {
+        frame.push_str("Daemon is not running\n");
+    }
+    frame.push_str(&format!("Active timers: {}\n", snapshot.timer_count));
+
+    if let Some((up, at)) = last_change {
+        let change = if up { "started" } else { "stopped" };
+        frame.push_str(&format!("Daemon {} at {}\n", change, at.format(&clock)?));
+    }
+    frame.push_str(&format!(
+        "\nUpdated {}, every {}s (Ctrl-C to stop)\n",
+        now.format(&clock)?,
+        STATUS_WATCH_SECS
+    ));
+
+    Ok(frame)
+}
+
+/// Redraws the daemon state and active timer count every few seconds until
+/// interrupted, noting when the daemon starts or stops.
+///
+/// Unlike `status`, watching never restarts the daemon, so a daemon that dies
+/// stays visible as stopped.
+///
+/// # Returns
+///
+/// Only returns on error, e.g. if the database can't be loaded.
+fn watch_status() -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = io::stdout().lock();
+    let mut previous: Option<bool> = None;
+    let mut last_change = None;
+
+    loop {
+        let snapshot = StatusSnapshot::read()?;
+        let now = local_now();
+        if previous.is_some_and(|up| up != snapshot.daemon_up) {
+            last_change = Some((snapshot.daemon_up, now));
+        }
+        previous = Some(snapshot.daemon_up);
+
+        let frame = render_status_frame(&snapshot, last_change, now)?;
+        write!(stdout, "{}{}", ANSI_CLEAR_SCREEN, frame)?;
+        stdout.flush()?;
+
+        thread::sleep(Duration::from_secs(STATUS_WATCH_SECS));
+    }
+}
+
+/// Returns the `check` exit code: 0 if any timers are active, 1 if none.
+fn check_exit_code(db: &Database) -> i32 {
+    if db.timers.is_empty() { 1 } else { 0 }
+}
+
+/// Exits with 0 if any timers are active and 1 if none, for shell conditionals.
+///
+/// Prints nothing unless `verbose` is set, in which case it prints the number of
+/// active timers.
+///
+/// # Arguments
+///
+/// * `verbose` - Whether to also print the active timer count
+///
+/// # Returns
+///
+/// Only returns (with an error) if the database cannot be loaded; otherwise the
+/// process exits with the check result.
+///
+/// # Examples
+///
+/// ```ignore
+/// // break check && echo "timers running"
+/// check_timers(false)?;
+/// ```
+fn check_timers(verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::load()?;
+
+    if verbose {
+        println!("{}", format_count(&db));
+    }
+
+    process::exit(check_exit_code(&db));
+}
+
+/// Renders metrics in the Prometheus text exposition format.
+///
+/// `break_next_due_seconds` is left out when there are no active timers, and is
+/// negative when the soonest timer has already expired.
+///
+/// # Arguments
+///
+/// * `db` - The database to report on
+/// * `daemon_up` - Whether the daemon is running
+/// * `now` - The time remaining seconds are measured from
+fn render_metrics(db: &Database, daemon_up: bool, now: time::OffsetDateTime) -> String {
+    let mut metrics = String::new();
+    let mut gauge = |name: &str, help: &str, value: i64| {
+        metrics.push_str(&format!(
+            "# HELP {0} {1}\n# TYPE {0} gauge\n{0} {2}\n",
+            name, help, value
+        ));
+    };
+
+    gauge(
+        "break_active_timers",
+        "Number of active timers.",
+        db.timers.len() as i64,
+    );
+    gauge(
+        "break_history_total",
+        "Number of completed timers kept in history.",
+        db.history.len() as i64,
+    );
+    gauge(
+        "break_daemon_up",
+        "Whether the daemon is running (1) or not (0).",
+        i64::from(daemon_up),
+    );
+    if let Some(next) = db.timers.iter().map(|t| t.remaining_seconds(now)).min() {
+        gauge(
+            "break_next_due_seconds",
+            "Seconds until the soonest active timer is due.",
+            next,
+        );
+    }
+
+    metrics
+}
+
+/// Prints Prometheus text-format metrics, e.g. for a node exporter textfile
+/// collector.
+///
+/// Unlike `status`, this never starts the daemon.
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the database or daemon state
+/// cannot be read.
+fn show_metrics() -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::load()?;
+    let daemon_up = daemon::is_daemon_running()?;
+    print!(
+        "{}",
+        render_metrics(&db, daemon_up, time::OffsetDateTime::now_utc())
+    );
+    Ok(())
+}
+
+/// An active timer as remembered by `break diff`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SnapshotEntry {
+    uuid: uuid::Uuid,
+    id: u32,
+    message: String,
+    /// Lets a recurring timer that fired (and is still active) show up as fired
+    fire_count: u32,
+}
+
+/// Changes between a snapshot and the current database.
+#[derive(Debug, Default, PartialEq)]
+struct TimerDiff {
+    /// Timers that fired: one-shot timers now in history, or recurring timers
+    /// whose fire count went up
+    fired: Vec<SnapshotEntry>,
+    /// Active timers that weren't in the snapshot
+    added: Vec<SnapshotEntry>,
+    /// Timers that left without firing (removed or cleared)
+    removed: Vec<SnapshotEntry>,
+}
+
+impl TimerDiff {
+    fn is_empty(&self) -> bool {
+        self.fired.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Records the active timers of `db` for the next `break diff`.
+fn snapshot_timers(db: &Database) -> Vec<SnapshotEntry> {
+    db.timers
+        .iter()
+        .map(|t| SnapshotEntry {
+            uuid: t.uuid,
+            id: t.id,
+            message: t.message.clone(),
+            fire_count: t.fire_count,
+        })
+        .collect()
+}
+
+/// Compares a snapshot of active timers with the current database.
+///
+/// # Arguments
+///
+/// * `snapshot` - The active timers when `diff` last ran
+/// * `db` - The current database
+fn diff_timers(snapshot: &[SnapshotEntry], db: &Database) -> TimerDiff {
+    let mut diff = TimerDiff::default();
+    let current = snapshot_timers(db);
+
+    for entry in snapshot {
+        match current.iter().find(|t| t.uuid == entry.uuid) {
+            Some(now) if now.fire_count > entry.fire_count => diff.fired.push(now.clone()),
+            Some(_) => {}
+            None if db.history.iter().any(|t| t.uuid == entry.uuid) => {
+                diff.fired.push(entry.clone())
+            }
+            None => diff.removed.push(entry.clone()),
+        }
+    }
+    diff.added = current
+        .into_iter()
+        .filter(|t| !snapshot.iter().any(|entry| entry.uuid == t.uuid))
+        .collect();
+
+    diff
+}
+
+/// Returns the path of the snapshot kept by `break diff`, next to the database.
+fn snapshot_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(Database::db_path()?.with_file_name("diff-snapshot.json"))
+}
+
+/// Shows which timers fired, were added, or were removed since `break diff` last
+/// ran, then remembers the current timers for next time.
+///
+/// Without a previous snapshot every active timer counts as added.
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the database can't be loaded or
+/// the snapshot can't be read or written.
+fn show_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let path = snapshot_path()?;
+    let snapshot: Vec<SnapshotEntry> = match std::fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e).into()),
+    };
+
+    let db = Database::load()?;
+    let diff = diff_timers(&snapshot, &db);
+
+    if diff.is_empty() {
+        println!("No changes since the last diff");
+    }
+    for (label, entries) in [
+        ("Fired", &diff.fired),
+        ("Added", &diff.added),
+        ("Removed", &diff.removed),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        println!("{}:", label);
+        for entry in entries {
+            println!("  #{}: \"{}\"", entry.id, entry.message);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string(&snapshot_timers(&db))?)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(())
+}
+
+/// Shows the event journal: every timer that fired, completed, or was removed,
+/// oldest first, in local time.
+///
+/// # Arguments
+///
+/// * `since` - Only show events at or after this time (a clock time like "9:00",
+///   or a duration like "2h" meaning that long ago)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if `since` is invalid or the journal
+/// can't be read.
+fn show_journal(since: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let now = local_now();
+    let since = since
+        .map(|value| parser::parse_past_time(value, now))
+        .transpose()
+        .map_err(|e| e.context("Invalid --since"))?;
+
+    let path = journal::journal_path()?;
+    let events = journal::read(&path, since)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    if events.is_empty() {
+        println!("No events in the journal");
+        return Ok(());
+    }
+
+    let format = time::macros::format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+    for event in events {
+        println!(
+            "{}  {:<9}  #{}: \"{}\"",
+            event.at.to_offset(now.offset()).format(&format)?,
+            event.event,
+            event.id,
+            event.message
+        );
+    }
+
+    Ok(())
+}
+
+/// Widest bar in the `status --by-hour` histogram, in characters.
+const STATS_BAR_WIDTH: u32 = 40;
+
+/// Counts completed timers per hour of the day.
+///
+/// Completions come from the history and from `completed` journal events, which
+/// reach further back; a completion found in both is counted once.
+///
+/// # Arguments
+///
+/// * `history` - Completed timers (entries without `completed_at` are skipped)
+/// * `events` - Journal events (only `completed` ones are counted)
+/// * `offset` - The offset whose clock the hours are read on
+///
+/// # Returns
+///
+/// Returns the counts for hours 0 to 23.
+fn completions_by_hour(
+    history: &[database::Timer],
+    events: &[journal::JournalEvent],
+    offset: time::UtcOffset,
+) -> [u32; 24] {
+    let completions: BTreeSet<(uuid::Uuid, i64)> = events
+        .iter()
+        .filter(|event| event.event == journal::EventKind::Completed)
+        .map(|event| (event.uuid, event.at.unix_timestamp()))
+        .chain(history.iter().filter_map(|timer| {
+            timer
+                .completed_at
+                .map(|at| (timer.uuid, at.unix_timestamp()))
+        }))
+        .collect();
+
+    let mut counts = [0; 24];
+    for (_, at) in completions {
+        if let Ok(at) = time::OffsetDateTime::from_unix_timestamp(at) {
+            counts[usize::from(at.to_offset(offset).hour())] += 1;
+        }
+    }
+    counts
+}
+
+/// Renders per-hour counts as a text histogram, one line per hour.
+fn render_hour_histogram(counts: &[u32; 24]) -> String {
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    counts
+        .iter()
+        .enumerate()
+        .map(|(hour, &count)| {
+            // Any completion gets at least one mark
+            let width = (count * STATS_BAR_WIDTH).div_ceil(max) as usize;
+            format!(
+                "{:02}  {:<width$}  {}\n",
+                hour,
+                "#".repeat(width),
+                count,
+                width = STATS_BAR_WIDTH as usize
+            )
+        })
+        .collect()
+}
+
+/// Shows when timers get completed, as counts per local hour of the day.
+///
+/// # Arguments
+///
+/// * `json` - Print a JSON array of the 24 counts instead, pretty-printed unless
+///   `Some(true)` (`--json-compact`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the database or journal can't be
+/// read.
+fn show_stats_by_hour(json: Option<bool>) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::load()?;
+    let path = journal::journal_path()?;
+    let events = journal::read(&path, None)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let counts = completions_by_hour(&db.history, &events, local_now().offset());
+
+    if let Some(compact) = json {
+        println!("{}", to_json(&counts, compact)?);
+        return Ok(());
+    }
+    if counts.iter().all(|count| *count == 0) {
+        println!("No completed timers yet");
+        return Ok(());
+    }
+
+    println!("Completed timers by hour:");
+    print!("{}", render_hour_histogram(&counts));
+    Ok(())
+}
+
+/// Manually starts the daemon process.
+///
+/// Spawns a new daemon process to monitor timers. This is typically called
+/// automatically when timers are created, but can be manually invoked if needed.
+/// If the daemon is already running, this has no effect.
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the daemon process cannot be spawned.
+fn start_daemon() -> Result<(), Box<dyn std::error::Error>> {
+    if daemon::spawning_disabled() {
+        println!("Daemon not started: spawning is disabled (BREAK_NO_DAEMON or --no-daemon-spawn)");
+        return Ok(());
+    }
+    daemon::start_daemon_process()?;
+    println!("Daemon started");
+    Ok(())
+}
+
+/// Diagnostics gathered by `break info`.
+#[derive(Debug, Serialize)]
+struct Info {
+    version: &'static str,
+    database: String,
+    pid_file: String,
+    /// breakrs has no config file; settings come from the environment
+    config_file: Option<String>,
+    environment: BTreeMap<String, String>,
+    daemon_running: bool,
+    daemon_pid: Option<u32>,
+    notification_backend: Option<String>,
+    active_timers: usize,
+    history_entries: usize,
+}
+
+/// Gathers paths, settings, daemon state, and timer counts for `break info`.
+///
+/// Unlike `status`, this never starts the daemon.
+fn collect_info() -> Result<Info, Box<dyn std::error::Error>> {
+    let db = Database::load()?;
+    let daemon_pid = daemon::running_daemon_pid()?;
+    let notification_backend = match daemon_pid {
+        Some(_) => daemon::running_backend()?,
+        None => None,
+    };
+
+    Ok(Info {
+        version: env!("CARGO_PKG_VERSION"),
+        database: Database::db_path()?.display().to_string(),
+        pid_file: daemon::pid_file_path()?.display().to_string(),
+        config_file: None,
+        environment: config::environment_settings(),
+        daemon_running: daemon_pid.is_some(),
+        daemon_pid,
+        notification_backend,
+        active_timers: db.timers.len(),
+        history_entries: db.history.len(),
+    })
+}
+
+/// Renders `break info` as aligned `key: value` lines.
+fn render_info(info: &Info) -> String {
+    let environment = if info.environment.is_empty() {
+        "(no BREAK_* variables set)".to_string()
+    } else {
+        info.environment
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let daemon = match info.daemon_pid {
+        Some(pid) => format!("running (PID {})", pid),
+        None => "not running".to_string(),
+    };
+
+    let lines = [
+        ("Version", info.version.to_string()),
+        ("Database", info.database.clone()),
+        ("PID file", info.pid_file.clone()),
+        (
+            "Config file",
+            info.config_file
+                .clone()
+                .unwrap_or_else(|| "none (settings come from the environment)".to_string()),
+        ),
+        ("Environment", environment),
+        ("Daemon", daemon),
+        (
+            "Notifications",
+            info.notification_backend
+                .clone()
+                .unwrap_or_else(|| "unknown (daemon not running)".to_string()),
+        ),
+        ("Active timers", info.active_timers.to_string()),
+        ("History entries", info.history_entries.to_string()),
+    ];
+
+    lines
+        .iter()
+        .map(|(key, value)| format!("{:<16} {}", format!("{}:", key), value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prints diagnostics for bug reports: version, paths, settings, and daemon state.
+///
+/// # Arguments
+///
+/// * `json` - Print the information as a JSON object instead of text;
+///   `Some(true)` prints it on a single line
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the database or daemon state
+/// cannot be read.
+fn show_info(json: Option<bool>) -> Result<(), Box<dyn std::error::Error>> {
+    let info = collect_info()?;
+
+    match json {
+        Some(compact) => println!("{}", to_json(&info, compact)?),
+        None => println!("{}", render_info(&info)),
+    }
+
+    Ok(())
+}
+
+/// Formats a time as an iCalendar UTC date-time (`20250124T083000Z`).
+fn ics_date_time(time: time::OffsetDateTime) -> String {
+    let utc = time.to_offset(time::UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        utc.year(),
+        u8::from(utc.month()),
+        utc.day(),
+        utc.hour(),
+        utc.minute(),
+        utc.second()
+    )
+}
+
+/// Escapes a value for an iCalendar TEXT property.
+fn ics_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line, folding it at 75 bytes as iCalendar requires.
+fn push_ics_line(ics: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            width = 1;
+        }
+        ics.push(c);
+        width += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// Renders timers as an iCalendar document.
+///
+/// Each timer becomes a VEVENT running from its creation to its due time, with a
+/// VALARM that goes off when it's due. The message is the SUMMARY and the tags are
+/// the CATEGORIES.
+///
+/// # Arguments
+///
+/// * `timers` - The timers to export
+/// * `now` - The export time, used for each event's DTSTAMP
+///
+/// # Returns
+///
+/// Returns the calendar with CRLF line endings.
+fn timers_to_ics(timers: &[database::Timer], now: time::OffsetDateTime) -> String {
+    let mut ics = String::new();
+    let mut line = |line: &str| push_ics_line(&mut ics, line);
+
+    line("BEGIN:VCALENDAR");
+    line("VERSION:2.0");
+    line(concat!(
+        "PRODID:-//breakrs//breakrs ",
+        env!("CARGO_PKG_VERSION"),
+        "//EN"
+    ));
+    line("CALSCALE:GREGORIAN");
+
+    for timer in timers {
+        let summary = ics_escape(&timer.message);
+        line("BEGIN:VEVENT");
+        line(&format!("UID:{}@breakrs", timer.uuid));
+        line(&format!("DTSTAMP:{}", ics_date_time(now)));
+        line(&format!("DTSTART:{}", ics_date_time(timer.created_at)));
+        line(&format!("DTEND:{}", ics_date_time(timer.due_at)));
+        line(&format!("SUMMARY:{}", summary));
+        if !timer.tags.is_empty() {
+            let categories: Vec<String> = timer.tags.iter().map(|tag| ics_escape(tag)).collect();
+            line(&format!("CATEGORIES:{}", categories.join(",")));
+        }
+        if timer.urgent {
+            line("PRIORITY:1");
+        }
+        line("BEGIN:VALARM");
+        line("ACTION:DISPLAY");
+        line(&format!("DESCRIPTION:{}", summary));
+        line(&format!(
+            "TRIGGER;VALUE=DATE-TIME:{}",
+            ics_date_time(timer.due_at)
+        ));
+        line("END:VALARM");
+        line("END:VEVENT");
+    }
+
+    line("END:VCALENDAR");
+    ics
+}
+
+/// Exports the active timers to stdout or a file.
+///
+/// # Arguments
+///
+/// * `format` - The export format name given with `--format` (defaults to `ics`)
+/// * `output` - Write the export to this file instead of stdout
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an error if the format is unknown, the database
+/// cannot be loaded, or the output file cannot be written.
+fn export_timers(
+    format: Option<&str>,
+    output: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format = match format {
+        Some(name) => ExportFormat::from_str(name, true).map_err(|_| {
+            let supported: Vec<String> = ExportFormat::value_variants()
+                .iter()
+                .filter_map(|f| f.to_possible_value().map(|v| v.get_name().to_string()))
+                .collect();
+            format!(
+                "Unknown export format '{}' (supported: {})",
+                name,
+                supported.join(", ")
+            )
+        })?,
+        None => ExportFormat::default(),
+    };
+
+    let db = Database::load()?;
+    let contents = match format {
+        ExportFormat::Ics => timers_to_ics(&db.timers, time::OffsetDateTime::now_utc()),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, contents)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            println!(
+                "Exported {} timer{} to {}",
+                db.timers.len(),
+                if db.timers.len() == 1 { "" } else { "s" },
+                path.display()
+            );
+        }
+        None => print!("{}", contents),
+    }
+
+    Ok(())
+}
+
+/// Adds a recurring timer for every simple schedule in a crontab.
+///
+/// Each timer first fires at the schedule's next run on the local clock and then
+/// repeats at its interval, staying on the clock like `every day at`. Schedules
+/// that can't be a recurring timer are listed on stderr and skipped; all timers
+/// are added in one transaction, so a failed import adds none.
+///
+/// # Arguments
+///
+/// * `path` - The crontab, or `-` to read it from stdin
+/// * `raw` - Show intervals in minutes without rolling up into hours (`--raw`)
+///
+/// # Errors
+///
+/// Returns an error if the crontab can't be read or the timers can't be added.
+fn import_cron(path: &Path, raw: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let crontab = if path == Path::new("-") {
+        io::read_to_string(io::stdin()).map_err(|e| format!("Failed to read stdin: {}", e))?
+    } else {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+    };
+
+    let (entries, skipped) = cron::parse_crontab(&crontab);
+    for (line, reason) in &skipped {
+        eprintln!("Skipped line {}: {}", line, reason);
+    }
+    if entries.is_empty() {
+        println!("No cron schedules to import");
+        return Ok(());
+    }
+
+    let now = local_now();
+    let timers = Database::with_transaction(|db| {
+        entries
+            .iter()
+            .map(|entry| {
+                let first_run = entry.schedule.next_after(now);
+                let timer = db.add_timer(
+                    entry.message.clone(),
+                    (first_run - now).whole_seconds().max(1) as u64,
+                    false,
+                    false,
+                    true,
+                )?;
+                Ok(db
+                    .update_timer(timer.id, |t| {
+                        t.due_at = first_run.to_offset(time::UtcOffset::UTC);
+                        t.duration_seconds = entry.schedule.period_secs();
+                        t.anchored = true;
+                    })
+                    .unwrap_or(timer))
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(|e| format!("Failed to import timers: {}", e).into())
+    })?;
+
+    for timer in &timers {
+        println!(
+            "Timer #{} set for \"{}\" (every {})",
+            timer.id,
+            timer.message,
+            format_duration(timer.duration_seconds as i64, 5, raw)
+        );
+    }
+    println!(
+        "Imported {} timer{}",
+        timers.len(),
+        if timers.len() == 1 { "" } else { "s" }
+    );
+
+    daemon::ensure_daemon_running()?;
+    Ok(())
+}
+
+/// Returns the names of the shells completions can be generated for.
+fn supported_shells() -> Vec<String> {
+    Shell::value_variants()
+        .iter()
+        .filter_map(|shell| shell.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+/// Parses a shell name, listing the supported shells if it is not one of them.
+fn parse_shell(name: &str) -> Result<Shell, String> {
+    Shell::from_str(name, true).map_err(|_| {
+        format!(
+            "Unsupported shell '{}'. Supported shells: {}",
+            name,
+            supported_shells().join(", ")
+        )
+    })
+}
+
+/// Generates shell completion scripts for the specified shell.
+///
+/// This function outputs the completion script to stdout (or to `output`), which
+/// can be saved or sourced directly. Supports bash, zsh, fish, PowerShell, and elvish.
+///
+/// # Arguments
+///
+/// * `shell` - The name of the shell to generate completions for
+/// * `output` - Optional file to write the completions to instead of stdout
+///
+/// # Errors
+///
+/// Returns an error if the shell is not supported or the output file cannot be written.
+///
+/// # Examples
+///
+/// ```bash
+/// # Generate and install bash completions
+/// breakrs completions bash > ~/.local/share/bash-completion/completions/breakrs
+///
+/// # Generate and install zsh completions
+/// breakrs completions zsh > ~/.zsh/completion/_breakrs
+///
+/// # Generate and install fish completions
+/// breakrs completions fish > ~/.config/fish/completions/breakrs.fish
+/// ```
+fn generate_completions(
+    shell: &str,
+    output: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let shell = parse_shell(shell)?;
+    let mut cmd = Cli::command();
+    let bin_name = cmd.get_name().to_string();
+
+    match output {
+        Some(path) => {
+            let mut file = std::fs::File::create(path)
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+            generate(shell, &mut cmd, bin_name, &mut file);
+            println!("Wrote {} completions to {}", shell, path.display());
+        }
+        None => generate(shell, &mut cmd, bin_name, &mut io::stdout()),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(
+            parse_utc_offset("+0200"),
+            Some(time::UtcOffset::from_hms(2, 0, 0).unwrap())
+        );
+        assert_eq!(
+            parse_utc_offset("-0530"),
+            Some(time::UtcOffset::from_hms(-5, -30, 0).unwrap())
+        );
+        assert_eq!(parse_utc_offset("+0000"), Some(time::UtcOffset::UTC));
+        assert_eq!(parse_utc_offset("CEST"), None);
+        assert_eq!(parse_utc_offset(""), None);
+    }
+
+    fn test_timer(now: OffsetDateTime) -> database::Timer {
+        database::Timer {
+            uuid: uuid::Uuid::new_v4(),
+            id: 7,
+            message: "Tea".to_string(),
+            duration_seconds: 330,
+            created_at: now,
+            due_at: now + time::Duration::seconds(330),
+            urgent: true,
+            sound: false,
+            recurring: false,
+            completed_at: None,
+            tags: Vec::new(),
+            fired_at: None,
+            silent: false,
+            icon: None,
+            fire_count: 0,
+            anchored: false,
+            notified: false,
+            timeout_secs: None,
+            urgent_after_secs: None,
+            pinned: false,
+            messages: Vec::new(),
+            nag_interval_secs: None,
+            paused_at: None,
+            resume_at: None,
+            transient: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_expand_creation_placeholders() {
+        let now = time::macros::datetime!(2025-01-24 14:30:00 +1);
+
+        assert_eq!(
+            expand_creation_placeholders("standup notes for {date} ({time}, #{id})", 7, now),
+            "standup notes for 2025-01-24 (14:30, #7)"
+        );
+        // Fire-time placeholders wait for the daemon
+        assert_eq!(
+            expand_creation_placeholders("sent {fire_date} {fire_time}", 7, now),
+            "sent {fire_date} {fire_time}"
+        );
+        assert_eq!(expand_creation_placeholders("Tea", 7, now), "Tea");
+    }
+
+    #[test]
+    fn test_pinned_timers_listed_first() {
+        let now = OffsetDateTime::now_utc();
+        let timers: Vec<database::Timer> = [(1, false), (2, true), (3, false), (4, true)]
+            .into_iter()
+            .map(|(id, pinned)| database::Timer {
+                id,
+                pinned,
+                ..test_timer(now)
+            })
+            .collect();
+
+        let mut listed: Vec<&database::Timer> = timers.iter().collect();
+        pinned_first(&mut listed);
+        let ids: Vec<u32> = listed.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![2, 4, 1, 3]);
+
+        assert_eq!(format_flags(&timers[1]), " [urgent, pinned]");
+    }
+
+    #[test]
+    fn test_resolve_seconds() {
+        assert_eq!(resolve_seconds("300", io::empty()), Ok(300));
+        assert_eq!(resolve_seconds("-", io::Cursor::new("300\n")), Ok(300));
+        assert!(resolve_seconds("5m", io::empty()).is_err());
+        assert!(resolve_seconds("-", io::Cursor::new("")).is_err());
+        assert!(resolve_seconds("-1", io::empty()).is_err());
+
+        // `--seconds 300 coffee`: the input is only the message
+        let cli = Cli::try_parse_from(["breakrs", "--seconds", "300", "coffee"]).unwrap();
+        assert_eq!(cli.seconds.as_deref(), Some("300"));
+        let (input, _, _, _) = resolve_timer_input(&cli);
+        let options = parser::ParseOptions {
+            seconds: Some(resolve_seconds(cli.seconds.as_deref().unwrap(), io::empty()).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            parser::parse_input_with(&input, &options).unwrap(),
+            (300, "coffee".to_string())
+        );
+    }
+
+    #[test]
+    fn test_short_timer_action() {
+        use config::ShortTimerPolicy::{Bump, Inline};
+
+        assert_eq!(
+            short_timer_action(3, 3, Bump, false),
+            ShortTimerAction::Keep
+        );
+        assert_eq!(
+            short_timer_action(300, 3, Inline, false),
+            ShortTimerAction::Keep
+        );
+        assert_eq!(
+            short_timer_action(1, 3, Bump, false),
+            ShortTimerAction::Bump(3)
+        );
+        assert_eq!(
+            short_timer_action(1, 3, Inline, false),
+            ShortTimerAction::Inline
+        );
+        // Recurring and escalating timers can't run in the foreground
+        assert_eq!(
+            short_timer_action(1, 3, Inline, true),
+            ShortTimerAction::Bump(3)
+        );
+        // A minimum of 0 disables the check
+        assert_eq!(
+            short_timer_action(1, 0, Bump, false),
+            ShortTimerAction::Keep
+        );
+    }
+
+    #[test]
+    fn test_timers_to_ics() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 +2);
+        let mut timer = test_timer(now);
+        timer.message = "Tea, then biscuits".to_string();
+        timer.tags = vec!["home".to_string(), "break".to_string()];
+
+        let ics = timers_to_ics(std::slice::from_ref(&timer), now);
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert_eq!(lines[lines.len() - 2], "END:VCALENDAR");
+        for expected in [
+            "BEGIN:VEVENT",
+            &format!("UID:{}@breakrs", timer.uuid),
+            "DTSTART:20250124T080000Z",
+            "DTEND:20250124T080530Z",
+            "SUMMARY:Tea\\, then biscuits",
+            "CATEGORIES:home,break",
+            "PRIORITY:1",
+            "BEGIN:VALARM",
+            "ACTION:DISPLAY",
+            "TRIGGER;VALUE=DATE-TIME:20250124T080530Z",
+            "END:VALARM",
+            "END:VEVENT",
+        ] {
+            assert!(
+                lines.contains(&expected),
+                "missing {:?} in {}",
+                expected,
+                ics
+            );
+        }
+
+        // Long lines are folded at 75 bytes
+        timer.message = "x".repeat(100);
+        let ics = timers_to_ics(&[timer], now);
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert!(ics.contains(&format!(
+            "SUMMARY:{}\r\n {}",
+            "x".repeat(67),
+            "x".repeat(33)
+        )));
+    }
+
+    #[test]
+    fn test_render_template_custom() {
+        let now = OffsetDateTime::now_utc();
+        let timer = test_timer(now);
+
+        let line = render_template(
+            "{id}|{message}|{remaining}|{duration}|{flags}",
+            &timer,
+            now,
+            i64::MAX,
+            false,
+        )
+        .unwrap();
+        assert_eq!(line, "7|Tea|5m 30s|330| [urgent]");
+    }
+
+    #[test]
+    fn test_render_template_due_and_expired() {
+        let now = OffsetDateTime::now_utc();
+        let mut timer = test_timer(now);
+        timer.due_at = time::macros::datetime!(2025-01-24 12:30:00 UTC);
+
+        let line = render_template("{due} {remaining}", &timer, now, i64::MAX, false).unwrap();
+        assert_eq!(line, "2025-01-24T12:30:00Z EXPIRED");
+    }
+
+    #[test]
+    fn test_render_template_defaults_match_builtin_output() {
+        let now = OffsetDateTime::now_utc();
+        let timer = test_timer(now);
+
+        assert_eq!(
+            render_template(DEFAULT_ADD_FORMAT, &timer, now, i64::MAX, false).unwrap(),
+            "Timer #7 set for \"Tea\" (330 seconds) [urgent]"
+        );
+        assert_eq!(
+            render_template(DEFAULT_LIST_FORMAT, &timer, now, i64::MAX, false).unwrap(),
+            "  #7: \"Tea\" - 5m 30s remaining [urgent]"
+        );
+    }
+
+    #[test]
+    fn test_render_template_seconds_threshold() {
+        let now = OffsetDateTime::now_utc();
+        let mut long = test_timer(now);
+        long.due_at = now + time::Duration::seconds(30 * 60 + 15);
+        let short = test_timer(now);
+
+        // Default: seconds are always shown
+        assert_eq!(
+            render_template("{remaining}", &long, now, i64::MAX, false).unwrap(),
+            "30m 15s"
+        );
+
+        // With a 10 minute threshold, only the short timer keeps its seconds
+        assert_eq!(
+            render_template("{remaining}", &long, now, 10, false).unwrap(),
+            "30m"
+        );
+        assert_eq!(
+            render_template("{remaining}", &short, now, 10, false).unwrap(),
+            "5m 30s"
+        );
+    }
+
+    #[test]
+    fn test_raw_durations() {
+        // A 90-minute timer, as set with `break 90m task`
+        assert_eq!(format_duration(90 * 60, 5, false), "1h 30m");
+        assert_eq!(format_duration(90 * 60, 5, true), "90m");
+
+        // Normalized output drops seconds once hours show; raw only goes by the threshold
+        assert_eq!(format_duration(90 * 60 + 5, i64::MAX, false), "1h 30m");
+        assert_eq!(format_duration(90 * 60 + 5, i64::MAX, true), "90m 5s");
+        assert_eq!(format_duration(90 * 60 + 5, 10, true), "90m");
+        assert_eq!(format_duration(45, 5, true), "45s");
+
+        let now = OffsetDateTime::now_utc();
+        let mut timer = test_timer(now);
+        timer.due_at = now + time::Duration::minutes(90);
+        assert_eq!(
+            render_template("{remaining}", &timer, now, 10, false).unwrap(),
+            "1h 30m"
+        );
+        assert_eq!(
+            render_template("{remaining}", &timer, now, 10, true).unwrap(),
+            "90m"
+        );
+    }
+
+    #[test]
+    fn test_list_shows_label() {
+        let now = OffsetDateTime::now_utc();
+        let mut timer = test_timer(now);
+        timer.message = "Daily standup: blockers, then the release checklist".to_string();
+        timer.label = Some("standup".to_string());
+
+        let line = render_template(DEFAULT_LIST_FORMAT, &timer, now, i64::MAX, false).unwrap();
+        assert!(line.starts_with("  #7: \"standup\" - "), "{}", line);
+        let line = render_template("{message}", &timer, now, i64::MAX, false).unwrap();
+        assert_eq!(line, timer.message);
+
+        // Without a label, long messages are shortened in the list
+        timer.label = None;
+        let line = render_template("{label}", &timer, now, i64::MAX, false).unwrap();
+        assert_eq!(line, "Daily standup: blockers, then the relea…");
+
+        let cli = Cli::try_parse_from(["breakrs", "10m", "standup", "--label", "su"]).unwrap();
+        let (input, _, _, notification) = resolve_timer_input(&cli);
+        assert_eq!(input, "10m standup");
+        assert_eq!(notification.label(), Ok(Some("su".to_string())));
+        let blank = NotificationOptions {
+            label: Some("  ".to_string()),
+            ..Default::default()
+        };
+        assert!(blank.label().is_err());
+    }
+
+    #[test]
+    fn test_render_template_leaves_message_braces_alone() {
+        let now = OffsetDateTime::now_utc();
+        let mut timer = test_timer(now);
+        timer.message = "review {id}".to_string();
+
+        let line = render_template("{id}: {message}", &timer, now, i64::MAX, false).unwrap();
+        assert_eq!(line, "7: review {id}");
+    }
+
+    #[test]
+    fn test_render_status_frame() {
+        let now = OffsetDateTime::now_utc().replace_time(time::macros::time!(14:05:09));
+        let running = StatusSnapshot {
+            daemon_up: true,
+            backend: Some("dbus".to_string()),
+            timer_count: 3,
+        };
+        assert_eq!(
+            render_status_frame(&running, None, now).unwrap(),
+            concat!(
+                "Daemon is running\n",
+                "Notifications: dbus\n",
+                "Active timers: 3\n",
+                "\nUpdated 14:05:09, every 2s (Ctrl-C to stop)\n",
+            )
+        );
+
+        // A daemon that died while watching is shown as stopped, with when
+        let stopped = StatusSnapshot {
+            daemon_up: false,
+            backend: None,
+            timer_count: 1,
+        };
+        let died = now - time::Duration::seconds(4);
+        assert_eq!(
+            render_status_frame(&stopped, Some((false, died)), now).unwrap(),
+            concat!(
+                "Daemon is not running\n",
+                "Active timers: 1\n",
+                "Daemon stopped at 14:05:05\n",
+                "\nUpdated 14:05:09, every 2s (Ctrl-C to stop)\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_top_frame() {
+        let now = OffsetDateTime::now_utc();
+        let mut later = test_timer(now);
+        later.id = 1;
+        later.message = "Later".to_string();
+        later.urgent = false;
+        later.due_at = now + time::Duration::seconds(600);
+        let soon = test_timer(now);
+        let mut expired = test_timer(now);
+        expired.id = 3;
+        expired.message = "Done".to_string();
+        expired.urgent = false;
+        expired.due_at = now - time::Duration::seconds(5);
+
+        let frame =
+            render_top_frame(&[later, soon, expired], now, 2, None, i64::MAX, false).unwrap();
+        assert_eq!(
+            frame,
+            concat!(
+                "Active timers: 3 (soonest first)\n",
+                "  #3: \"Done\" - EXPIRED\n",
+                "  #7: \"Tea\" - 5m 30s remaining [urgent]\n",
+                "  ... and 1 more\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_history_entries_limit() {
+        let now = OffsetDateTime::now_utc();
+        let mut db = Database::new();
+        for _ in 0..3 {
+            db.add_to_history(test_timer(now));
+        }
+
+        let (recent, all) = (HistorySort::Recent, CreatedRange::default());
+        assert_eq!(history_entries(&db, None, recent, false, all).len(), 3);
+        assert_eq!(history_entries(&db, Some(2), recent, false, all).len(), 2);
+        assert_eq!(history_entries(&db, Some(0), recent, false, all).len(), 0);
+        // Larger limits are clamped to what is stored
+        assert_eq!(history_entries(&db, Some(50), recent, false, all).len(), 3);
+    }
+
+    #[test]
+    fn test_created_range() {
+        let now = OffsetDateTime::now_utc();
+        // Created 3 hours, 90 minutes, and 10 minutes ago
+        let timers: Vec<database::Timer> = [("Early", 180), ("Mid", 90), ("Late", 10)]
+            .into_iter()
+            .map(|(message, minutes_ago)| {
+                let mut timer = test_timer(now);
+                timer.message = message.to_string();
+                timer.created_at = now - time::Duration::minutes(minutes_ago);
+                timer
+            })
+            .collect();
+        let matching = |range: CreatedRange| -> Vec<&str> {
+            timers
+                .iter()
+                .filter(|t| range.contains(t))
+                .map(|t| t.message.as_str())
+                .collect()
+        };
+        let ago = |hours| Some(now - time::Duration::hours(hours));
+
+        assert_eq!(matching(CreatedRange::default()), ["Early", "Mid", "Late"]);
+        // --created-after 2h: the last two hours
+        let after = CreatedRange {
+            after: ago(2),
+            before: None,
+        };
+        assert_eq!(matching(after), ["Mid", "Late"]);
+        // --created-before 1h
+        let before = CreatedRange {
+            after: None,
+            before: ago(1),
+        };
+        assert_eq!(matching(before), ["Early", "Mid"]);
+        // Both bounds
+        let between = CreatedRange {
+            after: ago(2),
+            before: ago(1),
+        };
+        assert_eq!(matching(between), ["Mid"]);
+
+        // History filters before sorting and limiting
+        let mut db = Database::new();
+        db.history = timers.clone();
+        let entries = history_entries(&db, Some(1), HistorySort::Oldest, false, after);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message, "Mid");
+    }
+
+    #[test]
+    fn test_history_entries_sort() {
+        let now = OffsetDateTime::now_utc();
+        let mut db = Database::new();
+        // Stored newest-first: (message, minutes ago, duration)
+        for (message, minutes_ago, duration) in [("B", 1, 60), ("C", 5, 900), ("A", 10, 300)] {
+            let mut timer = test_timer(now);
+            timer.message = message.to_string();
+            timer.duration_seconds = duration;
+            timer.completed_at = Some(now - time::Duration::minutes(minutes_ago));
+            db.history.push(timer);
+        }
+
+        let messages = |limit, sort, reverse| -> Vec<String> {
+            history_entries(&db, limit, sort, reverse, CreatedRange::default())
+                .iter()
+                .map(|t| t.message.clone())
+                .collect()
+        };
+
+        assert_eq!(messages(None, HistorySort::Recent, false), ["B", "C", "A"]);
+        assert_eq!(messages(None, HistorySort::Oldest, false), ["A", "C", "B"]);
+        assert_eq!(
+            messages(None, HistorySort::Duration, false),
+            ["C", "A", "B"]
+        );
+
+        assert_eq!(messages(None, HistorySort::Recent, true), ["A", "C", "B"]);
+        assert_eq!(messages(None, HistorySort::Duration, true), ["B", "A", "C"]);
+
+        // The limit applies after sorting
+        assert_eq!(messages(Some(2), HistorySort::Oldest, false), ["A", "C"]);
+        assert_eq!(messages(Some(1), HistorySort::Duration, true), ["B"]);
+
+        // The stored order is untouched
+        assert_eq!(db.history[0].message, "B");
+    }
+
+    #[test]
+    fn test_to_json_pretty_and_compact() {
+        let now = OffsetDateTime::now_utc();
+        let mut db = Database::new();
+        db.add_to_history(test_timer(now));
+        let entries = history_entries(
+            &db,
+            None,
+            HistorySort::Recent,
+            false,
+            CreatedRange::default(),
+        );
+
+        let pretty = to_json(&entries, false).unwrap();
+        let compact = to_json(&entries, true).unwrap();
+        assert!(pretty.contains('\n'));
+        assert!(!compact.contains('\n'));
+
+        // Same data either way
+        let pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        let compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        assert_eq!(pretty, compact);
+    }
+
+    #[test]
+    fn test_history_json_shape() {
+        let now = OffsetDateTime::now_utc();
+        let mut db = Database::new();
+        db.add_to_history(test_timer(now));
+
+        let json = serde_json::to_string(&history_entries(
+            &db,
+            None,
+            HistorySort::Recent,
+            false,
+            CreatedRange::default(),
+        ))
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let entries = value.as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["id"], 7);
+        assert_eq!(entries[0]["message"], "Tea");
+        assert_eq!(entries[0]["urgent"], true);
+        assert!(entries[0]["completed_at"].is_i64());
+        assert!(entries[0]["due_at"].is_i64());
+    }
+
+    #[test]
+    fn test_supported_shells_list() {
+        let shells = supported_shells();
+        for shell in ["bash", "zsh", "fish", "powershell"] {
+            assert!(shells.iter().any(|s| s == shell), "missing {}", shell);
+        }
+    }
+
+    #[test]
+    fn test_parse_shell_error_lists_supported_shells() {
+        assert_eq!(parse_shell("zsh").unwrap(), Shell::Zsh);
+        assert_eq!(parse_shell("Bash").unwrap(), Shell::Bash);
+
+        let err = parse_shell("zsh-custom").unwrap_err();
+        assert!(err.contains("Unsupported shell 'zsh-custom'"));
+        assert!(err.contains("bash, elvish, fish, powershell, zsh"));
+    }
+
+    #[test]
+    fn test_write_jsonl_tick() {
+        let now = OffsetDateTime::now_utc();
+        let mut later = test_timer(now);
+        later.id = 1;
+        later.due_at = now + time::Duration::seconds(600);
+
+        let mut out = Vec::new();
+        write_jsonl_tick(&mut out, &[later, test_timer(now)], now).unwrap();
+        write_jsonl_tick(&mut out, &[], now).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+
+        let timers = lines[0]["timers"].as_array().unwrap();
+        assert_eq!(lines[0]["time"], now.unix_timestamp());
+        assert_eq!(timers.len(), 2);
+        assert_eq!(timers[0]["id"], 7);
+        assert_eq!(timers[0]["remaining_seconds"], 330);
+        assert_eq!(timers[1]["id"], 1);
+        assert_eq!(timers[1]["remaining_seconds"], 600);
+
+        assert!(lines[1]["timers"].as_array().unwrap().is_empty());
+    }
+
+    fn test_info() -> Info {
+        Info {
+            version: "0.2.1",
+            database: "/data/break/timers.json".to_string(),
+            pid_file: "/state/break/daemon.pid".to_string(),
+            config_file: None,
+            environment: BTreeMap::from([(
+                "BREAK_NOTIFICATION_RETRIES".to_string(),
+                "3".to_string(),
+            )]),
+            daemon_running: true,
+            daemon_pid: Some(4242),
+            notification_backend: Some("desktop".to_string()),
+            active_timers: 2,
+            history_entries: 5,
+        }
+    }
+
+    #[test]
+    fn test_render_info_keys() {
+        let text = render_info(&test_info());
+        for key in [
+            "Version:",
+            "Database:",
+            "PID file:",
+            "Config file:",
+            "Environment:",
+            "Daemon:",
+            "Notifications:",
+            "Active timers:",
+            "History entries:",
+        ] {
+            assert!(text.contains(key), "missing {}", key);
+        }
+        assert!(text.contains("running (PID 4242)"));
+        assert!(text.contains("BREAK_NOTIFICATION_RETRIES=3"));
+    }
+
+    #[test]
+    fn test_info_json_keys() {
+        let value = serde_json::to_value(test_info()).unwrap();
+        for key in [
+            "version",
+            "database",
+            "pid_file",
+            "config_file",
+            "environment",
+            "daemon_running",
+            "daemon_pid",
+            "notification_backend",
+            "active_timers",
+            "history_entries",
+        ] {
+            assert!(value.get(key).is_some(), "missing {}", key);
+        }
+        assert_eq!(value["daemon_pid"], 4242);
+        assert_eq!(value["environment"]["BREAK_NOTIFICATION_RETRIES"], "3");
+    }
+
+    #[test]
+    fn test_format_count() {
+        let mut db = Database::new();
+        assert_eq!(format_count(&db), "0");
+
+        db.add_timer("One".to_string(), 300, false, false, false)
+            .unwrap();
+        db.add_timer("Two".to_string(), 600, false, false, false)
+            .unwrap();
+        assert_eq!(format_count(&db), "2");
+    }
+
+    #[test]
+    fn test_check_exit_code() {
+        let mut db = Database::new();
+        assert_eq!(check_exit_code(&db), 1);
+
+        db.add_timer("One".to_string(), 300, false, false, false)
+            .unwrap();
+        assert_eq!(check_exit_code(&db), 0);
+
+        db.clear_all();
+        assert_eq!(check_exit_code(&db), 1);
+    }
+
+    #[test]
+    fn test_diff_timers() {
+        let mut db = Database::new();
+        let tea = db
+            .add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        let call = db
+            .add_timer("Call".to_string(), 300, false, false, false)
+            .unwrap();
+        let stretch = db
+            .add_timer("Stretch".to_string(), 60, false, false, true)
+            .unwrap();
+        let walk = db
+            .add_timer("Walk".to_string(), 600, false, false, false)
+            .unwrap();
+        let snapshot = snapshot_timers(&db);
+
+        assert!(diff_timers(&snapshot, &db).is_empty());
+
+        // Tea fires, Call is removed, Stretch recurs, Walk is untouched, Lunch is new
+        db.complete_timer(tea.id);
+        db.remove_timer(call.id);
+        db.reset_timer(stretch.id);
+        let lunch = db
+            .add_timer("Lunch".to_string(), 3600, false, false, false)
+            .unwrap();
+
+        let diff = diff_timers(&snapshot, &db);
+        let ids = |entries: &[SnapshotEntry]| entries.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.fired), vec![tea.id, stretch.id]);
+        assert_eq!(ids(&diff.added), vec![lunch.id]);
+        assert_eq!(ids(&diff.removed), vec![call.id]);
+        assert!(!ids(&diff.fired).contains(&walk.id));
+
+        // Once the snapshot is updated, nothing has changed
+        assert!(diff_timers(&snapshot_timers(&db), &db).is_empty());
+    }
+
+    #[test]
+    fn test_group_timers_by_tag() {
+        let mut db = Database::new();
+        for (message, seconds, tags) in [
+            ("Standup", 600, vec!["work"]),
+            ("Walk", 300, vec!["health", "outside"]),
+            ("Tea", 120, vec![]),
+            ("Review", 60, vec!["work", "health"]),
+            ("Water", 900, vec!["health"]),
+        ] {
+            let timer = db
+                .add_timer(message.to_string(), seconds, false, false, false)
+                .unwrap();
+            db.set_tags(timer.id, tags.into_iter().map(String::from).collect());
+        }
+        let timers: Vec<&database::Timer> = db.timers.iter().collect();
+
+        let messages = |group: &[&database::Timer]| -> Vec<String> {
+            group.iter().map(|t| t.message.clone()).collect()
+        };
+        let groups = group_timers_by_tag(&timers);
+        let tags: Vec<Option<&str>> = groups.iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(tags, vec![Some("health"), Some("work"), None]);
+        // Sorted by due time within a group; "Review" is only under its first tag
+        assert_eq!(messages(&groups[0].1), vec!["Walk", "Water"]);
+        assert_eq!(messages(&groups[1].1), vec!["Review", "Standup"]);
+        assert_eq!(messages(&groups[2].1), vec!["Tea"]);
+
+        // Keyed by tag for --json, with untagged timers under ""
+        let keyed = groups_by_key(&groups);
+        assert_eq!(
+            keyed.keys().copied().collect::<Vec<_>>(),
+            vec!["", "health", "work"]
+        );
+        assert_eq!(messages(keyed[""]), vec!["Tea"]);
+        let json = serde_json::to_value(&keyed).unwrap();
+        assert_eq!(json["work"][0]["message"], "Review");
+    }
+
+    #[test]
+    fn test_remaining_text() {
+        let now = OffsetDateTime::now_utc();
+        let mut db = Database::new();
+        let tea = db
+            .add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        let walk = db
+            .add_timer("Walk".to_string(), 60, false, false, false)
+            .unwrap();
+        db.update_timer(walk.id, |t| t.due_at = now - time::Duration::seconds(30));
+
+        let seconds: i64 = remaining_text(&db, tea.id, now, false, false)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((299..=300).contains(&seconds));
+        assert_eq!(
+            remaining_text(
+                &db,
+                tea.id,
+                tea.due_at - time::Duration::seconds(90),
+                true,
+                false
+            )
+            .unwrap(),
+            "1m 30s"
+        );
+
+        // Expired timers have nothing left
+        assert_eq!(
+            remaining_text(&db, walk.id, now, false, false).unwrap(),
+            "0"
+        );
+        assert_eq!(remaining_text(&db, 99, now, false, false), None);
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        let now = OffsetDateTime::now_utc();
+        let mut db = Database::new();
+
+        let metrics = render_metrics(&db, false, now);
+        assert!(metrics.contains("# TYPE break_active_timers gauge\n"));
+        assert!(metrics.contains("\nbreak_active_timers 0\n"));
+        assert!(metrics.contains("\nbreak_history_total 0\n"));
+        assert!(metrics.contains("\nbreak_daemon_up 0\n"));
+        assert!(!metrics.contains("break_next_due_seconds"));
+
+        db.add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        db.add_timer("Walk".to_string(), 600, false, false, false)
+            .unwrap();
+        let metrics = render_metrics(&db, true, now);
+
+        // Every sample line is a metric name and a parseable value
+        let samples: BTreeMap<&str, f64> = metrics
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| {
+                let (name, value) = line.split_once(' ').unwrap();
+                (name, value.parse().unwrap())
+            })
+            .collect();
+        assert_eq!(samples["break_active_timers"], 2.0);
+        assert_eq!(samples["break_daemon_up"], 1.0);
+        assert!((299.0..=300.0).contains(&samples["break_next_due_seconds"]));
+    }
+
+    #[test]
+    fn test_wait_until_empty() {
+        let poll = Duration::from_millis(10);
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Tea".to_string(), 1, false, false, false)
+            .unwrap();
+        db.update_timer(timer.id, |t| {
+            t.due_at = OffsetDateTime::now_utc() + time::Duration::milliseconds(150)
+        });
+
+        // Returns promptly once the short timer completes (as the daemon would)
+        let start = std::time::Instant::now();
+        let done = wait_until_empty(
+            || {
+                db.remove_expired_timers();
+                Ok(db.timers.len())
+            },
+            Some(Duration::from_secs(5)),
+            poll,
+        )
+        .unwrap();
+        assert!(done);
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // Gives up at the timeout while a timer is still active
+        let start = std::time::Instant::now();
+        let done = wait_until_empty(|| Ok(1), Some(Duration::from_millis(50)), poll).unwrap();
+        assert!(!done);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // Nothing active returns at once, even with a zero timeout
+        assert!(wait_until_empty(|| Ok(0), Some(Duration::ZERO), poll).unwrap());
+        assert!(wait_until_empty(|| Err("unreadable".into()), None, poll).is_err());
+
+        // Recurring timers never finish, so they aren't waited for
+        let mut db = Database::new();
+        db.add_timer("Water".to_string(), 1800, false, false, true)
+            .unwrap();
+        assert_eq!(one_off_count(&db), 0);
+        assert!(wait_until_empty(|| Ok(one_off_count(&db)), None, poll).unwrap());
+        db.add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        assert_eq!(one_off_count(&db), 1);
+    }
+
+    #[test]
+    fn test_no_autostart_never_spawns() {
+        let cli = Cli::try_parse_from(["breakrs", "list", "--no-autostart"]).unwrap();
+        let mut autostart = daemon::Autostart {
+            disabled: daemon::Autostart::configured(cli.no_autostart).disabled,
+            start: || -> Result<(), Box<dyn std::error::Error>> { panic!("spawned a daemon") },
+        };
+
+        // Read-only commands go through `autostart`, so none of them spawns
+        list_timers(
+            None,
+            true,
+            ListFilter::default(),
+            None,
+            None,
+            false,
+            &mut autostart,
+        )
+        .unwrap();
+        show_status(true, &mut autostart).unwrap();
+        let mut db = Database::new();
+        db.add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        autostart.for_timers(&db).unwrap();
+    }
+
+    #[test]
+    fn test_amend_form_parse_and_apply() {
+        let mut db = Database::new();
+        let timer = db
+            .add_timer("Tea".to_string(), 300, false, false, false)
+            .unwrap();
+        let now = OffsetDateTime::now_utc();
+        let original = AmendForm::from_timer(&timer, now);
+        let form = original.render(timer.id);
+        assert!(form.contains("message: Tea\n"));
+        assert!(form.contains("urgent: no\n"));
+
+        // Unchanged form: nothing to apply
+        assert_eq!(AmendForm::parse(&form, &original).unwrap(), original);
+
+        // Scripted edit: new message, longer time, urgent on; comments are ignored
+        let edited_text = form
+            .replace("message: Tea", "message: Green tea: steep")
+            .replace(
+                &format!("remaining: {}", original.remaining),
+                "remaining: 20m",
+            )
+            .replace("urgent: no", "Urgent: YES")
+            + "# a note\n";
+        let edited = AmendForm::parse(&edited_text, &original).unwrap();
+        assert_eq!(edited.message, "Green tea: steep");
+        assert!(edited.urgent && !edited.sound);
+
+        let amended = apply_amendment(&mut db, timer.id, &original, &edited)
+            .unwrap()
+            .unwrap();
+        assert_eq!(amended.message, "Green tea: steep");
+        assert_eq!(amended.duration_seconds, 1200);
+        assert!(amended.urgent);
+        assert!(!amended.recurring);
+
+        // Missing timers and invalid input
+        assert!(
+            apply_amendment(&mut db, 99, &original, &edited)
+                .unwrap()
+                .is_none()
+        );
+        let bad_time = AmendForm {
+            remaining: "soon".to_string(),
+            ..original.clone()
+        };
+        assert!(apply_amendment(&mut db, timer.id, &original, &bad_time).is_err());
+        assert!(AmendForm::parse("urgent: maybe", &original).is_err());
+        assert!(AmendForm::parse("colour: red", &original).is_err());
+        assert!(AmendForm::parse("message:", &original).is_err());
+    }
+
+    #[test]
+    fn test_rename_keeps_flag_like_words_in_message() {
+        let cli =
+            Cli::try_parse_from(["breakrs", "rename", "1", "--urgent", "-s", "call"]).unwrap();
+        let Some(Commands::Rename { id, message }) = cli.command else {
+            panic!("expected rename command");
+        };
+        assert_eq!(id, 1);
+        assert_eq!(message.join(" "), "--urgent -s call");
+
+        let mut db = Database::new();
+        db.add_timer("Old".to_string(), 300, false, true, false)
+            .unwrap();
+        db.edit_message(id, message.join(" "));
+
+        let timer = &db.timers[0];
+        assert_eq!(timer.message, "--urgent -s call");
+        assert!(!timer.urgent);
+        assert!(timer.sound);
+        assert!(!timer.recurring);
+
+        // `mv` would read as `move`, so it isn't a rename alias
+        let cli = Cli::try_parse_from(["breakrs", "mv", "5", "1"]).unwrap();
+        assert!(!matches!(cli.command, Some(Commands::Rename { .. })));
+    }
+
+    #[test]
+    fn test_wait_and_fire_notifies_once() {
+        let mut scratch = Database::new();
+        let timer = scratch
+            .add_timer("Done".to_string(), 1, false, false, false)
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        let mut fired = Vec::new();
+        assert!(wait_and_fire(&timer, |t| {
+            fired.push(t.message.clone());
+            true
+        }));
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert_eq!(fired, vec!["Done"]);
+
+        // Silent timers just wait
+        let mut silent = timer.clone();
+        silent.silent = true;
+        assert!(!wait_and_fire(&silent, |_| panic!(
+            "silent timers don't notify"
+        )));
+    }
+
+    #[test]
+    fn test_no_daemon_flag_in_any_position() {
+        for args in [
+            vec!["breakrs", "--no-daemon", "5m", "x"],
+            vec!["breakrs", "5m", "x", "--no-daemon"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (input, flags, _, _) = resolve_timer_input(&cli);
+            assert_eq!(input, "5m x");
+            assert!(flags.no_daemon);
+        }
+    }
+
+    #[test]
+    fn test_remove_takes_id_or_all_expired() {
+        let cli = Cli::try_parse_from(["breakrs", "rm", "--all-expired"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Remove {
+                id: None,
+                all_expired: true
+            })
+        ));
+
+        assert!(Cli::try_parse_from(["breakrs", "rm"]).is_err());
+        assert!(Cli::try_parse_from(["breakrs", "rm", "3", "--all-expired"]).is_err());
+    }
+
+    #[test]
+    fn test_combined_short_flags_in_any_position() {
+        let all = TimerFlags {
+            urgent: true,
+            sound: true,
+            recurring: true,
+            ..Default::default()
+        };
+
+        for args in [
+            vec!["breakrs", "-usr", "5m", "x"],
+            vec!["breakrs", "5m", "x", "-usr"],
+            vec!["breakrs", "-us", "5m", "x", "-r"],
+            vec!["breakrs", "-u", "5m", "-rs", "x"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (input, flags, _, _) = resolve_timer_input(&cli);
+            assert_eq!(input, "5m x", "{:?}", args);
+            assert_eq!(flags, all, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_confirm_ambiguous_durations() {
+        let cli = Cli::try_parse_from(["breakrs", "5m", "3h", "meeting", "--confirm"]).unwrap();
+        let (input, flags, _, _) = resolve_timer_input(&cli);
+        assert!(flags.confirm);
+        let options = parser::ParseOptions::default();
+
+        // Several separate durations need confirming; one run of units doesn't
+        let parsed = parser::parse_input_parts(&input, &options).unwrap();
+        assert!(parsed.parts.len() > 1);
+        assert_eq!(
+            parser::parse_input_parts("1h 30m meeting", &options)
+                .unwrap()
+                .parts
+                .len(),
+            1
+        );
+
+        let mut prompt = Vec::new();
+        assert!(confirm_durations(&parsed, false, "y\n".as_bytes(), &mut prompt).unwrap());
+        assert_eq!(
+            String::from_utf8(prompt).unwrap(),
+            "Found 2 separate durations: 5m + 3h 0m = 3h 5m for \"meeting\". Create this timer? [y/N] "
+        );
+        assert!(confirm_durations(&parsed, false, "YES\n".as_bytes(), io::sink()).unwrap());
+        assert!(!confirm_durations(&parsed, false, "n\n".as_bytes(), io::sink()).unwrap());
+        assert!(!confirm_durations(&parsed, false, "\n".as_bytes(), io::sink()).unwrap());
+        assert!(!confirm_durations(&parsed, false, "".as_bytes(), io::sink()).unwrap());
+    }
+
+    #[test]
+    fn test_with_defaults() {
+        let saved = defaults::Defaults {
+            urgent: true,
+            sound: true,
+            ..Default::default()
+        };
+        let cli = Cli::try_parse_from(["breakrs", "5m", "x", "-r"]).unwrap();
+        let (_, flags, _, notification) = resolve_timer_input(&cli);
+        let flags = with_defaults(flags, &notification, saved);
+        assert!(flags.urgent && flags.sound && flags.recurring && !flags.silent);
+
+        // The quiet preset wins over the defaults
+        let cli = Cli::try_parse_from(["breakrs", "5m", "x", "--background"]).unwrap();
+        let (_, flags, _, notification) = resolve_timer_input(&cli);
+        let flags = with_defaults(flags, &notification, saved);
+        assert!(!flags.urgent && !flags.sound);
+        assert!(
+            flags
+                .notification_timeout(&NotificationOptions::default())
+                .is_ok()
+        );
+
+        // A recurring default doesn't apply to timers that can't recur
+        let saved = defaults::Defaults {
+            recurring: true,
+            ..Default::default()
+        };
+        let cli = Cli::try_parse_from(["breakrs", "5m", "x"]).unwrap();
+        let (_, flags, _, notification) = resolve_timer_input(&cli);
+        assert!(with_defaults(flags, &notification, saved).recurring);
+        for args in [
+            vec!["breakrs", "5m", "tea", "--nag", "30s"],
+            vec!["breakrs", "5m", "tea", "--urgent-after", "2m"],
+            vec!["breakrs", "--no-daemon", "5s", "tea"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (_, flags, _, notification) = resolve_timer_input(&cli);
+            let flags = with_defaults(flags, &notification, saved);
+            assert!(!flags.recurring, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_background_preset() {
+        for args in [
+            vec!["breakrs", "--background", "5m", "x"],
+            vec!["breakrs", "5m", "x", "--background"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (input, flags, _, notification) = resolve_timer_input(&cli);
+            assert_eq!(input, "5m x", "{:?}", args);
+            assert!(
+                flags.background && !flags.urgent && !flags.sound,
+                "{:?}",
+                args
+            );
+            // A timeout of its own, so BREAK_NOTIFICATION_TIMEOUT never applies
+            assert_eq!(
+                flags.notification_timeout(&notification),
+                Ok(Some(BACKGROUND_TIMEOUT_SECS)),
+                "{:?}",
+                args
+            );
+        }
+
+        // An explicit --timeout still wins
+        let cli = Cli::try_parse_from(["breakrs", "5m", "x", "--background", "--timeout", "10s"])
+            .unwrap();
+        let (_, flags, _, notification) = resolve_timer_input(&cli);
+        assert_eq!(flags.notification_timeout(&notification), Ok(Some(10)));
+
+        // Urgency and sound contradict the preset
+        for args in [
+            vec!["breakrs", "--background", "-u", "5m", "x"],
+            vec!["breakrs", "5m", "x", "--background", "-s"],
+            vec!["breakrs", "5m", "x", "--background", "--urgent-after", "1m"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (_, flags, _, notification) = resolve_timer_input(&cli);
+            assert!(
+                flags.notification_timeout(&notification).is_err(),
+                "{:?}",
+                args
+            );
+        }
+
+        // Without the preset nothing changes
+        let (_, flags, _, notification) =
+            resolve_timer_input(&Cli::try_parse_from(["breakrs", "5m", "x"]).unwrap());
+        assert_eq!(flags.notification_timeout(&notification), Ok(None));
+    }
+
+    #[test]
+    fn test_persist_and_transient_flags() {
+        for (args, expected) in [
+            (vec!["breakrs", "5m", "x"], Ok(None)),
+            (vec!["breakrs", "--transient", "5m", "x"], Ok(Some(true))),
+            (vec!["breakrs", "5m", "x", "--transient"], Ok(Some(true))),
+            (vec!["breakrs", "--persist", "5m", "x"], Ok(Some(false))),
+            (vec!["breakrs", "5m", "x", "--persist"], Ok(Some(false))),
+            (
+                vec!["breakrs", "5m", "x", "--persist", "--transient"],
+                Err("--persist can't be combined with --transient".to_string()),
+            ),
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (input, flags, _, _) = resolve_timer_input(&cli);
+            assert_eq!(input, "5m x", "{:?}", args);
+            assert_eq!(flags.transient(), expected, "{:?}", args);
+        }
+
+        assert!(Cli::try_parse_from(["breakrs", "--persist", "--transient", "5m", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_short_flag_group_is_message_text() {
+        let (cleaned, flags) =
+            extract_flags_from_input(&["5m".to_string(), "-ux".to_string(), "-".to_string()]);
+        assert_eq!(cleaned, "5m -ux -");
+        assert_eq!(flags, TimerFlags::default());
+    }
+
+    #[test]
+    fn test_flag_only_input_needs_usage() {
+        let (cleaned, flags) = extract_flags_from_input(&["-u".to_string()]);
+        assert!(flags.urgent);
+        assert!(!has_timer_input(&cleaned));
+
+        let (cleaned, flags) = extract_flags_from_input(&["--sound".to_string()]);
+        assert!(flags.sound);
+        assert!(!has_timer_input(&cleaned));
+
+        let (cleaned, _) = extract_flags_from_input(&["-u".to_string(), "  ".to_string()]);
+        assert!(!has_timer_input(&cleaned));
+
+        let (cleaned, _) = extract_flags_from_input(&["5m".to_string(), "-u".to_string()]);
+        assert!(has_timer_input(&cleaned));
+    }
+
+    #[test]
+    fn test_extract_tags_from_input() {
+        let input: Vec<String> = ["--tag", "work", "5m", "standup", "--tag=meetings", "-u"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (rest, tags) = extract_option_from_input(&input, "--tag");
+        assert_eq!(rest, vec!["5m", "standup", "-u"]);
+        assert_eq!(tags, vec!["work", "meetings"]);
+
+        // A dangling --tag stays in the message
+        let input = vec!["5m".to_string(), "--tag".to_string()];
+        let (rest, tags) = extract_option_from_input(&input, "--tag");
+        assert_eq!(rest, vec!["5m", "--tag"]);
+        assert!(tags.is_empty());
+    }
+
+    #[test]
+    fn test_icon_in_any_position() {
+        for args in [
+            vec!["breakrs", "--icon", "mail", "5m", "x"],
+            vec!["breakrs", "5m", "x", "--icon", "mail"],
+            vec!["breakrs", "--icon", "other", "5m", "x", "--icon=mail"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (input, _, _, notification) = resolve_timer_input(&cli);
+            assert_eq!(input, "5m x", "{:?}", args);
+            assert_eq!(notification.icon.as_deref(), Some("mail"), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_timeout_option() {
+        for args in [
+            vec!["breakrs", "--timeout", "10s", "5m", "x"],
+            vec!["breakrs", "5m", "x", "--timeout", "10s"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (input, _, _, notification) = resolve_timer_input(&cli);
+            assert_eq!(input, "5m x", "{:?}", args);
+            assert_eq!(notification.timeout_secs(), Ok(Some(10)), "{:?}", args);
+        }
+
+        let options = |timeout: &str| NotificationOptions {
+            timeout: Some(timeout.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(options("Never").timeout_secs(), Ok(Some(0)));
+        assert_eq!(options("1m").timeout_secs(), Ok(Some(60)));
+        assert!(options("forever").timeout_secs().is_err());
+        assert_eq!(NotificationOptions::default().timeout_secs(), Ok(None));
+    }
+
+    #[test]
+    fn test_urgent_after_option() {
+        let cli = Cli::try_parse_from(["breakrs", "5m", "tea", "--urgent-after", "2m"]).unwrap();
+        let (input, flags, _, notification) = resolve_timer_input(&cli);
+        assert_eq!(input, "5m tea");
+        assert!(!flags.urgent);
+        assert_eq!(notification.urgent_after_secs(), Ok(Some(120)));
+
+        let invalid = NotificationOptions {
+            urgent_after: Some("later".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.urgent_after_secs().is_err());
+        let too_long = NotificationOptions {
+            urgent_after: Some("1000000000d".to_string()),
+            ..Default::default()
+        };
+        assert!(too_long.urgent_after_secs().is_err());
+    }
+
+    #[test]
+    fn test_edit_flag_options() {
+        assert_eq!(flag_change(false, false), None);
+        assert_eq!(flag_change(true, false), Some(true));
+        assert_eq!(flag_change(false, true), Some(false));
+
+        let cli = Cli::try_parse_from(["breakrs", "edit", "3", "--urgent", "--no-sound"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Edit {
+                id: 3,
+                duration: None,
+                urgent: true,
+                no_urgent: false,
+                sound: false,
+                no_sound: true,
+                recurring: false,
+                no_recurring: false,
+                ..
+            })
+        ));
+
+        // Something to change is required, and a flag can't be set and cleared at once
+        assert!(Cli::try_parse_from(["breakrs", "edit", "3"]).is_err());
+        assert!(Cli::try_parse_from(["breakrs", "edit", "3", "--silent", "--no-silent"]).is_err());
+        assert!(Cli::try_parse_from(["breakrs", "edit", "3", "--duration", "10m"]).is_ok());
+    }
+
+    #[test]
+    fn test_nag_option() {
+        for args in [
+            vec!["breakrs", "--nag", "30s", "5m", "tea"],
+            vec!["breakrs", "5m", "tea", "--nag", "30s"],
+            vec!["breakrs", "5m", "tea", "--nag=30s"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (input, _, _, notification) = resolve_timer_input(&cli);
+            assert_eq!(input, "5m tea", "{:?}", args);
+            assert_eq!(notification.nag_secs(), Ok(Some(30)), "{:?}", args);
+        }
+
+        let invalid = NotificationOptions {
+            nag: Some("often".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.nag_secs().is_err());
+        let too_long = NotificationOptions {
+            nag: Some("1000000000d".to_string()),
+            ..Default::default()
+        };
+        assert!(too_long.nag_secs().is_err());
+    }
+
+    #[test]
+    fn test_messages_option() {
+        for args in [
+            vec!["breakrs", "-r", "--messages", "a", "b", "c", "--", "1h"],
+            vec!["breakrs", "1h", "--messages", "a", "b", "c", "-r"],
+            vec![
+                "breakrs",
+                "-r",
+                "1h",
+                "--messages",
+                "a",
+                "b",
+                "--messages",
+                "c",
+            ],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            let (input, flags, _, notification) = resolve_timer_input(&cli);
+            assert_eq!(input, "1h", "{:?}", args);
+            assert!(flags.recurring, "{:?}", args);
+            assert_eq!(notification.messages, vec!["a", "b", "c"], "{:?}", args);
+            assert_eq!(
+                notification.rotating_messages(true),
+                Ok(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+            );
+            assert!(notification.rotating_messages(false).is_err());
+        }
+
+        // One message is just the timer's message
+        let single = NotificationOptions {
+            messages: vec!["a".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(single.rotating_messages(false), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_validate_template_errors() {
+        assert!(validate_template("#{id} {message}").is_ok());
+        assert!(validate_template("no placeholders").is_ok());
+
+        let err = validate_template("{id} {nope}").unwrap_err();
+        assert!(err.contains("Unknown placeholder '{nope}'"));
+
+        let err = validate_template("{id").unwrap_err();
+        assert!(err.contains("Unterminated placeholder"));
+    }
+
+    #[test]
+    fn test_error_json() {
+        // A bad --created-after stays a parse error, so --json reports it as one
+        let err = CreatedRange::parse(Some("yesterday-ish"), None)
+            .err()
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&error_json(err.as_ref())).unwrap();
+        assert_eq!(value["code"], "parse");
+        assert!(
+            value["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid --created-after")
+        );
+
+        let err: Box<dyn std::error::Error> =
+            io::Error::new(io::ErrorKind::NotFound, "missing").into();
+        assert_eq!(error_code(err.as_ref()), "io");
+        let err: Box<dyn std::error::Error> = NotFoundError("Timer #4 not found".into()).into();
+        assert_eq!(
+            error_json(err.as_ref()),
+            r#"{"code":"not_found","error":"Timer #4 not found"}"#
+        );
+        let err: Box<dyn std::error::Error> = "--persist can't be combined with --transient".into();
+        assert_eq!(error_code(err.as_ref()), "other");
+
+        // Option values keep their parse error type too
+        let filter = ListFilter {
+            due_within: Some("soonish"),
+            ..Default::default()
+        };
+        let err = list_timers(
+            None,
+            false,
+            filter,
+            None,
+            Some(false),
+            false,
+            &mut daemon::Autostart::configured(false),
+        )
+        .unwrap_err();
+        assert_eq!(error_code(err.as_ref()), "parse");
+    }
+
+    #[test]
+    fn test_not_found_suggestions() {
+        let ids = [1, 2, 7, 31];
+
+        assert_eq!(nearest_ids(12, &ids), vec![2, 1]);
+        assert_eq!(
+            not_found_message(12, &ids),
+            "Timer #12 not found. Did you mean #2 or #1?"
+        );
+        // A changed digit, nearest first
+        assert_eq!(nearest_ids(3, &ids), vec![2, 1]);
+        assert_eq!(nearest_ids(37, &ids), vec![31, 7]);
+        assert_eq!(
+            not_found_message(71, &ids),
+            "Timer #71 not found. Did you mean #31 or #7?"
+        );
+        // Nothing close, or no timers at all
+        assert_eq!(not_found_message(458, &ids), "Timer #458 not found");
+        assert_eq!(not_found_message(12, &[]), "Timer #12 not found");
+    }
+
+    #[test]
+    fn test_completions_by_hour() {
+        let offset = time::macros::offset!(+2);
+        let completed = |at: OffsetDateTime| {
+            let mut timer = test_timer(at);
+            timer.uuid = uuid::Uuid::new_v4();
+            timer.completed_at = Some(at);
+            timer
+        };
+        // 09:15, 09:40 and 14:05 local time; the 09:40 one is also in the journal
+        let history = vec![
+            completed(time::macros::datetime!(2025-01-24 07:15:00 UTC)),
+            completed(time::macros::datetime!(2025-01-24 07:40:00 UTC)),
+            completed(time::macros::datetime!(2025-01-24 12:05:00 UTC)),
+        ];
+        let journaled = |event, timer: &database::Timer| {
+            journal::JournalEvent::new(event, timer, timer.completed_at.unwrap())
+        };
+        // Completed before the history kept it, at 23:30 local time
+        let older = completed(time::macros::datetime!(2025-01-20 21:30:00 UTC));
+        let events = vec![
+            journaled(journal::EventKind::Completed, &older),
+            journaled(journal::EventKind::Completed, &history[1]),
+            // Only completions count
+            journaled(journal::EventKind::Fired, &history[2]),
+        ];
+
+        let counts = completions_by_hour(&history, &events, offset);
+        let mut expected = [0; 24];
+        expected[9] = 2;
+        expected[14] = 1;
+        expected[23] = 1;
+        assert_eq!(counts, expected);
+        assert_eq!(
+            serde_json::to_string(&counts).unwrap().matches(',').count(),
+            23
+        );
+
+        let histogram = render_hour_histogram(&counts);
+        let lines: Vec<&str> = histogram.lines().collect();
+        assert_eq!(lines.len(), 24);
+        assert_eq!(lines[9], format!("09  {}  2", "#".repeat(40)));
+        assert_eq!(lines[14], format!("14  {:<40}  1", "#".repeat(20)));
+        assert_eq!(lines[0], format!("00  {:<40}  0", ""));
+
+        // An optional mode of `status`, which `stats` still names
+        let cli = Cli::try_parse_from(["breakrs", "stats", "--by-hour", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Status {
+                by_hour: true,
+                json: true,
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from(["breakrs", "stats"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Status { by_hour: false, .. })
+        ));
+        assert!(Cli::try_parse_from(["breakrs", "status", "--json"]).is_err());
+        assert!(Cli::try_parse_from(["breakrs", "status", "--by-hour", "--watch"]).is_err());
+    }
+
+    #[test]
+    fn test_replace_option() {
+        let cli =
+            Cli::try_parse_from(["breakrs", "--replace", "3", "10m", "new", "thing"]).unwrap();
+        let (input, flags, _, _) = resolve_timer_input(&cli);
+        assert_eq!(input, "10m new thing");
+        assert_eq!(flags.replace, Some(3));
+        assert!(Cli::try_parse_from(["breakrs", "--replace", "three", "10m", "x"]).is_err());
+    }
 }
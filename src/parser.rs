@@ -2,14 +2,21 @@
 //!
 //! This module provides flexible parsing of natural language duration input,
 //! supporting multiple formats including standard time units (`5m`, `1h30m`),
-//! colon-formatted times (`5:30`, `1:30:45`), and mixed formats.
+//! colon-formatted times (`5:30`, `1:30:45`), absolute times (`at 5pm`,
+//! `tomorrow at 9am`, `monday at 9am`, `quarter past 3`), and mixed formats.
 
+use crate::text;
 use std::error::Error;
 use std::fmt;
+use time::{Duration, OffsetDateTime, Time, Weekday};
 
 // Time constants to avoid magic numbers
 const SECONDS_PER_MINUTE: u64 = 60;
 const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE; // 3600
+const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR; // 86400
+
+/// Message used for duration-only input when empty messages are allowed
+pub const DEFAULT_MESSAGE: &str = "Break";
 
 #[derive(Debug)]
 pub struct ParseError(String);
@@ -22,9 +29,53 @@ impl fmt::Display for ParseError {
 
 impl Error for ParseError {}
 
+impl ParseError {
+    /// Prefixes the message with where the rejected input came from, such as
+    /// the option it was given to.
+    pub fn context(self, context: &str) -> Self {
+        ParseError(format!("{}: {}", context, self.0))
+    }
+}
+
+/// Options that relax the default parsing rules.
+#[derive(Debug, Default)]
+pub struct ParseOptions<'a> {
+    /// Message to use when the input contains a duration but no message text.
+    /// When `None`, duration-only input is an error.
+    pub default_message: Option<&'a str>,
+    /// Reference time for absolute times like `at 5pm`, in the user's local offset.
+    /// When `None`, the current UTC time is used.
+    pub now: Option<OffsetDateTime>,
+    /// Exact duration in seconds (`--seconds`). When set, no duration is parsed:
+    /// the whole input is the message.
+    pub seconds: Option<u64>,
+    /// Also read a unit written before its number (`m5` as `5m`), for users who
+    /// mistype durations. Off by default, since it can turn message text like
+    /// `h 2` into a duration.
+    pub lenient: bool,
+    /// Also read a comma between digits as a decimal point (`1,5h` as `1.5h`).
+    /// Off by default, since it would turn `1,000s` into one second.
+    pub decimal_comma: bool,
+}
+
+/// Timer input split into its total duration and message, along with the
+/// separately written durations the total was added up from.
+#[derive(Debug, PartialEq)]
+pub struct ParsedInput {
+    /// Total duration in seconds
+    pub seconds: u64,
+    pub message: String,
+    /// Seconds of each duration written apart from the others. Units that count
+    /// down in one run (`1h 30m`, `2h15m`) and `+`/`-` arithmetic form one part;
+    /// `5m 3h` or `5m meeting 3h` have two.
+    pub parts: Vec<u64>,
+}
+
 #[derive(Debug)]
 enum Token {
     Number(u64),
+    /// A fractional number and the text it was written as (e.g. `1,5`)
+    Decimal(f64, String),
     Unit(String),
 }
 
@@ -178,11 +229,128 @@ fn parse_number_word(word: &str) -> Option<u64> {
     }
 }
 
+/// Parses an approximate amount word (`couple` → 2, `few` → 3).
+///
+/// Unlike [`parse_number_word`], these are only numbers when a time unit follows,
+/// so they are not turned into number tokens by the tokenizer.
+fn parse_approximate_number(word: &str) -> Option<u64> {
+    match word {
+        "couple" => Some(2),
+        "few" => Some(3),
+        _ => None,
+    }
+}
+
+/// Matches an approximate duration like `a couple minutes` at the start of `tokens`.
+///
+/// The article (`a`/`an`) and an `of` before the unit are optional, so
+/// `couple of hours` works too.
+///
+/// # Returns
+///
+/// Returns the duration in seconds and the number of tokens consumed, or `None`
+/// if the tokens don't form an approximate duration (e.g. `a few good men`).
+fn parse_approximate_duration(tokens: &[Token]) -> Option<(u64, usize)> {
+    let mut i = 0;
+    if matches!(tokens.first(), Some(Token::Unit(word)) if word == "a" || word == "an") {
+        i += 1;
+    }
+
+    let Some(Token::Unit(word)) = tokens.get(i) else {
+        return None;
+    };
+    let amount = parse_approximate_number(word)?;
+    i += 1;
+
+    if matches!(tokens.get(i), Some(Token::Unit(word)) if word == "of") {
+        i += 1;
+    }
+
+    let Some(Token::Unit(unit)) = tokens.get(i) else {
+        return None;
+    };
+    let multiplier = parse_unit(unit).ok()?;
+
+    Some((amount * multiplier, i + 1))
+}
+
+/// Matches a single duration component (`30s`, `1.5h`, `a few minutes`) at the
+/// start of `tokens`.
+///
+/// # Returns
+///
+/// Returns the duration in seconds and the number of tokens consumed, or `None`
+/// if `tokens` doesn't start with a duration.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the duration doesn't fit in 64 bits of seconds.
+fn parse_duration_component(tokens: &[Token]) -> Result<Option<(u64, usize)>, ParseError> {
+    let Some(first) = tokens.first() else {
+        return Ok(None);
+    };
+    let multiplier = match tokens.get(1) {
+        Some(Token::Unit(unit)) => parse_unit(unit).ok(),
+        _ => None,
+    };
+    Ok(match (first, multiplier) {
+        (Token::Number(num), Some(multiplier)) => Some((unit_seconds(*num, multiplier)?, 2)),
+        (Token::Decimal(value, _), Some(multiplier)) => {
+            Some(((value * multiplier as f64).round() as u64, 2))
+        }
+        _ => parse_approximate_duration(tokens),
+    })
+}
+
+/// Returns `num` units of `multiplier` seconds each, e.g. `5h` as 18000.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the result doesn't fit in 64 bits of seconds.
+fn unit_seconds(num: u64, multiplier: u64) -> Result<u64, ParseError> {
+    num.checked_mul(multiplier)
+        .ok_or_else(duration_too_long_error)
+}
+
+/// Adds `seconds` to a running duration total.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the sum doesn't fit in 64 bits of seconds.
+fn add_seconds(total: u64, seconds: u64) -> Result<u64, ParseError> {
+    total
+        .checked_add(seconds)
+        .ok_or_else(duration_too_long_error)
+}
+
+/// The error for a duration too large to count in seconds at all.
+fn duration_too_long_error() -> ParseError {
+    ParseError("Duration is too long".to_string())
+}
+
+/// Builds a number token from digits with an optional `.` or `,` decimal separator.
+fn number_token(text: &str) -> Result<Token, ParseError> {
+    if text.contains(['.', ',']) {
+        let value: f64 = text
+            .replace(',', ".")
+            .parse()
+            .map_err(|_| ParseError(format!("Invalid number: {}", text)))?;
+        return Ok(Token::Decimal(value, text.to_string()));
+    }
+
+    let num: u64 = text
+        .parse()
+        .map_err(|_| ParseError(format!("Invalid number: {}", text)))?;
+    Ok(Token::Number(num))
+}
+
 /// Tokenizes input string into a sequence of numbers and units.
 ///
 /// This function breaks down a mixed input string (like "5m 30s reminder") into
 /// a sequence of tokens that can be processed by the parser. It handles:
 /// - Numeric digits (`5`, `30`) → `Token::Number`
+/// - Decimals (`1.5`, or `1,5` as written in many European locales when
+///   `decimal_comma` is set) → `Token::Decimal`
 /// - Text words (`m`, `minutes`, `reminder`) → `Token::Unit`
 /// - Number words (`five`, `twenty`) → `Token::Number` (via `parse_number_word`)
 /// - Special characters (emoji, punctuation) → included in `Token::Unit`
@@ -193,6 +361,8 @@ fn parse_number_word(word: &str) -> Option<u64> {
 /// # Arguments
 ///
 /// * `input` - The raw input string to tokenize
+/// * `decimal_comma` - Whether a comma between digits is a decimal point; when
+///   not, a word like `1,000s` is kept whole as message text
 ///
 /// # Returns
 ///
@@ -202,16 +372,49 @@ fn parse_number_word(word: &str) -> Option<u64> {
 /// # Examples
 ///
 /// ```ignore
-/// let tokens = tokenize("5m 30s break")?;
+/// let tokens = tokenize("5m 30s break", false)?;
 /// // Results in: [Number(5), Unit("m"), Number(30), Unit("s"), Unit("break")]
 /// ```
-fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+fn tokenize(input: &str, decimal_comma: bool) -> Result<Vec<Token>, ParseError> {
     let input = input.trim().to_lowercase();
     let mut tokens = Vec::new();
+
+    for word in input.split_whitespace() {
+        // A clock time left in the message (`buy 3pm tickets`) stays one word
+        if parse_bare_meridiem_time(word).is_some() {
+            tokens.push(Token::Unit(word.to_string()));
+            continue;
+        }
+        // So is a number with a thousands separator (`1,000s`)
+        if !decimal_comma && has_comma_between_digits(word) {
+            tokens.push(Token::Unit(word.to_string()));
+            continue;
+        }
+        tokenize_word(word, decimal_comma, &mut tokens)?;
+    }
+
+    Ok(tokens)
+}
+
+/// Returns whether `word` has a comma with a digit on each side, like `1,000`.
+fn has_comma_between_digits(word: &str) -> bool {
+    word.as_bytes()
+        .windows(3)
+        .any(|w| w[0].is_ascii_digit() && w[1] == b',' && w[2].is_ascii_digit())
+}
+
+/// Tokenizes one whitespace-free word for `tokenize`, appending to `tokens`.
+fn tokenize_word(
+    word: &str,
+    decimal_comma: bool,
+    tokens: &mut Vec<Token>,
+) -> Result<(), ParseError> {
     let mut current = String::new();
     let mut in_number = false;
 
-    for ch in input.chars() {
+    let mut chars = word.chars().peekable();
+
+    while let Some(ch) = chars.next() {
         if ch.is_ascii_digit() {
             if !in_number && !current.is_empty() {
                 // Transitioning from text to number, save the text token
@@ -220,44 +423,31 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
             }
             in_number = true;
             current.push(ch);
+        } else if in_number
+            && (ch == '.' || (ch == ',' && decimal_comma))
+            && !current.contains(['.', ','])
+            && chars.peek().is_some_and(|next| next.is_ascii_digit())
+        {
+            // Decimal separator between digits; elsewhere these are message punctuation
+            current.push(ch);
         } else if ch.is_ascii_alphabetic() {
             if in_number && !current.is_empty() {
                 // Transitioning from number to text, save the number token
-                let num: u64 = current
-                    .parse()
-                    .map_err(|_| ParseError(format!("Invalid number: {}", current)))?;
-                tokens.push(Token::Number(num));
+                tokens.push(number_token(&current)?);
                 current.clear();
             }
             in_number = false;
             current.push(ch);
-        } else if ch.is_whitespace() {
-            // Save current token if any
-            if !current.is_empty() {
-                if in_number {
-                    let num: u64 = current
-                        .parse()
-                        .map_err(|_| ParseError(format!("Invalid number: {}", current)))?;
-                    tokens.push(Token::Number(num));
-                } else {
-                    // Check if this is a number word before treating as unit
-                    if let Some(num) = parse_number_word(&current) {
-                        tokens.push(Token::Number(num));
-                    } else {
-                        tokens.push(Token::Unit(current.clone()));
-                    }
-                }
-                current.clear();
-                in_number = false;
-            }
+        } else if text::extends_grapheme(ch) && !current.is_empty() {
+            // Combining marks, variation selectors and joiners belong to the character
+            // before them; after a digit (keycaps like "1️⃣") the token becomes text
+            in_number = false;
+            current.push(ch);
         } else {
             // Allow other characters as part of message text (emoji, punctuation, etc.)
             // If we're in a number, save it first
             if in_number && !current.is_empty() {
-                let num: u64 = current
-                    .parse()
-                    .map_err(|_| ParseError(format!("Invalid number: {}", current)))?;
-                tokens.push(Token::Number(num));
+                tokens.push(number_token(&current)?);
                 current.clear();
                 in_number = false;
             }
@@ -269,10 +459,7 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
     // Save final token
     if !current.is_empty() {
         if in_number {
-            let num: u64 = current
-                .parse()
-                .map_err(|_| ParseError(format!("Invalid number: {}", current)))?;
-            tokens.push(Token::Number(num));
+            tokens.push(number_token(&current)?);
         } else {
             // Check if this is a number word before treating as unit
             if let Some(num) = parse_number_word(&current) {
@@ -283,7 +470,7 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
         }
     }
 
-    Ok(tokens)
+    Ok(())
 }
 
 /// Parses a time unit string into its equivalent number of seconds.
@@ -293,6 +480,7 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
 ///
 /// # Supported Units
 ///
+/// - **Days**: `d`, `day`, `days` → 86400 seconds
 /// - **Hours**: `h`, `hr`, `hrs`, `hour`, `hours` → 3600 seconds
 /// - **Minutes**: `m`, `min`, `mins`, `minute`, `minutes` → 60 seconds
 /// - **Seconds**: `s`, `sec`, `secs`, `second`, `seconds` → 1 second
@@ -315,6 +503,8 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
 /// ```
 fn parse_unit(unit: &str) -> Result<u64, ParseError> {
     match unit {
+        // Days
+        "d" | "day" | "days" => Ok(SECONDS_PER_DAY),
         // Hours
         "h" | "hr" | "hrs" | "hour" | "hours" | "horus" | "housr" => Ok(SECONDS_PER_HOUR),
         // Minutes
@@ -375,6 +565,37 @@ fn is_colon_time(s: &str) -> bool {
     s.chars().all(|c| c.is_ascii_digit() || c == ':')
 }
 
+/// Splits a word like `1h5:30` into its standard-unit prefix and colon time.
+///
+/// Only words whose prefix is a complete duration (ending in a unit) qualify, so
+/// text such as `room5:30` stays in the message.
+///
+/// # Returns
+///
+/// Returns `Some((units, colon_time))`, e.g. `("1h", "5:30")`, or `None`.
+fn split_unit_prefixed_colon_time(word: &str) -> Option<(&str, &str)> {
+    let units = word.trim_end_matches(|c: char| c.is_ascii_digit() || c == ':');
+    let colon_time = &word[units.len()..];
+
+    let ends_with_unit = units.ends_with(|c: char| c.is_ascii_alphabetic());
+    (ends_with_unit && is_colon_time(colon_time) && parse_duration(units).is_ok())
+        .then_some((units, colon_time))
+}
+
+/// Detects a colon time followed directly by a time unit, like `5:30m`.
+///
+/// Suffixes that aren't time units (such as `am`/`pm` in `10:30am`) don't match.
+///
+/// # Returns
+///
+/// Returns the colon time part (e.g. `"5:30"`), or `None`.
+fn colon_time_with_unit_suffix(word: &str) -> Option<&str> {
+    let split = word.find(|c: char| !(c.is_ascii_digit() || c == ':'))?;
+    let (colon_time, unit) = word.split_at(split);
+
+    (is_colon_time(colon_time) && parse_unit(&unit.to_lowercase()).is_ok()).then_some(colon_time)
+}
+
 /// Parses user input that mixes duration components with message text.
 ///
 /// This function accepts flexible, natural language input for specifying break timers.
@@ -417,38 +638,804 @@ fn is_colon_time(s: &str) -> bool {
 /// - No duration components found in input
 /// - No message text found (duration only)
 /// - Invalid time unit or format
+/// - An absolute time (`at 3pm`) together with a relative duration (`10m`)
 /// - Empty input
+#[cfg_attr(not(test), allow(dead_code))] // The CLI calls parse_input_with directly
 pub fn parse_input(input: &str) -> Result<(u64, String), ParseError> {
-    // First, scan for colon-formatted times
+    parse_input_with(input, &ParseOptions::default())
+}
+
+/// Parses a standalone duration such as `30d`, `1h 30m` or `5:30`.
+///
+/// Used for command options that take a duration rather than a timer, so any
+/// message text in the input is an error.
+///
+/// # Examples
+///
+/// ```
+/// # use breakrs::parser::parse_duration;
+/// assert_eq!(parse_duration("30d").unwrap(), 30 * 86400);
+/// assert_eq!(parse_duration("1h 30m").unwrap(), 5400);
+/// assert!(parse_duration("5m coffee").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns `ParseError` if no duration is found or the input contains other text.
+pub fn parse_duration(input: &str) -> Result<u64, ParseError> {
+    let options = ParseOptions {
+        default_message: Some(""),
+        ..Default::default()
+    };
+    let (seconds, message) = parse_input_with(input, &options)?;
+
+    if !message.is_empty() {
+        return Err(ParseError(format!(
+            "Unexpected text in duration: '{}'",
+            message
+        )));
+    }
+
+    Ok(seconds)
+}
+
+/// Replaces the `{<prefix>date}` and `{<prefix>time}` placeholders in a timer
+/// message with the date (`2025-01-24`) and time (`14:30`) of `at`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let message = expand_time_placeholders("notes for {date}", "", now);
+/// assert_eq!(message, "notes for 2025-01-24");
+/// let message = expand_time_placeholders("sent {fire_time}", "fire_", now);
+/// assert_eq!(message, "sent 10:00");
+/// ```
+pub fn expand_time_placeholders(message: &str, prefix: &str, at: OffsetDateTime) -> String {
+    let date = format!(
+        "{:04}-{:02}-{:02}",
+        at.year(),
+        u8::from(at.month()),
+        at.day()
+    );
+    let time = format!("{:02}:{:02}", at.hour(), at.minute());
+    message
+        .replace(&format!("{{{}date}}", prefix), &date)
+        .replace(&format!("{{{}time}}", prefix), &time)
+}
+
+/// Parses a notification timeout: a duration such as `10s`, or `never`.
+///
+/// # Returns
+///
+/// Returns the timeout in seconds, with `never` (and a zero duration) as `0`.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the value is neither `never` nor a valid duration.
+pub fn parse_timeout(value: &str) -> Result<u64, ParseError> {
+    if value.trim().eq_ignore_ascii_case("never") {
+        return Ok(0);
+    }
+    parse_duration(value)
+}
+
+/// Parses a clock time like `9am`, `5:30 pm`, or `17:00` at the start of `words`.
+///
+/// Without `am`/`pm` the time must use the 24-hour `H:MM` form, so a bare number
+/// (`at 12`) is never taken as a time.
+///
+/// # Returns
+///
+/// Returns the time and the number of words consumed (2 for `5:30 pm`), or `None`
+/// if the words don't start with a clock time.
+fn parse_clock_time(words: &[&str]) -> Option<(Time, usize)> {
+    let word = words.first()?.to_lowercase();
+    let next = words.get(1).map(|w| w.to_lowercase());
+
+    let (clock, meridiem, consumed) = if let Some(clock) = word.strip_suffix("am") {
+        (clock.to_string(), Some(false), 1)
+    } else if let Some(clock) = word.strip_suffix("pm") {
+        (clock.to_string(), Some(true), 1)
+    } else if matches!(next.as_deref(), Some("am" | "pm")) {
+        (word, Some(next.as_deref() == Some("pm")), 2)
+    } else {
+        (word, None, 1)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse::<u8>().ok()?),
+        Some(_) => return None,
+        None if meridiem.is_some() => (clock.as_str(), 0),
+        None => return None,
+    };
+    if hour.is_empty() || !hour.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hour: u8 = hour.parse().ok()?;
+
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+
+    Some((Time::from_hms(hour, minute, 0).ok()?, consumed))
+}
+
+/// Parses a bare hour with am/pm like `3pm` or `12am`, which is clearly a clock
+/// time even without `at` (there is no `pm` unit).
+fn parse_bare_meridiem_time(word: &str) -> Option<Time> {
+    let hour = word
+        .strip_suffix("am")
+        .or_else(|| word.strip_suffix("pm"))?;
+    if !(1..=2).contains(&hour.len()) || !hour.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    parse_clock_time(&[word]).map(|(time, _)| time)
+}
+
+/// Parses an English clock idiom like `quarter past 3`, `half past 2`, or
+/// `a quarter to 4pm` at the start of `words`.
+///
+/// The hour is a number or number word from 1 to 12, optionally with am/pm
+/// (`3pm`, `3 pm`).
+///
+/// # Returns
+///
+/// Returns the time, whether it is ambiguous (no am/pm given, so it may also mean
+/// twelve hours later), and the number of words consumed; or `None` if the words
+/// don't start with an idiom.
+fn parse_clock_idiom(words: &[&str]) -> Option<(Time, bool, usize)> {
+    let lower: Vec<String> = words.iter().take(5).map(|w| w.to_lowercase()).collect();
+    let start = usize::from(lower.first().is_some_and(|w| w == "a"));
+
+    let minutes_past: i64 = match (lower.get(start)?.as_str(), lower.get(start + 1)?.as_str()) {
+        ("quarter", "past") => 15,
+        ("half", "past") => 30,
+        ("quarter", "to") => -15,
+        _ => return None,
+    };
+
+    let hour_word = lower.get(start + 2)?;
+    let (hour, ambiguous, consumed) = if let Some(time) = parse_bare_meridiem_time(hour_word) {
+        (time.hour(), false, 3)
+    } else {
+        let hour = parse_twelve_hour(hour_word)? % 12;
+        match lower.get(start + 3).map(String::as_str) {
+            Some("am") => (hour, false, 4),
+            Some("pm") => (hour + 12, false, 4),
+            _ => (hour, true, 3),
+        }
+    };
+
+    let time = Time::from_hms(hour, 0, 0).ok()? + Duration::minutes(minutes_past);
+    Some((time, ambiguous, start + consumed))
+}
+
+/// Parses an hour from 1 to 12 written as a number or number word (`3`, `three`).
+fn parse_twelve_hour(word: &str) -> Option<u8> {
+    let hour = word.parse().ok().or_else(|| parse_number_word(word))?;
+    (1..=12).contains(&hour).then_some(hour as u8)
+}
+
+/// Parses an o'clock time like `3 o'clock`, `three oclock` or `9 o clock pm` at
+/// the start of `words`.
+///
+/// The trailing `o'clock` is what marks the bare hour as a time rather than a
+/// number in the message.
+///
+/// # Returns
+///
+/// Returns the time, whether it is ambiguous (no am/pm given), and the number of
+/// words consumed; or `None` if the words don't start with an o'clock time.
+fn parse_oclock(words: &[&str]) -> Option<(Time, bool, usize)> {
+    let lower: Vec<String> = words.iter().take(4).map(|w| w.to_lowercase()).collect();
+    let hour = parse_twelve_hour(lower.first()?)? % 12;
+
+    let consumed = match lower.get(1)?.as_str() {
+        "o'clock" | "o’clock" | "oclock" => 2,
+        "o" if lower.get(2).is_some_and(|w| w == "clock") => 3,
+        _ => return None,
+    };
+    let (hour, ambiguous, consumed) = match lower.get(consumed).map(String::as_str) {
+        Some("am") => (hour, false, consumed + 1),
+        Some("pm") => (hour + 12, false, consumed + 1),
+        _ => (hour, true, consumed),
+    };
+
+    Some((Time::from_hms(hour, 0, 0).ok()?, ambiguous, consumed))
+}
+
+/// Parses a spoken clock time (`quarter past 3`, `3 o'clock`) at the start of
+/// `words`, optionally after `at`.
+///
+/// # Returns
+///
+/// Returns the same as `parse_clock_idiom`, counting the `at` in the words consumed.
+fn parse_spoken_time(words: &[&str]) -> Option<(Time, bool, usize)> {
+    let skip = usize::from(words.first()?.eq_ignore_ascii_case("at"));
+    parse_clock_idiom(&words[skip..])
+        .or_else(|| parse_oclock(&words[skip..]))
+        .map(|(time, ambiguous, consumed)| (time, ambiguous, skip + consumed))
+}
+
+/// Returns the next time the clock shows `time` after `now` (today or tomorrow).
+fn next_occurrence(now: OffsetDateTime, time: Time) -> OffsetDateTime {
+    let today = now.replace_time(time);
+    if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+/// Parses a point in the past, as used by the `--created-after`/`--created-before`
+/// filters.
+///
+/// Accepts a clock time (`9:00`, `9am`, `at 17:30`), meaning its most recent
+/// occurrence (today, or yesterday if it is still ahead), or a duration (`2h`)
+/// meaning that long before `now`. Clock times use the offset of `now`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let since = parse_past_time("2h", now)?; // two hours ago
+/// let morning = parse_past_time("9:00", now)?; // today at 9:00
+/// ```
+///
+/// # Errors
+///
+/// Returns `ParseError` if the input is neither a clock time nor a duration, or
+/// is a duration reaching back before the earliest representable date.
+pub fn parse_past_time(input: &str, now: OffsetDateTime) -> Result<OffsetDateTime, ParseError> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let clock_words = match words.split_first() {
+        Some((first, rest)) if first.eq_ignore_ascii_case("at") => rest,
+        _ => &words[..],
+    };
+
+    if let Some((time, consumed)) = parse_clock_time(clock_words)
+        && consumed == clock_words.len()
+    {
+        let today = now.replace_time(time);
+        return Ok(if today <= now {
+            today
+        } else {
+            today - Duration::days(1)
+        });
+    }
+
+    let seconds = parse_duration(input).map_err(|_| {
+        ParseError(format!(
+            "Invalid time '{}' (use a clock time like 9:00 or a duration like 2h)",
+            input.trim()
+        ))
+    })?;
+    i64::try_from(seconds)
+        .ok()
+        .and_then(|seconds| now.checked_sub(Duration::seconds(seconds)))
+        .ok_or_else(|| ParseError(format!("Time '{}' is too far back", input.trim())))
+}
+
+/// Parses a minute-of-the-hour shorthand like `:00` or `:30`.
+///
+/// # Returns
+///
+/// Returns the minute (0-59), or `None` if the word isn't a colon followed by
+/// exactly two digits.
+fn parse_minute_alignment(word: &str) -> Option<u8> {
+    let minute = word.strip_prefix(':')?;
+    if minute.len() != 2 || !minute.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    minute.parse().ok().filter(|minute| *minute < 60)
+}
+
+/// Returns the next time after `now` that the clock reaches `minute` past the hour.
+fn next_minute_boundary(now: OffsetDateTime, minute: u8) -> OffsetDateTime {
+    // `minute` is always below 60, so the time is valid
+    let this_hour = Time::from_hms(now.hour(), minute, 0).map_or(now, |t| now.replace_time(t));
+    if this_hour > now {
+        this_hour
+    } else {
+        this_hour + Duration::hours(1)
+    }
+}
+
+/// The day an `at <time>` is anchored to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DayAnchor {
+    Today,
+    Tomorrow,
+    /// The nearest upcoming weekday (`monday`)
+    Weekday(Weekday),
+    /// The weekday in the next calendar week (`next monday`)
+    NextWeek(Weekday),
+}
+
+/// Parses a weekday name, full (`monday`) or abbreviated (`mon`), in lowercase.
+///
+/// `wed`, `sat` and `sun` are ordinary words (`watch the sun at 7pm`), so those
+/// days need their full names.
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Monday),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tuesday),
+        "wednesday" => Some(Weekday::Wednesday),
+        "thursday" | "thu" | "thur" | "thurs" => Some(Weekday::Thursday),
+        "friday" | "fri" => Some(Weekday::Friday),
+        "saturday" => Some(Weekday::Saturday),
+        "sunday" => Some(Weekday::Sunday),
+        _ => None,
+    }
+}
+
+/// Parses a day anchor (`today`, `tomorrow`, `monday`, `next monday`) at the start
+/// of `words`.
+///
+/// # Returns
+///
+/// Returns the anchor and the number of words consumed, or `None` if the words
+/// don't start with one.
+fn parse_day_anchor(words: &[&str]) -> Option<(DayAnchor, usize)> {
+    let word = words.first()?.to_lowercase();
+    match word.as_str() {
+        "today" => Some((DayAnchor::Today, 1)),
+        "tomorrow" => Some((DayAnchor::Tomorrow, 1)),
+        "next" => {
+            let day = parse_weekday(&words.get(1)?.to_lowercase())?;
+            Some((DayAnchor::NextWeek(day), 2))
+        }
+        word => parse_weekday(word).map(|day| (DayAnchor::Weekday(day), 1)),
+    }
+}
+
+/// Returns the next `day` at `time` after `now`: today if it is that weekday and
+/// the time is still ahead, otherwise within the coming week.
+fn next_weekday_occurrence(now: OffsetDateTime, day: Weekday, time: Time) -> OffsetDateTime {
+    let days_ahead =
+        (7 + day.number_days_from_monday() - now.weekday().number_days_from_monday()) % 7;
+    let target = now.replace_time(time) + Duration::days(days_ahead.into());
+    if target > now {
+        target
+    } else {
+        target + Duration::days(7)
+    }
+}
+
+/// Returns `day` at `time` in the calendar week (Monday to Sunday) after the one
+/// containing `now`.
+fn weekday_next_week(now: OffsetDateTime, day: Weekday, time: Time) -> OffsetDateTime {
+    let monday =
+        now.replace_time(time) - Duration::days(now.weekday().number_days_from_monday().into());
+    monday + Duration::days(7 + i64::from(day.number_days_from_monday()))
+}
+
+/// Finds an absolute time (`at 5pm`, `3pm`, `today at 9:30am`, `tomorrow at 17:00`,
+/// `monday at 9am`, `:00`) in the input words and converts it into seconds from
+/// `now`.
+///
+/// - `at <time>` means the next occurrence: today, or tomorrow if it has passed
+/// - A bare hour with am/pm (`3pm`, `12am`) works like `at <time>`, unless
+///   `bare_times` is off
+/// - `quarter past 3`, `half past 2`, `quarter to 4` and `3 o'clock` work like
+///   `at <time>`; without am/pm they mean whichever of the two readings comes first
+/// - `today at <time>` must still be ahead today
+/// - `tomorrow at <time>` always means the next day
+/// - `<weekday> at <time>` means the nearest upcoming one: today if it is that day
+///   and the time is still ahead, otherwise within the coming week
+/// - `next <weekday> at <time>` means that day in the next calendar week (weeks
+///   start on Monday), so on a Wednesday `next friday` is nine days away
+/// - `:MM` means the next time the clock reaches that minute (`:00` is the top of
+///   the next hour, `:30` the next half hour)
+///
+/// Times are interpreted in the offset of `now`.
+///
+/// # Returns
+///
+/// Returns the seconds until the absolute time (0 if there is none) and the
+/// remaining words.
+///
+/// # Errors
+///
+/// Returns `ParseError` if `today at <time>` has already passed or the input
+/// contains more than one absolute time.
+fn extract_absolute_time<'a>(
+    words: &[&'a str],
+    now: OffsetDateTime,
+    bare_times: bool,
+) -> Result<(u64, Vec<&'a str>), ParseError> {
+    let (target, remaining) = find_absolute_time(words, now, bare_times)?;
+    let seconds = target.map_or(0, |target| (target - now).whole_seconds().max(1) as u64);
+    Ok((seconds, remaining))
+}
+
+/// Finds an absolute time in the input words, as described for
+/// `extract_absolute_time`.
+///
+/// # Returns
+///
+/// Returns the instant the absolute time refers to, if there is one, and the
+/// remaining words.
+///
+/// # Errors
+///
+/// Returns `ParseError` if `today at <time>` has already passed or the input
+/// contains more than one absolute time.
+fn find_absolute_time<'a>(
+    words: &[&'a str],
+    now: OffsetDateTime,
+    bare_times: bool,
+) -> Result<(Option<OffsetDateTime>, Vec<&'a str>), ParseError> {
+    let mut found = None;
+    let mut remaining = Vec::new();
+    let mut i = 0;
+
+    while i < words.len() {
+        let word = words[i].to_lowercase();
+
+        let (target, next) = if let Some(minute) = parse_minute_alignment(&word) {
+            (next_minute_boundary(now, minute), i + 1)
+        } else if let Some(time) = parse_bare_meridiem_time(&word).filter(|_| bare_times) {
+            (next_occurrence(now, time), i + 1)
+        } else if let Some((time, ambiguous, consumed)) = parse_spoken_time(&words[i..]) {
+            let target = next_occurrence(now, time);
+            let target = if ambiguous {
+                target.min(next_occurrence(now, time + Duration::hours(12)))
+            } else {
+                target
+            };
+            (target, i + consumed)
+        } else {
+            let anchor = parse_day_anchor(&words[i..]);
+            let at_index = i + anchor.map_or(0, |(_, consumed)| consumed);
+
+            let clock = words
+                .get(at_index)
+                .filter(|w| w.eq_ignore_ascii_case("at"))
+                .and_then(|_| parse_clock_time(&words[at_index + 1..]));
+
+            let Some((time, consumed)) = clock else {
+                remaining.push(words[i]);
+                i += 1;
+                continue;
+            };
+
+            let target = match anchor.map(|(anchor, _)| anchor) {
+                Some(DayAnchor::Tomorrow) => now.replace_time(time) + Duration::days(1),
+                Some(DayAnchor::Today) if now.replace_time(time) <= now => {
+                    return Err(ParseError(format!(
+                        "{} has already passed today",
+                        words[at_index + 1..at_index + 1 + consumed].join(" ")
+                    )));
+                }
+                Some(DayAnchor::Weekday(day)) => next_weekday_occurrence(now, day, time),
+                Some(DayAnchor::NextWeek(day)) => weekday_next_week(now, day, time),
+                Some(DayAnchor::Today) | None => next_occurrence(now, time),
+            };
+            (target, at_index + 1 + consumed)
+        };
+
+        if found.is_some() {
+            return Err(ParseError(
+                "Only one absolute time (like \"at 5pm\") is allowed".to_string(),
+            ));
+        }
+
+        found = Some(target);
+        i = next;
+    }
+
+    Ok((found, remaining))
+}
+
+/// Parses a point in the future given on its own, as used by
+/// `convert --to-absolute`.
+///
+/// Accepts the absolute times timer input does (`at 17:30`, `5pm`,
+/// `tomorrow at 9am`, `friday at 10:00`, `:30`); a bare clock time like `17:30`
+/// means `at 17:30`. Times use the offset of `now`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let due = parse_future_time("17:30", now)?; // the next 17:30
+/// let due = parse_future_time("tomorrow at 9am", now)?;
+/// ```
+///
+/// # Errors
+///
+/// Returns `ParseError` if the input isn't exactly one absolute time, or it has
+/// already passed (`today at 9:00` in the afternoon).
+pub fn parse_future_time(input: &str, now: OffsetDateTime) -> Result<OffsetDateTime, ParseError> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let invalid = || {
+        ParseError(format!(
+            "Invalid time '{}' (use a clock time like 17:30, 5pm or tomorrow at 9am)",
+            input.trim()
+        ))
+    };
+
+    let target = match find_absolute_time(&words, now, true)? {
+        (Some(target), remaining) if remaining.is_empty() => target,
+        (None, _) => match parse_clock_time(&words) {
+            Some((time, consumed)) if consumed == words.len() => next_occurrence(now, time),
+            _ => return Err(invalid()),
+        },
+        _ => return Err(invalid()),
+    };
+    Ok(target)
+}
+
+/// Parses a daily schedule like `every day at 12:30 lunch`.
+///
+/// The phrase can appear anywhere in the input; the remaining words are the
+/// message. The time uses the same forms as `at <time>` and the offset of
+/// `options.now`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (first, msg) = parse_daily_schedule("every day at 12:30 lunch", &options)?.unwrap();
+/// assert_eq!(msg, "lunch"); // `first` is the time until the next 12:30
+/// ```
+///
+/// # Returns
+///
+/// Returns `Ok(Some((seconds, message)))` with the seconds until the next
+/// occurrence of the time, or `Ok(None)` if the input has no `every day at` phrase.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the phrase isn't followed by a clock time, or if no
+/// message is left and no default message is configured.
+pub fn parse_daily_schedule(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Option<(u64, String)>, ParseError> {
+    // With an exact duration, "every day at" is just part of the message
+    if options.seconds.is_some() {
+        return Ok(None);
+    }
+
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let Some(start) = words.windows(3).position(|w| {
+        w[0].eq_ignore_ascii_case("every")
+            && w[1].eq_ignore_ascii_case("day")
+            && w[2].eq_ignore_ascii_case("at")
+    }) else {
+        return Ok(None);
+    };
+
+    let Some((time, consumed)) = parse_clock_time(&words[start + 3..]) else {
+        return Err(ParseError(
+            "Expected a time after 'every day at' (e.g. 12:30 or 9am)".to_string(),
+        ));
+    };
+
+    let now = options.now.unwrap_or_else(OffsetDateTime::now_utc);
+    let seconds = (next_occurrence(now, time) - now).whole_seconds().max(1) as u64;
+
+    let message = words[..start]
+        .iter()
+        .chain(&words[start + 3 + consumed..])
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let message = match options.default_message {
+        _ if !message.is_empty() => message,
+        Some(default) => default.to_string(),
+        None => return Err(ParseError("No message found in input".to_string())),
+    };
+
+    Ok(Some((seconds, message)))
+}
+
+/// Takes the whole input as the message of a timer with an exact duration.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the duration is zero, or the input is empty and no
+/// default message is configured.
+fn literal_message_input(
+    input: &str,
+    seconds: u64,
+    options: &ParseOptions,
+) -> Result<(u64, String), ParseError> {
+    if seconds == 0 {
+        return Err(ParseError("Duration must be at least 1 second".to_string()));
+    }
+
+    let message = input.split_whitespace().collect::<Vec<_>>().join(" ");
+    match (message.is_empty(), options.default_message) {
+        (false, _) => Ok((seconds, message)),
+        (true, Some(default)) => Ok((seconds, default.to_string())),
+        (true, None) => Err(ParseError("No message found in input".to_string())),
+    }
+}
+
+/// Parses user input like [`parse_input`], with relaxed rules from `options`.
+///
+/// When `options.default_message` is set, input that only contains a duration
+/// (such as `5m` or `1:30`) uses the default message instead of failing. When
+/// `options.seconds` is set, the input is taken literally as the message, and
+/// `options.lenient` also accepts units written before their number (`m5`).
+///
+/// # Examples
+///
+/// ```
+/// # use breakrs::parser::{parse_input_with, ParseOptions};
+/// let options = ParseOptions { default_message: Some("Break"), ..Default::default() };
+/// let (duration, msg) = parse_input_with("5m", &options).unwrap();
+/// assert_eq!(duration, 300);
+/// assert_eq!(msg, "Break");
+/// ```
+///
+/// # Errors
+///
+/// Returns `ParseError` under the same conditions as [`parse_input`], except that a
+/// missing message is only an error when no default message is configured.
+pub fn parse_input_with(input: &str, options: &ParseOptions) -> Result<(u64, String), ParseError> {
+    parse_input_parts(input, options).map(|parsed| (parsed.seconds, parsed.message))
+}
+
+/// Returns whether parsing `input` depends on the local time in
+/// [`ParseOptions::now`]: it has an absolute time (`at 5pm`, `tomorrow 9am`), an
+/// `every day at` schedule, or a `{date}`/`{time}` placeholder.
+///
+/// Lets callers skip looking up the local time for input like `5m tea`.
+pub fn uses_local_time(input: &str) -> bool {
     let words: Vec<&str> = input.split_whitespace().collect();
-    let mut colon_duration = 0u64;
+    input.contains("{date}")
+        || input.contains("{time}")
+        || words.windows(3).any(|w| {
+            w[0].eq_ignore_ascii_case("every")
+                && w[1].eq_ignore_ascii_case("day")
+                && w[2].eq_ignore_ascii_case("at")
+        })
+        || !matches!(
+            find_absolute_time(&words, OffsetDateTime::now_utc(), true),
+            Ok((None, _))
+        )
+}
+
+/// The error for input with both an absolute time (`at 3pm`) and a relative
+/// duration (`10m`), which could mean either.
+fn mixed_absolute_time_error() -> ParseError {
+    ParseError(
+        "An absolute time (like \"at 3pm\") can't be combined with a duration (like \"10m\"); use one or the other"
+            .to_string(),
+    )
+}
+
+/// Adds a `number unit` duration to `parts`: it joins the previous part when it
+/// directly follows a larger unit (`1h 30m`), and starts a new part otherwise.
+fn push_duration_part(
+    parts: &mut Vec<u64>,
+    previous_unit: &mut Option<u64>,
+    seconds: u64,
+    unit_seconds: u64,
+) {
+    match (parts.last_mut(), *previous_unit) {
+        (Some(last), Some(previous)) if unit_seconds < previous => *last += seconds,
+        _ => parts.push(seconds),
+    }
+    *previous_unit = Some(unit_seconds);
+}
+
+/// Parses user input like [`parse_input_with`], also returning the separately
+/// written durations that were added up, so callers can tell `1h 30m meeting`
+/// (one duration) from `5m 3h meeting` (two, which may not be what was meant).
+///
+/// # Examples
+///
+/// ```ignore
+/// let parsed = parse_input_parts("5m 3h meeting", &ParseOptions::default())?;
+/// assert_eq!(parsed.seconds, 11100);
+/// assert_eq!(parsed.parts, vec![300, 10800]);
+/// ```
+///
+/// # Errors
+///
+/// Returns `ParseError` under the same conditions as [`parse_input_with`].
+pub fn parse_input_parts(input: &str, options: &ParseOptions) -> Result<ParsedInput, ParseError> {
+    parse_input_parts_reading(input, options, true)
+}
+
+/// Parses input for [`parse_input_parts`]; `bare_times` reads a bare `3pm` as the
+/// due time.
+///
+/// A bare time next to a relative duration (`5m buy 3pm tickets`) is message
+/// text, so on finding both the input is parsed again with `bare_times` off.
+fn parse_input_parts_reading(
+    input: &str,
+    options: &ParseOptions,
+    bare_times: bool,
+) -> Result<ParsedInput, ParseError> {
+    if let Some(seconds) = options.seconds {
+        return literal_message_input(input, seconds, options).map(|(seconds, message)| {
+            ParsedInput {
+                seconds,
+                message,
+                parts: vec![seconds],
+            }
+        });
+    }
+
+    // Absolute times ("at 5pm", "tomorrow at 9am") are resolved first, so their
+    // `HH:MM` part isn't mistaken for a colon duration
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let now = options.now.unwrap_or_else(OffsetDateTime::now_utc);
+    let (absolute_duration, words) = extract_absolute_time(&words, now, bare_times)?;
+    let mixed = || {
+        if bare_times {
+            parse_input_parts_reading(input, options, false)
+        } else {
+            Err(mixed_absolute_time_error())
+        }
+    };
+
+    // Then scan for colon-formatted times
+    let mut colon_duration = 0;
     let mut remaining_input = Vec::new();
+    let mut parts = Vec::new();
+    if absolute_duration > 0 {
+        parts.push(absolute_duration);
+    }
 
     for word in words {
         if is_colon_time(word) {
-            colon_duration += parse_colon_time(word)?;
+            let seconds = parse_colon_time(word)?;
+            colon_duration += seconds;
+            parts.push(seconds);
+        } else if let Some((units, colon_time)) = split_unit_prefixed_colon_time(word) {
+            // `1h5:30` is `1h` plus `5:30`
+            let seconds = parse_duration(units)? + parse_colon_time(colon_time)?;
+            colon_duration += seconds;
+            parts.push(seconds);
+        } else if let Some(colon_time) = colon_time_with_unit_suffix(word) {
+            return Err(ParseError(format!(
+                "A unit can't follow a colon time: '{}' (use '{}' or spell out the units like 5m30s)",
+                word, colon_time
+            )));
         } else {
             remaining_input.push(word);
         }
     }
 
-    // If we only had colon time and no other input, that's an error (no message)
-    if remaining_input.is_empty() && colon_duration > 0 {
-        return Err(ParseError("No message found in input".to_string()));
+    if absolute_duration > 0 && colon_duration > 0 {
+        return mixed();
+    }
+
+    // If we only had colon or absolute time and no other input, there is no message
+    if remaining_input.is_empty() && colon_duration + absolute_duration > 0 {
+        return match options.default_message {
+            Some(default) => Ok(ParsedInput {
+                seconds: colon_duration + absolute_duration,
+                message: default.to_string(),
+                parts,
+            }),
+            None => Err(ParseError("No message found in input".to_string())),
+        };
     }
 
     // Parse the remaining input for standard duration formats
     let remaining_str = remaining_input.join(" ");
-    let tokens = tokenize(&remaining_str)?;
+    let tokens = tokenize(&remaining_str, options.decimal_comma)?;
 
-    // Allow empty tokens if we got duration from colon format
-    if tokens.is_empty() && colon_duration == 0 {
+    // Allow empty tokens if we got duration from colon format or an absolute time
+    if tokens.is_empty() && colon_duration + absolute_duration == 0 {
         return Err(ParseError("Empty input".to_string()));
     }
 
     let mut total_seconds = colon_duration; // Start with colon duration
     let mut message_parts = Vec::new();
     let mut i = 0;
+    // Whether the last token(s) consumed were a duration, so a following
+    // `+`/`-` is arithmetic (`5m + 30s`) rather than message text
+    let mut after_duration = false;
+    // Unit of the last duration, while the current part can still continue
+    let mut previous_unit = None;
 
     while i < tokens.len() {
         match &tokens[i] {
@@ -459,38 +1446,131 @@ pub fn parse_input(input: &str) -> Result<(u64, String), ParseError> {
                 {
                     // Check if this is a valid time unit
                     if let Ok(multiplier) = parse_unit(unit) {
-                        total_seconds += num * multiplier;
+                        let seconds = unit_seconds(*num, multiplier)?;
+                        total_seconds = add_seconds(total_seconds, seconds)?;
+                        push_duration_part(&mut parts, &mut previous_unit, seconds, multiplier);
+                        after_duration = true;
                         i += 2;
                         continue;
                     }
                     // Not a time unit, treat as message text
                     message_parts.push(num.to_string());
                     message_parts.push(unit.clone());
+                    after_duration = false;
+                    previous_unit = None;
                     i += 2;
                     continue;
                 }
                 // No unit following, treat number as message text
                 message_parts.push(num.to_string());
+                after_duration = false;
+                previous_unit = None;
+                i += 1;
+            }
+            Token::Decimal(value, text) => {
+                // Decimals only count as a duration when a unit follows (`1.5h`),
+                // otherwise they stay in the message as written (`version 2.5`, `1,000`)
+                if i + 1 < tokens.len()
+                    && let Token::Unit(unit) = &tokens[i + 1]
+                {
+                    if let Ok(multiplier) = parse_unit(unit) {
+                        let seconds = (value * multiplier as f64).round() as u64;
+                        total_seconds = add_seconds(total_seconds, seconds)?;
+                        push_duration_part(&mut parts, &mut previous_unit, seconds, multiplier);
+                        after_duration = true;
+                        i += 2;
+                        continue;
+                    }
+                    message_parts.push(text.clone());
+                    message_parts.push(unit.clone());
+                    after_duration = false;
+                    previous_unit = None;
+                    i += 2;
+                    continue;
+                }
+                message_parts.push(text.clone());
+                after_duration = false;
+                previous_unit = None;
                 i += 1;
             }
             Token::Unit(unit) => {
+                // Arithmetic between durations: "5m + 30s", "5m - 10s" (clamped at zero)
+                if after_duration
+                    && (unit == "+" || unit == "-")
+                    && let Some((seconds, consumed)) = parse_duration_component(&tokens[i + 1..])?
+                {
+                    total_seconds = if unit == "+" {
+                        add_seconds(total_seconds, seconds)?
+                    } else {
+                        total_seconds.saturating_sub(seconds)
+                    };
+                    // Arithmetic belongs to the duration before it
+                    if let Some(last) = parts.last_mut() {
+                        *last = if unit == "+" {
+                            *last + seconds
+                        } else {
+                            last.saturating_sub(seconds)
+                        };
+                    }
+                    previous_unit = None;
+                    i += 1 + consumed;
+                    continue;
+                }
+                // Unit written before its number in lenient mode: "m5" is 5m
+                if options.lenient
+                    && let Some(Token::Number(num)) = tokens.get(i + 1)
+                    && let Ok(multiplier) = parse_unit(unit)
+                {
+                    let seconds = unit_seconds(*num, multiplier)?;
+                    total_seconds = add_seconds(total_seconds, seconds)?;
+                    push_duration_part(&mut parts, &mut previous_unit, seconds, multiplier);
+                    after_duration = true;
+                    i += 2;
+                    continue;
+                }
+                // Approximate amounts: "a couple minutes", "a few seconds"
+                if let Some((seconds, consumed)) = parse_approximate_duration(&tokens[i..]) {
+                    total_seconds = add_seconds(total_seconds, seconds)?;
+                    parts.push(seconds);
+                    previous_unit = None;
+                    after_duration = true;
+                    i += consumed;
+                    continue;
+                }
                 // Standalone unit, treat as message text
                 message_parts.push(unit.clone());
+                after_duration = false;
+                previous_unit = None;
                 i += 1;
             }
         }
     }
 
+    // An absolute time is the whole duration; a relative one on top is ambiguous
+    if absolute_duration > 0 {
+        if total_seconds > 0 {
+            return mixed();
+        }
+        total_seconds = absolute_duration;
+    }
+
     if total_seconds == 0 {
         return Err(ParseError("No valid duration found in input".to_string()));
     }
 
-    let message = message_parts.join(" ");
+    let mut message = message_parts.join(" ");
     if message.is_empty() {
-        return Err(ParseError("No message found in input".to_string()));
+        match options.default_message {
+            Some(default) => message = default.to_string(),
+            None => return Err(ParseError("No message found in input".to_string())),
+        }
     }
 
-    Ok((total_seconds, message))
+    Ok(ParsedInput {
+        seconds: total_seconds,
+        message,
+        parts,
+    })
 }
 
 #[cfg(test)]
@@ -623,6 +1703,33 @@ mod tests {
         assert_eq!(message, "call 123 people");
     }
 
+    #[test]
+    fn test_unicode_messages_round_trip() {
+        for message in [
+            "☕ coffee ☕",
+            "café résumé",
+            "cafe\u{301} and re\u{301}sume\u{301}",
+            "👩‍💻 ship it 👍🏽",
+            "🇳🇿 call 👨‍👩‍👧‍👦",
+            "早上好 stretch",
+        ] {
+            for input in [format!("5m {}", message), format!("{} 5m", message)] {
+                let (duration, parsed) = parse_input(&input).unwrap();
+                assert_eq!(duration, 300, "{}", input);
+                assert_eq!(parsed, message, "{}", input);
+            }
+        }
+
+        // A keycap after a digit stays whole and isn't read as a number
+        let (duration, message) = parse_input("5m press 1️⃣ then 2⃣").unwrap();
+        assert_eq!(duration, 300);
+        assert_eq!(message, "press 1️⃣ then 2⃣");
+        assert!(matches!(
+            tokenize("1️⃣", false).unwrap().as_slice(),
+            [Token::Unit(keycap)] if keycap == "1\u{fe0f}\u{20e3}"
+        ));
+    }
+
     #[test]
     fn test_parse_input_complex() {
         let (duration, message) = parse_input("1h 30m break for lunch at 12").unwrap();
@@ -630,6 +1737,53 @@ mod tests {
         assert_eq!(message, "break for lunch at 12");
     }
 
+    #[test]
+    fn test_duration_arithmetic() {
+        let (duration, message) = parse_input("5m + 30s coffee").unwrap();
+        assert_eq!(duration, 330);
+        assert_eq!(message, "coffee");
+
+        let (duration, message) = parse_input("5m - 10s coffee").unwrap();
+        assert_eq!(duration, 290);
+        assert_eq!(message, "coffee");
+
+        let (duration, _) = parse_input("5m + 30s - 10s coffee").unwrap();
+        assert_eq!(duration, 320);
+        let (duration, _) = parse_input("5m -10s coffee").unwrap();
+        assert_eq!(duration, 290);
+
+        // Subtraction clamps at zero before later additions
+        let (duration, _) = parse_input("1m - 5m + 30s tea").unwrap();
+        assert_eq!(duration, 30);
+        assert!(parse_input("1m - 5m tea").is_err());
+
+        // `+`/`-` not between durations stays in the message
+        let (duration, message) = parse_input("5m pros + cons - review").unwrap();
+        assert_eq!(duration, 300);
+        assert_eq!(message, "pros + cons - review");
+        let (duration, message) = parse_input("5m - check mail").unwrap();
+        assert_eq!(duration, 300);
+        assert_eq!(message, "- check mail");
+        let (duration, message) = parse_input("call + 5m").unwrap();
+        assert_eq!(duration, 300);
+        assert_eq!(message, "call +");
+
+        // Durations too large to count in seconds are errors, not overflows
+        for input in [
+            "5m + 99999999999999999h x",
+            "18446744073709551615s + 1s x",
+            "99999999999999999h x",
+            "18446744073709551615s 1s x",
+        ] {
+            assert_eq!(
+                parse_input(input).unwrap_err().to_string(),
+                "Parse error: Duration is too long",
+                "{}",
+                input
+            );
+        }
+    }
+
     // Error cases
     #[test]
     fn test_parse_input_errors() {
@@ -698,6 +1852,699 @@ mod tests {
         assert!(parse_input("5:3a message").is_err());
     }
 
+    #[test]
+    fn test_colon_format_with_units() {
+        // A unit prefix before a colon time adds up: 1h + 5:30
+        let (duration, message) = parse_input("1h5:30 stretch").unwrap();
+        assert_eq!(duration, 3600 + 330);
+        assert_eq!(message, "stretch");
+
+        let (duration, _) = parse_input("2m1:00 stretch").unwrap();
+        assert_eq!(duration, 120 + 60);
+
+        // A unit after a colon time is ambiguous, so it is rejected
+        let err = parse_input("5:30m stretch").unwrap_err();
+        assert!(err.0.contains("5:30m"));
+        assert!(parse_input("stretch 1:00:00h").is_err());
+
+        // am/pm is not a unit, so this stays message text
+        let (duration, _) = parse_input("5m call 10:30am").unwrap();
+        assert_eq!(duration, 300);
+    }
+
+    // Decimal duration tests
+    #[test]
+    fn test_decimal_durations() {
+        let (duration, message) = parse_input("1.5h long break").unwrap();
+        assert_eq!(duration, 5400);
+        assert_eq!(message, "long break");
+
+        let (duration, _) = parse_input("2.5 minutes tea").unwrap();
+        assert_eq!(duration, 150);
+
+        let (duration, _) = parse_input("0.5hours walk").unwrap();
+        assert_eq!(duration, 1800);
+    }
+
+    #[test]
+    fn test_decimal_comma_separator() {
+        let options = ParseOptions {
+            decimal_comma: true,
+            ..Default::default()
+        };
+        let (duration, message) = parse_input_with("1,5h long break", &options).unwrap();
+        assert_eq!(duration, 5400);
+        assert_eq!(message, "long break");
+
+        let (duration, _) = parse_input_with("stretch 2,5m", &options).unwrap();
+        assert_eq!(duration, 150);
+
+        // Off by default: a thousands separator stays in the message
+        assert_eq!(
+            parse_input("5m call 1,000s").unwrap(),
+            (300, "call 1,000s".to_string())
+        );
+        assert!(parse_input("1,5h long break").is_err());
+    }
+
+    #[test]
+    fn test_commas_and_decimals_in_message() {
+        let (duration, message) = parse_input("5m call bob, then alice").unwrap();
+        assert_eq!(duration, 300);
+        assert_eq!(message, "call bob, then alice");
+
+        let (_, message) = parse_input("5m call 1,000 people").unwrap();
+        assert_eq!(message, "call 1,000 people");
+
+        let (_, message) = parse_input("5m upgrade to 2.5").unwrap();
+        assert_eq!(message, "upgrade to 2.5");
+    }
+
+    // Default message tests
+    #[test]
+    fn test_approximate_numbers() {
+        assert_eq!(
+            parse_input("a couple minutes coffee").unwrap(),
+            (120, "coffee".to_string())
+        );
+        assert_eq!(
+            parse_input("a few seconds wait").unwrap(),
+            (3, "wait".to_string())
+        );
+        assert_eq!(
+            parse_input("stretch in a couple of hours").unwrap(),
+            (7200, "stretch in".to_string())
+        );
+        assert_eq!(
+            parse_input("5m few good men").unwrap(),
+            (300, "few good men".to_string())
+        );
+        assert_eq!(
+            parse_input("5m call a couple friends").unwrap(),
+            (300, "call a couple friends".to_string())
+        );
+    }
+
+    #[test]
+    fn test_uses_local_time() {
+        assert!(!uses_local_time("5m tea"));
+        assert!(!uses_local_time("1h 30m meeting notes"));
+        assert!(uses_local_time("call mom at 5pm"));
+        assert!(uses_local_time("standup tomorrow at 9:30"));
+        assert!(uses_local_time("buy 3pm tickets"));
+        assert!(uses_local_time("stretch every day at noon"));
+        assert!(uses_local_time("5m notes for {date}"));
+    }
+
+    fn options_at(now: OffsetDateTime) -> ParseOptions<'static> {
+        ParseOptions {
+            now: Some(now),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_today_and_tomorrow() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 UTC);
+        let options = options_at(now);
+
+        assert_eq!(
+            parse_input_with("tomorrow at 9am standup", &options).unwrap(),
+            (23 * 3600, "standup".to_string())
+        );
+        assert_eq!(
+            parse_input_with("today at 5pm leave", &options).unwrap(),
+            (7 * 3600, "leave".to_string())
+        );
+        // tomorrow rolls over even when the time is still ahead today
+        assert_eq!(
+            parse_input_with("Tomorrow at 11:30 am review", &options).unwrap(),
+            (25 * 3600 + 30 * 60, "review".to_string())
+        );
+        assert!(parse_input_with("today at 9am standup", &options).is_err());
+    }
+
+    #[test]
+    fn test_weekday_anchors() {
+        // A Wednesday
+        let wednesday = options_at(time::macros::datetime!(2025-01-22 10:00:00 UTC));
+        assert_eq!(
+            parse_input_with("monday at 9am review", &wednesday).unwrap(),
+            (4 * 24 * 3600 + 23 * 3600, "review".to_string())
+        );
+        assert_eq!(
+            parse_input_with("plan Fri at 9:30", &wednesday).unwrap(),
+            (47 * 3600 + 30 * 60, "plan".to_string())
+        );
+        // "next" means the following calendar week
+        assert_eq!(
+            parse_input_with("next friday at 9am plan", &wednesday).unwrap(),
+            (8 * 24 * 3600 + 23 * 3600, "plan".to_string())
+        );
+        assert_eq!(
+            parse_input_with("next monday at 9am review", &wednesday).unwrap(),
+            (4 * 24 * 3600 + 23 * 3600, "review".to_string())
+        );
+
+        // A Monday: today if the time is still ahead, otherwise next week
+        let monday = options_at(time::macros::datetime!(2025-01-27 10:00:00 UTC));
+        assert_eq!(
+            parse_input_with("monday at 11am review", &monday).unwrap(),
+            (3600, "review".to_string())
+        );
+        assert_eq!(
+            parse_input_with("monday at 9am review", &monday).unwrap(),
+            (6 * 24 * 3600 + 23 * 3600, "review".to_string())
+        );
+        assert_eq!(
+            parse_input_with("next monday at 11am review", &monday).unwrap(),
+            (7 * 24 * 3600 + 3600, "review".to_string())
+        );
+
+        // Weekdays without "at <time>" stay in the message
+        assert_eq!(
+            parse_input_with("5m call about next monday", &monday).unwrap(),
+            (300, "call about next monday".to_string())
+        );
+
+        // Short forms that are also words never anchor to a weekday
+        assert_eq!(
+            parse_input_with("watch the sun at 7pm", &wednesday).unwrap(),
+            (9 * 3600, "watch the sun".to_string())
+        );
+        assert_eq!(
+            parse_input_with("I sat at 3pm desk", &wednesday).unwrap(),
+            (5 * 3600, "i sat desk".to_string())
+        );
+        assert_eq!(
+            parse_input_with("they wed at 3pm", &wednesday).unwrap(),
+            (5 * 3600, "they wed".to_string())
+        );
+        assert_eq!(
+            parse_input_with("saturday at 3pm game", &wednesday).unwrap(),
+            (3 * 24 * 3600 + 5 * 3600, "game".to_string())
+        );
+    }
+
+    #[test]
+    fn test_at_absolute_time() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 +2);
+        let options = options_at(now);
+
+        // Next occurrence, in the offset of `now`
+        assert_eq!(
+            parse_input_with("at 17:30 gym", &options).unwrap(),
+            (7 * 3600 + 30 * 60, "gym".to_string())
+        );
+        assert_eq!(
+            parse_input_with("stand up at 9am", &options).unwrap(),
+            (23 * 3600, "stand up".to_string())
+        );
+        assert_eq!(
+            parse_input_with("at 12am midnight snack", &options).unwrap(),
+            (14 * 3600, "midnight snack".to_string())
+        );
+
+        // "at" without a clock time stays in the message
+        assert_eq!(
+            parse_input_with("5m meet at cafe", &options).unwrap(),
+            (300, "meet at cafe".to_string())
+        );
+        assert_eq!(
+            parse_input_with("5m lunch at 12", &options).unwrap(),
+            (300, "lunch at 12".to_string())
+        );
+        assert!(parse_input_with("at 5pm at 6pm x", &options).is_err());
+
+        // An absolute time replaces a duration rather than adding to it, so
+        // giving both is ambiguous
+        for input in [
+            "10m leave for meeting at 3pm",
+            "1h 30m meeting at 12:30",
+            "5m tea tomorrow at 9am",
+            "1:30 call today at 17:00",
+        ] {
+            let err = parse_input_with(input, &options).unwrap_err();
+            assert!(err.to_string().contains("can't be combined"), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_bare_meridiem_time() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 +2);
+        let options = options_at(now);
+
+        assert_eq!(
+            parse_input_with("3pm meeting", &options).unwrap(),
+            (5 * 3600, "meeting".to_string())
+        );
+        assert_eq!(
+            parse_input_with("call mom 9AM", &options).unwrap(),
+            (23 * 3600, "call mom".to_string())
+        );
+        // 12am is midnight, 12pm is noon
+        assert_eq!(
+            parse_input_with("12am sleep", &options).unwrap(),
+            (14 * 3600, "sleep".to_string())
+        );
+        assert_eq!(
+            parse_input_with("12pm lunch", &options).unwrap(),
+            (2 * 3600, "lunch".to_string())
+        );
+
+        // Not clock times: out-of-range hours and words that merely end in am/pm
+        assert!(parse_bare_meridiem_time("13pm").is_none());
+        assert!(parse_bare_meridiem_time("0am").is_none());
+        assert!(parse_bare_meridiem_time("130pm").is_none());
+        assert_eq!(
+            parse_input_with("5m spam", &options).unwrap(),
+            (300, "spam".to_string())
+        );
+        assert!(parse_input_with("3pm 4pm x", &options).is_err());
+
+        // Next to a relative duration, a bare time is part of the message
+        assert_eq!(
+            parse_input_with("5m buy 3pm tickets", &options).unwrap(),
+            (300, "buy 3pm tickets".to_string())
+        );
+        assert_eq!(
+            parse_input_with("10pm party 1:30", &options).unwrap(),
+            (90, "10pm party".to_string())
+        );
+        // ...but `at` still makes it the due time, which can't be combined
+        assert!(parse_input_with("5m buy tickets at 3pm", &options).is_err());
+    }
+
+    #[test]
+    fn test_clock_idioms() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 +2);
+        let options = options_at(now);
+
+        // Without am/pm the next occurrence wins: 3:15 has passed, so 15:15
+        assert_eq!(
+            parse_input_with("quarter past 3 meeting", &options).unwrap(),
+            (5 * 3600 + 15 * 60, "meeting".to_string())
+        );
+        assert_eq!(
+            parse_input_with("call half past two", &options).unwrap(),
+            (4 * 3600 + 30 * 60, "call".to_string())
+        );
+        assert_eq!(
+            parse_input_with("quarter to 4 standup", &options).unwrap(),
+            (5 * 3600 + 45 * 60, "standup".to_string())
+        );
+        // Still ahead this morning
+        assert_eq!(
+            parse_input_with("a quarter past 11 tea", &options).unwrap(),
+            (3600 + 15 * 60, "tea".to_string())
+        );
+        // Quarter to 12 can be 11:45 today, before 23:45
+        assert_eq!(
+            parse_input_with("quarter to 12 lunch", &options).unwrap(),
+            (3600 + 45 * 60, "lunch".to_string())
+        );
+
+        // am/pm fixes the reading
+        assert_eq!(
+            parse_input_with("quarter past 3am sleep", &options).unwrap(),
+            (17 * 3600 + 15 * 60, "sleep".to_string())
+        );
+        assert_eq!(
+            parse_input_with("quarter to 4 pm tea", &options).unwrap(),
+            (5 * 3600 + 45 * 60, "tea".to_string())
+        );
+
+        // Not idioms: no hour, or an hour out of range
+        assert_eq!(
+            parse_input_with("5m half past noon", &options).unwrap(),
+            (300, "half past noon".to_string())
+        );
+        assert!(parse_clock_idiom(&["quarter", "past", "13"]).is_none());
+        assert!(parse_clock_idiom(&["quarter", "past"]).is_none());
+    }
+
+    #[test]
+    fn test_lenient_unit_first() {
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_input_with("m5 break", &lenient).unwrap(),
+            (300, "break".to_string())
+        );
+        assert_eq!(
+            parse_input_with("tea h1m30", &lenient).unwrap(),
+            (5400, "tea".to_string())
+        );
+        // Regular durations and words that aren't units are unaffected
+        assert_eq!(
+            parse_input_with("5m take 5", &lenient).unwrap(),
+            (300, "take 5".to_string())
+        );
+
+        // Without the flag, a unit before a number stays in the message
+        let strict = ParseOptions::default();
+        assert!(parse_input_with("m5 break", &strict).is_err());
+        assert_eq!(
+            parse_input_with("1m m5 break", &strict).unwrap(),
+            (60, "m 5 break".to_string())
+        );
+    }
+
+    #[test]
+    fn test_duration_parts() {
+        let options = ParseOptions::default();
+        let parts = |input: &str| parse_input_parts(input, &options).unwrap().parts;
+
+        // Units counting down in one run are a single duration
+        assert_eq!(parts("1h 30m meeting"), vec![5400]);
+        assert_eq!(parts("2h15m30s meeting"), vec![8130]);
+        assert_eq!(parts("1 hour 30 minutes break"), vec![5400]);
+        assert_eq!(parts("5m + 30s - 10s coffee"), vec![320]);
+
+        // Out of order, repeated, or split by message text
+        let parsed = parse_input_parts("5m 3h meeting", &options).unwrap();
+        assert_eq!(parsed.seconds, 11100);
+        assert_eq!(parsed.message, "meeting");
+        assert_eq!(parsed.parts, vec![300, 10800]);
+        assert_eq!(parts("10m 10m tea"), vec![600, 600]);
+        assert_eq!(parts("5m meeting 3h"), vec![300, 10800]);
+        assert_eq!(parts("1:30 5m tea"), vec![90, 300]);
+
+        // The parts always add up to the total
+        for input in [
+            "15mins 1h 20s take a break",
+            "1h5:30 tea",
+            "a couple minutes 5s x",
+        ] {
+            let parsed = parse_input_parts(input, &options).unwrap();
+            assert_eq!(
+                parsed.parts.iter().sum::<u64>(),
+                parsed.seconds,
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_oclock_times() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 +2);
+        let options = options_at(now);
+
+        // Without am/pm the next occurrence wins: 3:00 has passed, so 15:00
+        assert_eq!(
+            parse_input_with("3 o'clock meeting", &options).unwrap(),
+            (5 * 3600, "meeting".to_string())
+        );
+        assert_eq!(
+            parse_input_with("tea at 11 oclock", &options).unwrap(),
+            (3600, "tea".to_string())
+        );
+        assert_eq!(
+            parse_input_with("call mum twelve o clock", &options).unwrap(),
+            (2 * 3600, "call mum".to_string())
+        );
+        assert_eq!(
+            parse_input_with("at quarter past 3 meeting", &options).unwrap(),
+            (5 * 3600 + 15 * 60, "meeting".to_string())
+        );
+
+        // am/pm fixes the reading
+        assert_eq!(
+            parse_input_with("9 o'clock am standup", &options).unwrap(),
+            (23 * 3600, "standup".to_string())
+        );
+        assert_eq!(
+            parse_input_with("9 O’CLOCK PM news", &options).unwrap(),
+            (11 * 3600, "news".to_string())
+        );
+
+        // The hour needs o'clock after it, and must be on a 12-hour clock
+        assert_eq!(
+            parse_input_with("5m call 3 people", &options).unwrap(),
+            (300, "call 3 people".to_string())
+        );
+        assert!(parse_oclock(&["13", "o'clock"]).is_none());
+        assert!(parse_oclock(&["o'clock"]).is_none());
+        assert!(parse_oclock(&["3", "o"]).is_none());
+    }
+
+    #[test]
+    fn test_expand_time_placeholders() {
+        let at = time::macros::datetime!(2025-01-04 09:05:00 +2);
+
+        assert_eq!(
+            expand_time_placeholders("notes for {date} at {time}", "", at),
+            "notes for 2025-01-04 at 09:05"
+        );
+        // Other prefixes are left alone, so fire-time placeholders survive creation
+        assert_eq!(
+            expand_time_placeholders("{date} / {fire_date}", "", at),
+            "2025-01-04 / {fire_date}"
+        );
+        assert_eq!(
+            expand_time_placeholders("{date} / {fire_date}", "fire_", at),
+            "{date} / 2025-01-04"
+        );
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("never").unwrap(), 0);
+        assert_eq!(parse_timeout("NEVER").unwrap(), 0);
+        assert_eq!(parse_timeout("10s").unwrap(), 10);
+        assert_eq!(parse_timeout("2m").unwrap(), 120);
+        assert!(parse_timeout("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_past_time() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 +2);
+
+        assert_eq!(
+            parse_past_time("9:00", now).unwrap(),
+            time::macros::datetime!(2025-01-24 09:00:00 +2)
+        );
+        assert_eq!(
+            parse_past_time("at 8:30 am", now).unwrap(),
+            time::macros::datetime!(2025-01-24 08:30:00 +2)
+        );
+        // A clock time still ahead today means yesterday
+        assert_eq!(
+            parse_past_time("17:00", now).unwrap(),
+            time::macros::datetime!(2025-01-23 17:00:00 +2)
+        );
+        // Durations count back from now
+        assert_eq!(
+            parse_past_time("2h", now).unwrap(),
+            now - Duration::hours(2)
+        );
+        assert_eq!(
+            parse_past_time("90 minutes", now).unwrap(),
+            now - Duration::minutes(90)
+        );
+
+        assert!(parse_past_time("this morning", now).is_err());
+        assert!(parse_past_time("9:00 tea", now).is_err());
+        assert!(parse_past_time("1000000000d", now).is_err());
+    }
+
+    #[test]
+    fn test_parse_future_time() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 +2);
+
+        assert_eq!(
+            parse_future_time("17:30", now).unwrap(),
+            time::macros::datetime!(2025-01-24 17:30:00 +2)
+        );
+        assert_eq!(
+            parse_future_time("at 5pm", now).unwrap(),
+            time::macros::datetime!(2025-01-24 17:00:00 +2)
+        );
+        // A clock time already passed today means tomorrow
+        assert_eq!(
+            parse_future_time("9:15", now).unwrap(),
+            time::macros::datetime!(2025-01-25 09:15:00 +2)
+        );
+        assert_eq!(
+            parse_future_time("tomorrow at 8am", now).unwrap(),
+            time::macros::datetime!(2025-01-25 08:00:00 +2)
+        );
+
+        assert!(parse_future_time("today at 9:00", now).is_err());
+        assert!(parse_future_time("2h", now).is_err());
+        assert!(parse_future_time("5pm tea", now).is_err());
+    }
+
+    #[test]
+    fn test_align_to_hour_and_half_hour() {
+        let options = options_at(time::macros::datetime!(2025-01-24 10:17:30 UTC));
+
+        assert_eq!(
+            parse_input_with(":00 top of the hour", &options).unwrap(),
+            (42 * 60 + 30, "top of the hour".to_string())
+        );
+        assert_eq!(
+            parse_input_with("stretch :30", &options).unwrap(),
+            (12 * 60 + 30, "stretch".to_string())
+        );
+
+        // A boundary already passed this hour means the next hour
+        let options = options_at(time::macros::datetime!(2025-01-24 10:45:00 UTC));
+        assert_eq!(
+            parse_input_with(":30 stretch", &options).unwrap(),
+            (45 * 60, "stretch".to_string())
+        );
+
+        // Exactly on the boundary aligns to the next one, never to now
+        let options = options_at(time::macros::datetime!(2025-01-24 23:00:00 UTC));
+        assert_eq!(
+            parse_input_with(":00 midnight", &options).unwrap(),
+            (3600, "midnight".to_string())
+        );
+
+        // Only two-digit minutes below 60, and only one absolute time
+        assert!(parse_input_with(":60 x", &options).is_err());
+        assert!(parse_input_with(":00 at 5pm x", &options).is_err());
+    }
+
+    #[test]
+    fn test_parse_daily_schedule() {
+        let now = time::macros::datetime!(2025-01-24 10:00:00 UTC);
+        let options = options_at(now);
+
+        assert_eq!(
+            parse_daily_schedule("every day at 12:30 lunch", &options).unwrap(),
+            Some((2 * 3600 + 30 * 60, "lunch".to_string()))
+        );
+        // A time already passed today first fires tomorrow
+        assert_eq!(
+            parse_daily_schedule("standup Every Day at 9am", &options).unwrap(),
+            Some((23 * 3600, "standup".to_string()))
+        );
+
+        assert_eq!(
+            parse_daily_schedule("5m every day stuff", &options).unwrap(),
+            None
+        );
+        assert!(parse_daily_schedule("every day at noon lunch", &options).is_err());
+        assert!(parse_daily_schedule("every day at 12:30", &options).is_err());
+
+        let options = ParseOptions {
+            default_message: Some(DEFAULT_MESSAGE),
+            now: Some(now),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_daily_schedule("every day at 12:30", &options).unwrap(),
+            Some((2 * 3600 + 30 * 60, DEFAULT_MESSAGE.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_default_message_duration_only() {
+        let options = ParseOptions {
+            default_message: Some(DEFAULT_MESSAGE),
+            ..Default::default()
+        };
+
+        let (duration, message) = parse_input_with("5m", &options).unwrap();
+        assert_eq!(duration, 300);
+        assert_eq!(message, "Break");
+
+        let (duration, message) = parse_input_with("1h 30m", &options).unwrap();
+        assert_eq!(duration, 5400);
+        assert_eq!(message, "Break");
+    }
+
+    #[test]
+    fn test_exact_seconds_bypass_duration_parsing() {
+        let options = ParseOptions {
+            seconds: Some(300),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            parse_input_with("coffee", &options).unwrap(),
+            (300, "coffee".to_string())
+        );
+        // Duration-like words are kept as message text
+        assert_eq!(
+            parse_input_with("5m  call at 3pm", &options).unwrap(),
+            (300, "5m call at 3pm".to_string())
+        );
+        assert_eq!(
+            parse_daily_schedule("every day at 12:30 lunch", &options).unwrap(),
+            None
+        );
+
+        assert!(parse_input_with("", &options).is_err());
+        let with_default = ParseOptions {
+            default_message: Some(DEFAULT_MESSAGE),
+            ..options
+        };
+        assert_eq!(
+            parse_input_with("", &with_default).unwrap(),
+            (300, DEFAULT_MESSAGE.to_string())
+        );
+
+        let zero = ParseOptions {
+            seconds: Some(0),
+            ..Default::default()
+        };
+        assert!(parse_input_with("coffee", &zero).is_err());
+    }
+
+    #[test]
+    fn test_default_message_colon_only() {
+        let options = ParseOptions {
+            default_message: Some("Tea"),
+            ..Default::default()
+        };
+
+        let (duration, message) = parse_input_with("5:30", &options).unwrap();
+        assert_eq!(duration, 330);
+        assert_eq!(message, "Tea");
+    }
+
+    #[test]
+    fn test_default_message_does_not_override_message() {
+        let options = ParseOptions {
+            default_message: Some(DEFAULT_MESSAGE),
+            ..Default::default()
+        };
+
+        let (_, message) = parse_input_with("5m coffee", &options).unwrap();
+        assert_eq!(message, "coffee");
+
+        // Still requires a duration
+        assert!(parse_input_with("just a message", &options).is_err());
+    }
+
+    #[test]
+    fn test_day_units() {
+        let (duration, message) = parse_input("2d vacation over").unwrap();
+        assert_eq!(duration, 2 * 86400);
+        assert_eq!(message, "vacation over");
+
+        let (duration, _) = parse_input("1 day 2h check in").unwrap();
+        assert_eq!(duration, 86400 + 7200);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30d").unwrap(), 30 * 86400);
+        assert_eq!(parse_duration("1h 30m").unwrap(), 5400);
+        assert_eq!(parse_duration("5:30").unwrap(), 330);
+        assert_eq!(parse_duration("two hours").unwrap(), 7200);
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("5m coffee").is_err());
+    }
+
     // Number word parsing tests
     #[test]
     fn test_number_words_basic() {
@@ -0,0 +1,269 @@
+//! Unicode-aware text helpers for displaying and shortening timer messages.
+//!
+//! Messages can hold emoji, combining accents and CJK text, so slicing them by
+//! bytes or `char`s can split a visible character in half or misjudge how wide it
+//! is on screen. These helpers work on grapheme clusters (what a user sees as one
+//! character) and terminal column widths instead.
+//!
+//! The rules cover the text people actually put in timer messages (accents, emoji
+//! with skin tones and ZWJ sequences, flags, keycaps, CJK) without pulling in the
+//! full Unicode tables.
+
+/// Returns true if `ch` never starts a grapheme cluster of its own and instead
+/// attaches to the character before it (combining marks, variation selectors,
+/// emoji modifiers and the zero-width joiner).
+pub fn extends_grapheme(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x0300..=0x036F     // Combining diacritical marks
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x0E31
+            | 0x0E34..=0x0E3A
+            | 0x0E47..=0x0E4E
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200C..=0x200D // Zero-width non-joiner and joiner
+            | 0x20D0..=0x20FF // Combining marks for symbols, including the keycap
+            | 0xFE00..=0xFE0F // Variation selectors
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF // Emoji skin tone modifiers
+            | 0xE0020..=0xE007F // Emoji tag sequences (subdivision flags)
+            | 0xE0100..=0xE01EF
+    )
+}
+
+/// Returns true for the regional indicator letters that pair up into flags.
+fn is_regional_indicator(ch: char) -> bool {
+    matches!(ch as u32, 0x1F1E6..=0x1F1FF)
+}
+
+/// Returns how many terminal columns `ch` takes on its own: 2 for wide East Asian
+/// characters and emoji, 0 for characters that attach to the one before, else 1.
+fn char_width(ch: char) -> usize {
+    if extends_grapheme(ch) {
+        return 0;
+    }
+    let wide = matches!(
+        ch as u32,
+        0x1100..=0x115F
+            | 0x231A..=0x231B
+            | 0x2329..=0x232A
+            | 0x23E9..=0x23EC
+            | 0x23F0
+            | 0x23F3
+            | 0x25FD..=0x25FE
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x267F
+            | 0x2693
+            | 0x26A1
+            | 0x26AA..=0x26AB
+            | 0x26BD..=0x26BE
+            | 0x26C4..=0x26C5
+            | 0x26CE
+            | 0x26D4
+            | 0x26EA
+            | 0x26F2..=0x26F3
+            | 0x26F5
+            | 0x26FA
+            | 0x26FD
+            | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728
+            | 0x274C
+            | 0x274E
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27B0
+            | 0x27BF
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F004
+            | 0x1F0CF
+            | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F200..=0x1F251
+            | 0x1F300..=0x1F64F
+            | 0x1F680..=0x1F6FF
+            | 0x1F7E0..=0x1F7EB
+            | 0x1F90C..=0x1F9FF
+            | 0x1FA70..=0x1FAFF
+            | 0x20000..=0x3FFFD
+    );
+    if wide { 2 } else { 1 }
+}
+
+/// Splits `text` into grapheme clusters, the units a user sees as one character.
+///
+/// A cluster is a base character plus any combining marks, variation selectors
+/// and skin tones after it; characters joined by a zero-width joiner (`👩‍💻`) and
+/// pairs of regional indicators (flags like `🇳🇿`) stay together.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(graphemes("cafe\u{301}!"), vec!["c", "a", "f", "e\u{301}", "!"]);
+/// ```
+pub fn graphemes(text: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    let mut regional_run = 0;
+
+    for (index, ch) in text.char_indices() {
+        let joins = match prev {
+            None => false,
+            Some(prev) => {
+                extends_grapheme(ch)
+                    || prev == '\u{200D}'
+                    || (is_regional_indicator(ch) && regional_run % 2 == 1)
+            }
+        };
+        if !joins && index > 0 {
+            clusters.push(&text[start..index]);
+            start = index;
+        }
+        regional_run = if is_regional_indicator(ch) {
+            regional_run + 1
+        } else {
+            0
+        };
+        prev = Some(ch);
+    }
+    if start < text.len() {
+        clusters.push(&text[start..]);
+    }
+
+    clusters
+}
+
+/// Returns how many terminal columns a grapheme cluster takes.
+fn grapheme_width(cluster: &str) -> usize {
+    let mut chars = cluster.chars();
+    let Some(first) = chars.next() else {
+        return 0;
+    };
+    // Emoji presentation (U+FE0F) and flags always draw two columns wide
+    if cluster.contains('\u{FE0F}') || is_regional_indicator(first) {
+        return 2;
+    }
+    char_width(first)
+}
+
+/// Returns how many terminal columns `text` takes when printed.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(display_width("tea"), 3);
+/// assert_eq!(display_width("☕ tea"), 6);
+/// ```
+pub fn display_width(text: &str) -> usize {
+    graphemes(text).into_iter().map(grapheme_width).sum()
+}
+
+/// Shortens `text` to at most `max_width` terminal columns, ending it with an
+/// ellipsis when anything was cut.
+///
+/// Cuts only fall between grapheme clusters, so emoji and accented letters are
+/// never split; text that already fits is returned unchanged.
+///
+/// # Arguments
+///
+/// * `text` - The text to shorten
+/// * `max_width` - The widest the result may be, ellipsis included
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(truncate_to_width("Stretch your legs", 8), "Stretch…");
+/// assert_eq!(truncate_to_width("☕☕☕", 4), "☕…");
+/// ```
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+
+    let budget = max_width.saturating_sub(1);
+    let mut kept = String::new();
+    let mut width = 0;
+    for cluster in graphemes(text) {
+        let cluster_width = grapheme_width(cluster);
+        if width + cluster_width > budget {
+            break;
+        }
+        kept.push_str(cluster);
+        width += cluster_width;
+    }
+
+    format!("{}…", kept.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphemes_keep_clusters_whole() {
+        assert_eq!(graphemes(""), Vec::<&str>::new());
+        assert_eq!(graphemes("tea"), vec!["t", "e", "a"]);
+        // Combining accent, skin tone, ZWJ sequence, flag pair and keycap
+        assert_eq!(graphemes("e\u{301}x"), vec!["e\u{301}", "x"]);
+        assert_eq!(graphemes("👍🏽!"), vec!["👍🏽", "!"]);
+        assert_eq!(graphemes("👩‍💻 go"), vec!["👩‍💻", " ", "g", "o"]);
+        assert_eq!(graphemes("🇳🇿🇯🇵"), vec!["🇳🇿", "🇯🇵"]);
+        assert_eq!(graphemes("1️⃣2"), vec!["1️⃣", "2"]);
+
+        // Joining the clusters gives back the original text
+        let text = "Café ☕ with 👩‍👩‍👧 at 3️⃣ 🇳🇿 — 早上好";
+        assert_eq!(graphemes(text).concat(), text);
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("tea"), 3);
+        assert_eq!(display_width("cafe\u{301}"), 4);
+        assert_eq!(display_width("☕ tea"), 6);
+        assert_eq!(display_width("👩‍💻"), 2);
+        assert_eq!(display_width("🇳🇿"), 2);
+        assert_eq!(display_width("早上好"), 6);
+        assert_eq!(display_width("❤️"), 2);
+    }
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("Tea", 3), "Tea");
+        assert_eq!(truncate_to_width("Stretch your legs", 8), "Stretch…");
+        assert_eq!(truncate_to_width("Stretch your legs", 0), "…");
+
+        // Wide characters count double and are never split
+        assert_eq!(truncate_to_width("☕☕☕", 4), "☕…");
+        assert_eq!(truncate_to_width("☕☕☕", 5), "☕☕…");
+        assert_eq!(truncate_to_width("早上好世界", 6), "早上…");
+        assert_eq!(truncate_to_width("👩‍💻👩‍💻👩‍💻", 5), "👩‍💻👩‍💻…");
+
+        // Combining marks stay with their letter
+        let accented = "re\u{301}sume\u{301} review";
+        assert_eq!(truncate_to_width(accented, 7), "re\u{301}sume\u{301}…");
+        for max in 0..12 {
+            let short = truncate_to_width(accented, max);
+            assert!(display_width(&short) <= max.max(1), "{:?}", short);
+            assert!(!short.starts_with('\u{301}') && !short.contains(" \u{301}"));
+        }
+    }
+}